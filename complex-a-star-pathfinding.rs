@@ -1,6 +1,12 @@
 use std::collections::{HashMap, BinaryHeap};
 use std::cmp::Ordering;
 
+#[path = "rust-graph-algorithms.rs"]
+#[allow(dead_code)]
+mod graph_algorithms;
+
+use graph_algorithms::GraphError;
+
 // Terrain types with associated movement costs
 #[derive(Clone, Copy, PartialEq, Eq)]
 enum Terrain {
@@ -25,7 +31,7 @@ impl Direction {
 }
 
 // Position on the grid
-#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 struct Position {
     x: i32,
     y: i32,
@@ -132,7 +138,7 @@ fn complex_heuristic(grid: &Grid, current: &Position, goal: &Position) -> u32 {
         Terrain::Water => 5,
         Terrain::Obstacle => u32::MAX,
     };
-    let elevation_diff = (grid.get_elevation(current) - grid.get_elevation(goal)).abs() as u32;
+    let elevation_diff = (grid.get_elevation(current) - grid.get_elevation(goal)).unsigned_abs();
     let elevation_penalty = elevation_diff * 2;
 
     // Encourage moving towards areas with better terrain
@@ -162,7 +168,16 @@ fn complex_heuristic(grid: &Grid, current: &Position, goal: &Position) -> u32 {
 }
 
 // A* pathfinding algorithm
-fn a_star(grid: &Grid, start: Position, goal: Position) -> Option<Vec<Position>> {
+fn a_star(grid: &Grid, start: Position, goal: Position) -> Result<Vec<Position>, GraphError<Position>> {
+    for pos in [start, goal] {
+        if !grid.is_valid_position(&pos) {
+            return Err(GraphError::UnknownNode(pos));
+        }
+        if grid.get_terrain(&pos) == Terrain::Obstacle {
+            return Err(GraphError::BlockedNode(pos));
+        }
+    }
+
     let mut open_set = BinaryHeap::new();
     let mut came_from = HashMap::new();
     let mut g_score = HashMap::new();
@@ -174,7 +189,7 @@ fn a_star(grid: &Grid, start: Position, goal: Position) -> Option<Vec<Position>>
 
     while let Some(current) = open_set.pop() {
         if current.position == goal {
-            return Some(reconstruct_path(came_from, current.position));
+            return Ok(reconstruct_path(came_from, current.position));
         }
 
         for neighbor in grid.get_neighbors(&current.position) {
@@ -195,7 +210,7 @@ fn a_star(grid: &Grid, start: Position, goal: Position) -> Option<Vec<Position>>
         }
     }
 
-    None
+    Err(GraphError::NoPath { from: start, to: goal })
 }
 
 fn reconstruct_path(came_from: HashMap<Position, Position>, current: Position) -> Vec<Position> {
@@ -225,16 +240,39 @@ fn main() {
             } else if (x + y) % 3 == 0 {
                 grid.set_terrain(pos, Terrain::Forest);
             }
-            grid.set_elevation(pos, ((x + y) % 5) as i32);
+            grid.set_elevation(pos, (x + y) % 5);
         }
     }
 
     let start = Position::new(0, 0);
     let goal = Position::new(9, 9);
 
-    if let Some(path) = a_star(&grid, start, goal) {
-        println!("Path found: {:?}", path);
-    } else {
-        println!("No path found");
+    match a_star(&grid, start, goal) {
+        Ok(path) => println!("Path found: {:?}", path),
+        Err(e) => println!("Error: {}", e),
+    }
+
+    let path = a_star(&grid, start, goal).unwrap();
+    assert_eq!((path[0], path[path.len() - 1]), (start, goal));
+    assert!(path.windows(2).all(|step| grid.get_neighbors(&step[0]).contains(&step[1])));
+    assert!(path.iter().all(|pos| grid.get_terrain(pos) != Terrain::Obstacle));
+
+    // Error cases
+    let blocked = a_star(&grid, start, Position::new(5, 0));
+    println!("Error: {}", blocked.clone().unwrap_err());
+    assert_eq!(blocked, Err(GraphError::BlockedNode(Position::new(5, 0))));
+    let outside = a_star(&grid, start, Position::new(12, 3));
+    println!("Error: {}", outside.clone().unwrap_err());
+    assert_eq!(outside, Err(GraphError::UnknownNode(Position::new(12, 3))));
+    assert_eq!(a_star(&grid, Position::new(-1, 0), goal), Err(GraphError::UnknownNode(Position::new(-1, 0))));
+
+    // A wall across the whole grid leaves the far side unreachable
+    let mut walled = Grid::new(3, 3);
+    for y in 0..3 {
+        walled.set_terrain(Position::new(1, y), Terrain::Obstacle);
     }
+    let unreachable = a_star(&walled, Position::new(0, 0), Position::new(2, 2));
+    println!("Error: {}", unreachable.clone().unwrap_err());
+    assert_eq!(unreachable, Err(GraphError::NoPath { from: Position::new(0, 0), to: Position::new(2, 2) }));
+    println!("All error cases reported as expected");
 }
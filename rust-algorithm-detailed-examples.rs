@@ -1,6 +1,10 @@
 use std::collections::{HashMap, HashSet, VecDeque, BinaryHeap};
 use std::cmp::Ordering;
 
+#[path = "rust-state-space-search.rs"]
+#[allow(dead_code)]
+mod state_space_search;
+
 // 1. Two Pointers Technique
 fn two_pointers_examples() {
     // Example 1: Two Sum II - Input array is sorted
@@ -242,30 +246,9 @@ fn bfs_examples() {
     bfs(&graph, 2);
 
     // Example 2: Word Ladder
+    // Solved by the generic BFS in rust-state-space-search.rs
     fn ladder_length(begin_word: String, end_word: String, word_list: Vec<String>) -> i32 {
-        let mut word_set: HashSet<String> = word_list.into_iter().collect();
-        if !word_set.contains(&end_word) {
-            return 0;
-        }
-        let mut queue = VecDeque::new();
-        queue.push_back((begin_word, 1));
-        
-        while let Some((word, level)) = queue.pop_front() {
-            if word == end_word {
-                return level;
-            }
-            
-            for i in 0..word.len() {
-                let mut new_word = word.clone();
-                for c in 'a'..='z' {
-                    new_word.replace_range(i..i+1, &c.to_string());
-                    if word_set.remove(&new_word) {
-                        queue.push_back((new_word.clone(), level + 1));
-                    }
-                }
-            }
-        }
-        0
+        state_space_search::WordLadder::new(&begin_word, &end_word, word_list).length()
     }
     println!("Word Ladder Length: {}", ladder_length(
         "hit".to_string(),
@@ -1,203 +1,1282 @@
-use std::cmp::max;
+use std::cmp::{max, Ordering};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Debug;
+use std::ops::{Add, Bound, RangeBounds};
+use std::ptr::NonNull;
+
+#[path = "rust-tree-printer.rs"]
+#[allow(dead_code)]
+mod tree_printer;
+
+use tree_printer::TreeShape;
+
+// AVL height stays below 1.45 * log2(n + 2), so this many levels cover any tree that fits in memory
+const MAX_HEIGHT: usize = 96;
+
+type BoxedNode<K, V, S = ()> = Box<Node<K, V, S>>;
+type Link<K, V, S = ()> = Option<BoxedNode<K, V, S>>;
+// Remainder of a subtree together with the node detached from it
+type Detached<K, V, S> = (Link<K, V, S>, BoxedNode<K, V, S>);
+// Keys below a split key, the node holding the key if present, and keys above it
+type Split<K, V, S> = (Link<K, V, S>, Option<BoxedNode<K, V, S>>, Link<K, V, S>);
+// Subtree after an insert, the value replaced if any, and the key's rank in the subtree
+type Inserted<K, V, S> = (BoxedNode<K, V, S>, Option<V>, usize);
+
+// Summary kept for every subtree, such as the sum or maximum of its values.
+// `combine` must be associative, with `empty` as its identity.
+pub trait Augment<K, V> {
+    type Summary: Clone;
+
+    fn empty() -> Self::Summary;
+    fn single(key: &K, value: &V) -> Self::Summary;
+    fn combine(left: &Self::Summary, right: &Self::Summary) -> Self::Summary;
+}
+
+// No summary beyond the subtree sizes every node keeps
+pub struct NoAugment;
+
+impl<K, V> Augment<K, V> for NoAugment {
+    type Summary = ();
+
+    fn empty() {}
+    fn single(_key: &K, _value: &V) {}
+    fn combine(_left: &(), _right: &()) {}
+}
+
+// Sum of the values in each subtree
+pub struct SubtreeSum;
+
+impl<K, V: Copy + Default + Add<Output = V>> Augment<K, V> for SubtreeSum {
+    type Summary = V;
+
+    fn empty() -> V {
+        V::default()
+    }
+
+    fn single(_key: &K, value: &V) -> V {
+        *value
+    }
+
+    fn combine(left: &V, right: &V) -> V {
+        *left + *right
+    }
+}
+
+// Largest value in each subtree
+pub struct SubtreeMax;
+
+impl<K, V: Ord + Clone> Augment<K, V> for SubtreeMax {
+    type Summary = Option<V>;
+
+    fn empty() -> Option<V> {
+        None
+    }
+
+    fn single(_key: &K, value: &V) -> Option<V> {
+        Some(value.clone())
+    }
+
+    fn combine(left: &Option<V>, right: &Option<V>) -> Option<V> {
+        max(left.clone(), right.clone())
+    }
+}
 
 // Node structure for AVL tree
-struct Node<T: Ord> {
-    value: T,
-    left: Option<Box<Node<T>>>,
-    right: Option<Box<Node<T>>>,
+struct Node<K, V, S = ()> {
+    key: K,
+    value: V,
+    left: Link<K, V, S>,
+    right: Link<K, V, S>,
     height: i32,
+    size: usize,
+    summary: S,
 }
 
-impl<T: Ord> Node<T> {
-    fn new(value: T) -> Self {
+impl<K, V, S> Node<K, V, S> {
+    fn new(key: K, value: V, summary: S) -> Self {
         Node {
+            key,
             value,
             left: None,
             right: None,
             height: 1,
+            size: 1,
+            summary,
         }
     }
 }
 
-// AVL Tree structure
-pub struct AVLTree<T: Ord> {
-    root: Option<Box<Node<T>>>,
+impl<K, V, S> TreeShape for Node<K, V, S> {
+    fn children(&self) -> Vec<Option<&Self>> {
+        vec![self.left.as_deref(), self.right.as_deref()]
+    }
+
+    fn annotation(&self) -> String {
+        let height = |link: &Link<K, V, S>| link.as_ref().map_or(0, |n| n.height);
+        format!("h={} bf={}", self.height, height(&self.left) - height(&self.right))
+    }
 }
 
-impl<T: Ord> AVLTree<T> {
+fn above_start<K: Ord>(start: Bound<&K>, key: &K) -> bool {
+    match start {
+        Bound::Included(start) => key >= start,
+        Bound::Excluded(start) => key > start,
+        Bound::Unbounded => true,
+    }
+}
+
+fn below_end<K: Ord>(end: Bound<&K>, key: &K) -> bool {
+    match end {
+        Bound::Included(end) => key <= end,
+        Bound::Excluded(end) => key < end,
+        Bound::Unbounded => true,
+    }
+}
+
+// AVL tree map: keys kept in sorted order, each carrying a value.
+// Every node also records its subtree size for order statistics, plus an
+// optional `Augment` summary.
+pub struct AVLMap<K: Ord, V, A: Augment<K, V> = NoAugment> {
+    root: Link<K, V, A::Summary>,
+    len: usize,
+}
+
+impl<K: Ord, V, A: Augment<K, V>> Default for AVLMap<K, V, A> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Ord, V, A: Augment<K, V>> AVLMap<K, V, A> {
     pub fn new() -> Self {
-        AVLTree { root: None }
+        AVLMap { root: None, len: 0 }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
     }
 
     // Get the height of a node
-    fn height(node: &Option<Box<Node<T>>>) -> i32 {
+    fn height(node: &Link<K, V, A::Summary>) -> i32 {
         node.as_ref().map_or(0, |n| n.height)
     }
 
-    // Update the height of a node
-    fn update_height(node: &mut Box<Node<T>>) {
+    fn size(node: &Link<K, V, A::Summary>) -> usize {
+        node.as_ref().map_or(0, |n| n.size)
+    }
+
+    fn summary(node: &Link<K, V, A::Summary>) -> A::Summary {
+        node.as_ref().map_or_else(A::empty, |n| n.summary.clone())
+    }
+
+    // Recompute height, subtree size and summary of a node from its children
+    fn update(node: &mut Node<K, V, A::Summary>) {
         node.height = 1 + max(Self::height(&node.left), Self::height(&node.right));
+        node.size = 1 + Self::size(&node.left) + Self::size(&node.right);
+        let own = A::single(&node.key, &node.value);
+        node.summary = A::combine(&A::combine(&Self::summary(&node.left), &own), &Self::summary(&node.right));
     }
 
     // Get the balance factor of a node
-    fn balance_factor(node: &Box<Node<T>>) -> i32 {
+    fn balance_factor(node: &Node<K, V, A::Summary>) -> i32 {
         Self::height(&node.left) - Self::height(&node.right)
     }
 
     // Right rotation
-    fn rotate_right(mut y: Box<Node<T>>) -> Box<Node<T>> {
+    fn rotate_right(mut y: BoxedNode<K, V, A::Summary>) -> BoxedNode<K, V, A::Summary> {
+        let before = tree_printer::before_rotation(&*y);
         let mut x = y.left.take().unwrap();
         let t2 = x.right.take();
         y.left = t2;
+        Self::update(&mut y);
         x.right = Some(y);
-        
-        Self::update_height(&mut x.right.as_mut().unwrap());
-        Self::update_height(&mut x);
+        Self::update(&mut x);
+        tree_printer::after_rotation("rotate_right", before, &*x);
         x
     }
 
     // Left rotation
-    fn rotate_left(mut x: Box<Node<T>>) -> Box<Node<T>> {
+    fn rotate_left(mut x: BoxedNode<K, V, A::Summary>) -> BoxedNode<K, V, A::Summary> {
+        let before = tree_printer::before_rotation(&*x);
         let mut y = x.right.take().unwrap();
         let t2 = y.left.take();
         x.right = t2;
+        Self::update(&mut x);
         y.left = Some(x);
-        
-        Self::update_height(&mut y.left.as_mut().unwrap());
-        Self::update_height(&mut y);
+        Self::update(&mut y);
+        tree_printer::after_rotation("rotate_left", before, &*y);
         y
     }
 
-    // Insert a value into the AVL tree
-    pub fn insert(&mut self, value: T) {
-        self.root = Self::insert_rec(self.root.take(), value);
+    // Restore the AVL property at a node whose children are already balanced
+    fn rebalance(mut node: BoxedNode<K, V, A::Summary>) -> BoxedNode<K, V, A::Summary> {
+        Self::update(&mut node);
+        let balance = Self::balance_factor(&node);
+
+        if balance > 1 {
+            // Left Right Case
+            if Self::balance_factor(node.left.as_ref().unwrap()) < 0 {
+                node.left = Some(Self::rotate_left(node.left.take().unwrap()));
+            }
+            // Left Left Case
+            return Self::rotate_right(node);
+        }
+
+        if balance < -1 {
+            // Right Left Case
+            if Self::balance_factor(node.right.as_ref().unwrap()) > 0 {
+                node.right = Some(Self::rotate_right(node.right.take().unwrap()));
+            }
+            // Right Right Case
+            return Self::rotate_left(node);
+        }
+
+        node
+    }
+
+    // Insert a key-value pair, returning the previous value for the key
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        self.insert_ranked(key, value).0
     }
 
-    fn insert_rec(node: Option<Box<Node<T>>>, value: T) -> Option<Box<Node<T>>> {
+    // Insert and also return the rank the key ended up at
+    fn insert_ranked(&mut self, key: K, value: V) -> (Option<V>, usize) {
+        let (root, old, rank) = Self::insert_rec(self.root.take(), key, value);
+        self.root = Some(root);
+        if old.is_none() {
+            self.len += 1;
+        }
+        (old, rank)
+    }
+
+    // Rotations keep the in-order sequence, so the rank counted on the way down
+    // still locates the key once the path is rebalanced
+    fn insert_rec(node: Link<K, V, A::Summary>, key: K, value: V) -> Inserted<K, V, A::Summary> {
         let mut node = match node {
-            None => return Some(Box::new(Node::new(value))),
-            Some(mut node) => {
-                if value < node.value {
-                    node.left = Self::insert_rec(node.left.take(), value);
-                } else if value > node.value {
-                    node.right = Self::insert_rec(node.right.take(), value);
-                } else {
-                    return Some(node); // Value already exists
-                }
-                node
+            None => {
+                let summary = A::single(&key, &value);
+                return (Box::new(Node::new(key, value, summary)), None, 0);
             }
+            Some(node) => node,
         };
 
-        Self::update_height(&mut node);
+        let (old, rank) = match key.cmp(&node.key) {
+            Ordering::Less => {
+                let (left, old, rank) = Self::insert_rec(node.left.take(), key, value);
+                node.left = Some(left);
+                (old, rank)
+            }
+            Ordering::Greater => {
+                let skipped = Self::size(&node.left) + 1;
+                let (right, old, rank) = Self::insert_rec(node.right.take(), key, value);
+                node.right = Some(right);
+                (old, skipped + rank)
+            }
+            Ordering::Equal => {
+                let old = std::mem::replace(&mut node.value, value);
+                Self::update(&mut node);
+                let rank = Self::size(&node.left);
+                return (node, Some(old), rank);
+            }
+        };
 
-        let balance = Self::balance_factor(&node);
+        (Self::rebalance(node), old, rank)
+    }
+
+    // Remove a key, returning its value if it was present
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        self.remove_by(|node| key.cmp(&node.key))
+    }
+
+    // Remove the node that `locate` steers to; it says whether the node sought
+    // lies left of, right of or at each node on the path
+    fn remove_by(&mut self, mut locate: impl FnMut(&Node<K, V, A::Summary>) -> Ordering) -> Option<V> {
+        let (root, removed) = Self::remove_rec(self.root.take(), &mut locate);
+        self.root = root;
+        if removed.is_some() {
+            self.len -= 1;
+        }
+        removed
+    }
+
+    fn remove_rec(
+        node: Link<K, V, A::Summary>,
+        locate: &mut impl FnMut(&Node<K, V, A::Summary>) -> Ordering,
+    ) -> (Link<K, V, A::Summary>, Option<V>) {
+        let mut node = match node {
+            None => return (None, None),
+            Some(node) => node,
+        };
+
+        let removed = match locate(&node) {
+            Ordering::Less => {
+                let (left, removed) = Self::remove_rec(node.left.take(), locate);
+                node.left = left;
+                removed
+            }
+            Ordering::Greater => {
+                let (right, removed) = Self::remove_rec(node.right.take(), locate);
+                node.right = right;
+                removed
+            }
+            Ordering::Equal => {
+                // Node with only one child or no child
+                let replacement = match (node.left.take(), node.right.take()) {
+                    (None, right) => right,
+                    (left, None) => left,
+                    // Node with two children: its in-order successor takes its place
+                    (left, Some(right)) => {
+                        let (rest, mut successor) = Self::remove_min(right);
+                        successor.left = left;
+                        successor.right = rest;
+                        Some(Self::rebalance(successor))
+                    }
+                };
+                return (replacement, Some(node.value));
+            }
+        };
+
+        (Some(Self::rebalance(node)), removed)
+    }
+
+    // Detach the minimum node of a subtree, returning the rebalanced remainder and the node
+    fn remove_min(mut node: BoxedNode<K, V, A::Summary>) -> Detached<K, V, A::Summary> {
+        match node.left.take() {
+            None => (node.right.take(), node),
+            Some(left) => {
+                let (rest, min) = Self::remove_min(left);
+                node.left = rest;
+                (Some(Self::rebalance(node)), min)
+            }
+        }
+    }
+
+    // Join-based bulk operations. `join_nodes` is the only primitive that has to
+    // know about balancing; split, union, intersection and difference are built
+    // on it and run in O(m log(n/m + 1)) for trees of sizes m <= n.
+    fn from_root(root: Link<K, V, A::Summary>) -> Self {
+        AVLMap { len: Self::size(&root), root }
+    }
+
+    // Join two trees around a middle node whose key lies between them. Walks down
+    // the spine of the taller tree to a subtree of matching height, so it costs
+    // O(|height(left) - height(right)| + 1)
+    fn join_nodes(left: Link<K, V, A::Summary>, mut mid: BoxedNode<K, V, A::Summary>, right: Link<K, V, A::Summary>) -> BoxedNode<K, V, A::Summary> {
+        let (left_height, right_height) = (Self::height(&left), Self::height(&right));
+        if left_height > right_height + 1 {
+            let mut node = left.unwrap();
+            node.right = Some(Self::join_nodes(node.right.take(), mid, right));
+            Self::rebalance(node)
+        } else if right_height > left_height + 1 {
+            let mut node = right.unwrap();
+            node.left = Some(Self::join_nodes(left, mid, node.left.take()));
+            Self::rebalance(node)
+        } else {
+            mid.left = left;
+            mid.right = right;
+            Self::update(&mut mid);
+            mid
+        }
+    }
 
-        // Left Left Case
-        if balance > 1 && value < node.left.as_ref().unwrap().value {
-            return Some(Self::rotate_right(node));
+    // Join two trees whose key ranges do not overlap, using the right tree's minimum as the middle
+    fn join_links(left: Link<K, V, A::Summary>, right: Link<K, V, A::Summary>) -> Link<K, V, A::Summary> {
+        match right {
+            None => left,
+            Some(right) => {
+                let (rest, min) = Self::remove_min(right);
+                Some(Self::join_nodes(left, min, rest))
+            }
+        }
+    }
+
+    fn split_link(node: Link<K, V, A::Summary>, key: &K) -> Split<K, V, A::Summary> {
+        let mut node = match node {
+            None => return (None, None, None),
+            Some(node) => node,
+        };
+        let (left, right) = (node.left.take(), node.right.take());
+        match key.cmp(&node.key) {
+            Ordering::Equal => (left, Some(node), right),
+            Ordering::Less => {
+                let (below, found, above) = Self::split_link(left, key);
+                (below, found, Some(Self::join_nodes(above, node, right)))
+            }
+            Ordering::Greater => {
+                let (below, found, above) = Self::split_link(right, key);
+                (Some(Self::join_nodes(left, node, below)), found, above)
+            }
         }
+    }
 
-        // Right Right Case
-        if balance < -1 && value > node.right.as_ref().unwrap().value {
-            return Some(Self::rotate_left(node));
+    fn union_links(a: Link<K, V, A::Summary>, b: Link<K, V, A::Summary>) -> Link<K, V, A::Summary> {
+        let (mut a, b) = match (a, b) {
+            (None, b) => return b,
+            (a, None) => return a,
+            (Some(a), b) => (a, b),
+        };
+        let (a_left, a_right) = (a.left.take(), a.right.take());
+        let (b_left, found, b_right) = Self::split_link(b, &a.key);
+        if let Some(found) = found {
+            a.value = found.value;
         }
+        let left = Self::union_links(a_left, b_left);
+        let right = Self::union_links(a_right, b_right);
+        Some(Self::join_nodes(left, a, right))
+    }
 
-        // Left Right Case
-        if balance > 1 && value > node.left.as_ref().unwrap().value {
-            node.left = Some(Self::rotate_left(node.left.take().unwrap()));
-            return Some(Self::rotate_right(node));
+    fn intersection_links(a: Link<K, V, A::Summary>, b: Link<K, V, A::Summary>) -> Link<K, V, A::Summary> {
+        let (mut a, b) = match (a, b) {
+            (None, _) | (_, None) => return None,
+            (Some(a), b) => (a, b),
+        };
+        let (a_left, a_right) = (a.left.take(), a.right.take());
+        let (b_left, found, b_right) = Self::split_link(b, &a.key);
+        let left = Self::intersection_links(a_left, b_left);
+        let right = Self::intersection_links(a_right, b_right);
+        match found {
+            Some(_) => Some(Self::join_nodes(left, a, right)),
+            None => Self::join_links(left, right),
         }
+    }
+
+    fn difference_links(a: Link<K, V, A::Summary>, b: Link<K, V, A::Summary>) -> Link<K, V, A::Summary> {
+        let (a, mut b) = match (a, b) {
+            (None, _) => return None,
+            (a, None) => return a,
+            (a, Some(b)) => (a, b),
+        };
+        let (b_left, b_right) = (b.left.take(), b.right.take());
+        let (a_left, _, a_right) = Self::split_link(a, &b.key);
+        let left = Self::difference_links(a_left, b_left);
+        let right = Self::difference_links(a_right, b_right);
+        Self::join_links(left, right)
+    }
+
+    // Split into the entries below `key`, the value at `key` if present, and the entries above it
+    pub fn split(self, key: &K) -> (Self, Option<V>, Self) {
+        let (below, found, above) = Self::split_link(self.root, key);
+        (Self::from_root(below), found.map(|node| node.value), Self::from_root(above))
+    }
+
+    // Combine two maps and one new entry; every key in `left` must be smaller than
+    // `key` and every key in `right` larger
+    pub fn join(left: Self, key: K, value: V, right: Self) -> Self {
+        assert!(
+            left.last().is_none_or(|(k, _)| *k < key) && right.first().is_none_or(|(k, _)| key < *k),
+            "join requires left < key < right"
+        );
+        let summary = A::single(&key, &value);
+        let mid = Box::new(Node::new(key, value, summary));
+        Self::from_root(Some(Self::join_nodes(left.root, mid, right.root)))
+    }
+
+    // All entries of both maps; for keys in both, the value from `other` wins as in `extend`
+    pub fn union(self, other: Self) -> Self {
+        Self::from_root(Self::union_links(self.root, other.root))
+    }
+
+    // Entries of `self` whose keys also appear in `other`
+    pub fn intersection(self, other: Self) -> Self {
+        Self::from_root(Self::intersection_links(self.root, other.root))
+    }
+
+    // Entries of `self` whose keys do not appear in `other`
+    pub fn difference(self, other: Self) -> Self {
+        Self::from_root(Self::difference_links(self.root, other.root))
+    }
+
+    // Move every entry of `other` into `self`. When all of other's keys are larger
+    // (or all smaller) this is a single O(log n) join; otherwise it falls back to a union
+    pub fn append(&mut self, other: Self) {
+        let this = std::mem::take(self);
+        let before = match (this.last(), other.first()) {
+            (Some((last, _)), Some((first, _))) => Some(last < first),
+            _ => None,
+        };
+        let after = match (other.last(), this.first()) {
+            (Some((last, _)), Some((first, _))) => last < first,
+            _ => false,
+        };
+        *self = match before {
+            None => Self::from_root(this.root.or(other.root)),
+            Some(true) => Self::from_root(Self::join_links(this.root, other.root)),
+            Some(false) if after => Self::from_root(Self::join_links(other.root, this.root)),
+            Some(false) => this.union(other),
+        };
+    }
+
+    fn find(&self, key: &K) -> Option<&Node<K, V, A::Summary>> {
+        let mut current = self.root.as_deref();
+        while let Some(node) = current {
+            current = match key.cmp(&node.key) {
+                Ordering::Less => node.left.as_deref(),
+                Ordering::Greater => node.right.as_deref(),
+                Ordering::Equal => return Some(node),
+            };
+        }
+        None
+    }
+
+    fn find_mut(&mut self, key: &K) -> Option<&mut Node<K, V, A::Summary>> {
+        let mut current = self.root.as_deref_mut();
+        while let Some(node) = current {
+            current = match key.cmp(&node.key) {
+                Ordering::Less => node.left.as_deref_mut(),
+                Ordering::Greater => node.right.as_deref_mut(),
+                Ordering::Equal => return Some(node),
+            };
+        }
+        None
+    }
+
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self.find(key).map(|node| &node.value)
+    }
+
+    // Update a value in place, refreshing the summaries on its path
+    pub fn modify(&mut self, key: &K, f: impl FnOnce(&mut V)) -> bool {
+        Self::modify_rec(&mut self.root, key, f)
+    }
 
-        // Right Left Case
-        if balance < -1 && value < node.right.as_ref().unwrap().value {
-            node.right = Some(Self::rotate_right(node.right.take().unwrap()));
-            return Some(Self::rotate_left(node));
+    fn modify_rec(node: &mut Link<K, V, A::Summary>, key: &K, f: impl FnOnce(&mut V)) -> bool {
+        let node = match node {
+            None => return false,
+            Some(node) => node,
+        };
+        let found = match key.cmp(&node.key) {
+            Ordering::Less => Self::modify_rec(&mut node.left, key, f),
+            Ordering::Greater => Self::modify_rec(&mut node.right, key, f),
+            Ordering::Equal => {
+                f(&mut node.value);
+                true
+            }
+        };
+        if found {
+            Self::update(node);
         }
+        found
+    }
 
-        Some(node)
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.find(key).is_some()
     }
 
-    // Helper function to find the node with minimum value
-    fn min_value_node(node: &Box<Node<T>>) -> &T {
-        let mut current = node;
-        while let Some(ref left) = current.left {
+    // Smallest key
+    pub fn first(&self) -> Option<(&K, &V)> {
+        let mut current = self.root.as_deref()?;
+        while let Some(left) = current.left.as_deref() {
             current = left;
         }
-        &current.value
+        Some((&current.key, &current.value))
     }
 
-    // Delete a value from the AVL tree
-    pub fn delete(&mut self, value: &T) {
-        self.root = Self::delete_rec(self.root.take(), value);
+    // Largest key
+    pub fn last(&self) -> Option<(&K, &V)> {
+        let mut current = self.root.as_deref()?;
+        while let Some(right) = current.right.as_deref() {
+            current = right;
+        }
+        Some((&current.key, &current.value))
     }
 
-    fn delete_rec(node: Option<Box<Node<T>>>, value: &T) -> Option<Box<Node<T>>> {
-        let mut node = match node {
-            None => return None,
-            Some(mut node) => {
-                if value < &node.value {
-                    node.left = Self::delete_rec(node.left.take(), value);
-                } else if value > &node.value {
-                    node.right = Self::delete_rec(node.right.take(), value);
-                } else {
-                    // Node with only one child or no child
-                    if node.left.is_none() {
-                        return node.right;
-                    } else if node.right.is_none() {
-                        return node.left;
-                    }
+    // Greatest key less than or equal to `key`
+    pub fn floor(&self, key: &K) -> Option<(&K, &V)> {
+        let mut best = None;
+        let mut current = self.root.as_deref();
+        while let Some(node) = current {
+            match key.cmp(&node.key) {
+                Ordering::Less => current = node.left.as_deref(),
+                Ordering::Greater => {
+                    best = Some(node);
+                    current = node.right.as_deref();
+                }
+                Ordering::Equal => return Some((&node.key, &node.value)),
+            }
+        }
+        best.map(|node| (&node.key, &node.value))
+    }
+
+    // Smallest key greater than or equal to `key`
+    pub fn ceiling(&self, key: &K) -> Option<(&K, &V)> {
+        let mut best = None;
+        let mut current = self.root.as_deref();
+        while let Some(node) = current {
+            match key.cmp(&node.key) {
+                Ordering::Greater => current = node.right.as_deref(),
+                Ordering::Less => {
+                    best = Some(node);
+                    current = node.left.as_deref();
+                }
+                Ordering::Equal => return Some((&node.key, &node.value)),
+            }
+        }
+        best.map(|node| (&node.key, &node.value))
+    }
+
+    // Number of keys strictly less than `key`
+    pub fn rank(&self, key: &K) -> usize {
+        self.count_before(key, false)
+    }
+
+    // Number of keys less than `key`, or less than or equal to it when `inclusive`
+    fn count_before(&self, key: &K, inclusive: bool) -> usize {
+        let mut count = 0;
+        let mut current = self.root.as_deref();
+        while let Some(node) = current {
+            let go_right = match key.cmp(&node.key) {
+                Ordering::Less => false,
+                Ordering::Equal => inclusive,
+                Ordering::Greater => true,
+            };
+            if go_right {
+                count += Self::size(&node.left) + 1;
+                current = node.right.as_deref();
+            } else {
+                current = node.left.as_deref();
+            }
+        }
+        count
+    }
 
-                    // Node with two children
-                    let min_value = Self::min_value_node(node.right.as_ref().unwrap()).clone();
-                    node.value = min_value;
-                    node.right = Self::delete_rec(node.right.take(), &node.value);
+    // The entry with exactly `k` smaller keys (0-based k-th smallest)
+    pub fn select(&self, mut k: usize) -> Option<(&K, &V)> {
+        let mut current = self.root.as_deref();
+        while let Some(node) = current {
+            let left_size = Self::size(&node.left);
+            match k.cmp(&left_size) {
+                Ordering::Less => current = node.left.as_deref(),
+                Ordering::Equal => return Some((&node.key, &node.value)),
+                Ordering::Greater => {
+                    k -= left_size + 1;
+                    current = node.right.as_deref();
                 }
-                node
             }
+        }
+        None
+    }
+
+    // Number of keys within `range`
+    pub fn count_range<R: RangeBounds<K>>(&self, range: R) -> usize {
+        let before_start = match range.start_bound() {
+            Bound::Included(start) => self.count_before(start, false),
+            Bound::Excluded(start) => self.count_before(start, true),
+            Bound::Unbounded => 0,
         };
+        let through_end = match range.end_bound() {
+            Bound::Included(end) => self.count_before(end, true),
+            Bound::Excluded(end) => self.count_before(end, false),
+            Bound::Unbounded => self.len,
+        };
+        through_end.saturating_sub(before_start)
+    }
 
-        Self::update_height(&mut node);
+    // Combined summary of the entries within `range`, visiting O(log n) nodes
+    pub fn summarize_range<R: RangeBounds<K>>(&self, range: R) -> A::Summary {
+        Self::fold(&self.root, range.start_bound(), range.end_bound())
+    }
 
-        let balance = Self::balance_factor(&node);
+    fn fold(node: &Link<K, V, A::Summary>, start: Bound<&K>, end: Bound<&K>) -> A::Summary {
+        let node = match node {
+            None => return A::empty(),
+            Some(node) => node,
+        };
+        if let (Bound::Unbounded, Bound::Unbounded) = (start, end) {
+            return node.summary.clone();
+        }
+        if !above_start(start, &node.key) {
+            return Self::fold(&node.right, start, end);
+        }
+        if !below_end(end, &node.key) {
+            return Self::fold(&node.left, start, end);
+        }
+        let left = Self::fold(&node.left, start, Bound::Unbounded);
+        let right = Self::fold(&node.right, Bound::Unbounded, end);
+        A::combine(&A::combine(&left, &A::single(&node.key, &node.value)), &right)
+    }
+
+    // In-order iterator; call `.rev()` for descending order
+    pub fn iter(&self) -> Iter<'_, K, V, A::Summary> {
+        self.range(..)
+    }
+
+    // Iterator over the keys within `range`, in ascending order
+    pub fn range<R: RangeBounds<K>>(&self, range: R) -> Iter<'_, K, V, A::Summary> {
+        let mut front = NodeStack::new();
+        let mut current = self.root.as_deref();
+        while let Some(node) = current {
+            if above_start(range.start_bound(), &node.key) {
+                front.push(node);
+                current = node.left.as_deref();
+            } else {
+                current = node.right.as_deref();
+            }
+        }
+
+        let mut back = NodeStack::new();
+        let mut current = self.root.as_deref();
+        while let Some(node) = current {
+            if below_end(range.end_bound(), &node.key) {
+                back.push(node);
+                current = node.right.as_deref();
+            } else {
+                current = node.left.as_deref();
+            }
+        }
+
+        let finished = match (front.peek(), back.peek()) {
+            (Some(first), Some(last)) => first.key > last.key,
+            _ => true,
+        };
+        Iter { front, back, finished }
+    }
 
-        // Left Left Case
-        if balance > 1 && Self::balance_factor(node.left.as_ref().unwrap()) >= 0 {
-            return Some(Self::rotate_right(node));
+    // Checks heights and balance factors of every node, returning the tree height
+    fn check_balanced(node: &Link<K, V, A::Summary>) -> Result<i32, String> {
+        let node = match node {
+            None => return Ok(0),
+            Some(node) => node,
+        };
+        let left = Self::check_balanced(&node.left)?;
+        let right = Self::check_balanced(&node.right)?;
+        if (left - right).abs() > 1 || node.height != 1 + max(left, right) {
+            return Err(format!("node out of balance: left height {}, right height {}, stored {}", left, right, node.height));
+        }
+        if node.size != 1 + Self::size(&node.left) + Self::size(&node.right) {
+            return Err(format!("stale subtree size {}", node.size));
+        }
+        Ok(node.height)
+    }
+
+}
+
+// Debugging output: the tree drawn with heights and balance factors
+impl<K: Ord + Debug, V, A: Augment<K, V>> AVLMap<K, V, A> {
+    pub fn pretty(&self) -> String {
+        tree_printer::ascii(self.root.as_deref(), |n| format!("{:?}", n.key))
+    }
+
+    pub fn to_dot(&self) -> String {
+        tree_printer::dot(self.root.as_deref(), |n| format!("{:?}", n.key))
+    }
+
+    // Run `operation` and return its result with the subtree before and after each rotation it made
+    pub fn record_rotations<R>(&mut self, operation: impl FnOnce(&mut Self) -> R) -> (R, Vec<String>) {
+        let key = |n: &Node<K, V, A::Summary>| format!("{:?}", n.key);
+        let mut keys = HashMap::new();
+        tree_printer::collect_keys(self.root.as_deref(), &key, &mut keys);
+        let (result, rotations) = tree_printer::record(|| operation(self));
+        tree_printer::collect_keys(self.root.as_deref(), &key, &mut keys);
+        (result, tree_printer::describe(&rotations, &keys))
+    }
+}
+
+// Mutable access would bypass summary maintenance, so it is only offered without
+// augmentation; augmented maps use `modify` instead
+impl<K: Ord, V> AVLMap<K, V> {
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        self.find_mut(key).map(|node| &mut node.value)
+    }
+
+    // Value of the k-th smallest key (0-based)
+    fn select_mut(&mut self, mut k: usize) -> Option<&mut V> {
+        let mut current = self.root.as_deref_mut();
+        while let Some(node) = current {
+            let left_size = Self::size(&node.left);
+            match k.cmp(&left_size) {
+                Ordering::Less => current = node.left.as_deref_mut(),
+                Ordering::Equal => return Some(&mut node.value),
+                Ordering::Greater => {
+                    k -= left_size + 1;
+                    current = node.right.as_deref_mut();
+                }
+            }
+        }
+        None
+    }
+
+    // Returns the entry for `key` for in-place insertion or update, in one descent
+    pub fn entry(&mut self, key: K) -> Entry<'_, K, V> {
+        match self.find_mut(&key).map(NonNull::from) {
+            Some(node) => Entry::Occupied(OccupiedEntry { map: self, node }),
+            None => Entry::Vacant(VacantEntry { map: self, key }),
+        }
+    }
+}
+
+// Fixed-capacity stack of ancestors, so iterators never allocate
+struct NodeStack<'a, K, V, S> {
+    nodes: [Option<&'a Node<K, V, S>>; MAX_HEIGHT],
+    len: usize,
+}
+
+impl<'a, K, V, S> NodeStack<'a, K, V, S> {
+    fn new() -> Self {
+        NodeStack { nodes: [None; MAX_HEIGHT], len: 0 }
+    }
+
+    fn push(&mut self, node: &'a Node<K, V, S>) {
+        self.nodes[self.len] = Some(node);
+        self.len += 1;
+    }
+
+    fn pop(&mut self) -> Option<&'a Node<K, V, S>> {
+        if self.len == 0 {
+            return None;
+        }
+        self.len -= 1;
+        self.nodes[self.len].take()
+    }
+
+    fn peek(&self) -> Option<&'a Node<K, V, S>> {
+        self.len.checked_sub(1).and_then(|top| self.nodes[top])
+    }
+}
+
+// Borrowing in-order iterator. The front and back stacks walk towards each other
+// and stop once they meet on the same node.
+pub struct Iter<'a, K, V, S = ()> {
+    front: NodeStack<'a, K, V, S>,
+    back: NodeStack<'a, K, V, S>,
+    finished: bool,
+}
+
+impl<'a, K, V, S> Iterator for Iter<'a, K, V, S> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+        let node = self.front.pop()?;
+        let mut current = node.right.as_deref();
+        while let Some(next) = current {
+            self.front.push(next);
+            current = next.left.as_deref();
+        }
+        if self.back.peek().is_some_and(|last| std::ptr::eq(last, node)) {
+            self.finished = true;
+        }
+        Some((&node.key, &node.value))
+    }
+}
+
+impl<K, V, S> DoubleEndedIterator for Iter<'_, K, V, S> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+        let node = self.back.pop()?;
+        let mut current = node.left.as_deref();
+        while let Some(next) = current {
+            self.back.push(next);
+            current = next.right.as_deref();
         }
+        if self.front.peek().is_some_and(|first| std::ptr::eq(first, node)) {
+            self.finished = true;
+        }
+        Some((&node.key, &node.value))
+    }
+}
+
+pub enum Entry<'a, K: Ord, V> {
+    Occupied(OccupiedEntry<'a, K, V>),
+    Vacant(VacantEntry<'a, K, V>),
+}
+
+// Holds the map for `remove` and a pointer to the node found, which stays valid
+// while the entry borrows the map: nodes only move or drop when the map changes
+pub struct OccupiedEntry<'a, K: Ord, V> {
+    map: &'a mut AVLMap<K, V>,
+    node: NonNull<Node<K, V>>,
+}
+
+pub struct VacantEntry<'a, K: Ord, V> {
+    map: &'a mut AVLMap<K, V>,
+    key: K,
+}
 
-        // Left Right Case
-        if balance > 1 && Self::balance_factor(node.left.as_ref().unwrap()) < 0 {
-            node.left = Some(Self::rotate_left(node.left.take().unwrap()));
-            return Some(Self::rotate_right(node));
+impl<'a, K: Ord, V> Entry<'a, K, V> {
+    pub fn key(&self) -> &K {
+        match self {
+            Entry::Occupied(entry) => entry.key(),
+            Entry::Vacant(entry) => &entry.key,
         }
+    }
+
+    pub fn or_insert(self, default: V) -> &'a mut V {
+        self.or_insert_with(|| default)
+    }
 
-        // Right Right Case
-        if balance < -1 && Self::balance_factor(node.right.as_ref().unwrap()) <= 0 {
-            return Some(Self::rotate_left(node));
+    pub fn or_insert_with(self, default: impl FnOnce() -> V) -> &'a mut V {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(default()),
         }
+    }
+
+    pub fn or_default(self) -> &'a mut V
+    where
+        V: Default,
+    {
+        self.or_insert_with(V::default)
+    }
 
-        // Right Left Case
-        if balance < -1 && Self::balance_factor(node.right.as_ref().unwrap()) > 0 {
-            node.right = Some(Self::rotate_right(node.right.take().unwrap()));
-            return Some(Self::rotate_left(node));
+    pub fn and_modify(mut self, f: impl FnOnce(&mut V)) -> Self {
+        if let Entry::Occupied(entry) = &mut self {
+            f(entry.get_mut());
         }
+        self
+    }
+}
+
+impl<'a, K: Ord, V> OccupiedEntry<'a, K, V> {
+    fn node(&self) -> &Node<K, V> {
+        // SAFETY: the node is alive and not otherwise borrowed while the entry holds the map
+        unsafe { self.node.as_ref() }
+    }
+
+    pub fn key(&self) -> &K {
+        &self.node().key
+    }
+
+    pub fn get(&self) -> &V {
+        &self.node().value
+    }
+
+    pub fn get_mut(&mut self) -> &mut V {
+        // SAFETY: as in `node`, and `&mut self` makes this the only access
+        unsafe { &mut self.node.as_mut().value }
+    }
+
+    pub fn into_mut(mut self) -> &'a mut V {
+        // SAFETY: the map stays borrowed for 'a, so nothing else can reach the node
+        unsafe { &mut self.node.as_mut().value }
+    }
+
+    pub fn insert(&mut self, value: V) -> V {
+        std::mem::replace(self.get_mut(), value)
+    }
+
+    // The node's own key cannot stay borrowed while the tree is restructured,
+    // so the descent is steered by the node's rank instead
+    pub fn remove(self) -> V {
+        let mut index = self.map.rank(self.key());
+        self.map
+            .remove_by(|node| {
+                let left = AVLMap::<K, V>::size(&node.left);
+                let side = index.cmp(&left);
+                if side == Ordering::Greater {
+                    index -= left + 1;
+                }
+                side
+            })
+            .unwrap()
+    }
+}
+
+impl<'a, K: Ord, V> VacantEntry<'a, K, V> {
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+
+    // The key moves into the tree, so the new value is found again by its rank
+    pub fn insert(self, value: V) -> &'a mut V {
+        let (_, rank) = self.map.insert_ranked(self.key, value);
+        self.map.select_mut(rank).unwrap()
+    }
+}
+
+// AVL Tree structure: a set of values, stored as an AVLMap with unit values
+pub struct AVLTree<T: Ord> {
+    map: AVLMap<T, ()>,
+}
+
+impl<T: Ord> Default for AVLTree<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Ord> AVLTree<T> {
+    pub fn new() -> Self {
+        AVLTree { map: AVLMap::new() }
+    }
+
+    // Insert a value into the AVL tree
+    pub fn insert(&mut self, value: T) {
+        self.map.insert(value, ());
+    }
+
+    // Delete a value from the AVL tree
+    pub fn delete(&mut self, value: &T) {
+        self.map.remove(value);
+    }
 
-        Some(node)
+    pub fn contains(&self, value: &T) -> bool {
+        self.map.contains_key(value)
     }
 
     // In-order traversal of the AVL tree
     pub fn inorder(&self) -> Vec<&T> {
-        let mut result = Vec::new();
-        Self::inorder_rec(&self.root, &mut result);
-        result
+        self.map.iter().map(|(value, _)| value).collect()
+    }
+
+    // Number of stored values smaller than `value`
+    pub fn rank(&self, value: &T) -> usize {
+        self.map.rank(value)
+    }
+
+    // The k-th smallest value, counting from 0
+    pub fn select(&self, k: usize) -> Option<&T> {
+        self.map.select(k).map(|(value, _)| value)
+    }
+
+    // Number of stored values in lo..=hi
+    pub fn count_range(&self, lo: &T, hi: &T) -> usize {
+        self.map.count_range((Bound::Included(lo), Bound::Included(hi)))
+    }
+
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    // Values below `value`, whether `value` was present, and values above it
+    pub fn split(self, value: &T) -> (Self, bool, Self) {
+        let (below, found, above) = self.map.split(value);
+        (AVLTree { map: below }, found.is_some(), AVLTree { map: above })
+    }
+
+    // Every value in `left` must be smaller than `value`, and every value in `right` larger
+    pub fn join(left: Self, value: T, right: Self) -> Self {
+        AVLTree { map: AVLMap::join(left.map, value, (), right.map) }
+    }
+
+    pub fn union(self, other: Self) -> Self {
+        AVLTree { map: self.map.union(other.map) }
+    }
+
+    pub fn intersection(self, other: Self) -> Self {
+        AVLTree { map: self.map.intersection(other.map) }
+    }
+
+    pub fn difference(self, other: Self) -> Self {
+        AVLTree { map: self.map.difference(other.map) }
+    }
+
+    // Concatenate in O(log n) when the value ranges do not overlap
+    pub fn append(&mut self, other: Self) {
+        self.map.append(other.map);
+    }
+}
+
+impl<T: Ord + Debug> AVLTree<T> {
+    pub fn pretty(&self) -> String {
+        self.map.pretty()
+    }
+
+    pub fn to_dot(&self) -> String {
+        self.map.to_dot()
+    }
+
+    // Run `operation` and return the subtree before and after each rotation it made
+    pub fn record_rotations(&mut self, operation: impl FnOnce(&mut Self)) -> Vec<String> {
+        let mut map = std::mem::take(&mut self.map);
+        let ((), steps) = map.record_rotations(|map| {
+            let mut tree = AVLTree { map: std::mem::take(map) };
+            operation(&mut tree);
+            *map = tree.map;
+        });
+        self.map = map;
+        steps
+    }
+}
+
+// Small xorshift generator for the randomized comparison below
+struct XorShift(u64);
+
+impl XorShift {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+}
+
+// Differential check: replays random operations against std's BTreeMap
+fn compare_with_btreemap(operations: usize, seed: u64) {
+    let mut rng = XorShift(seed);
+    let mut avl: AVLMap<u64, u64> = AVLMap::new();
+    let mut reference: BTreeMap<u64, u64> = BTreeMap::new();
+    let mut sums: AVLMap<u64, u64, SubtreeSum> = AVLMap::new();
+
+    for step in 0..operations {
+        let key = rng.next() % 512;
+        let value = rng.next();
+        match rng.next() % 8 {
+            0..=2 => {
+                let value = value % 1000;
+                sums.insert(key, value);
+                assert_eq!(avl.insert(key, value), reference.insert(key, value));
+            }
+            3 | 4 => {
+                sums.remove(&key);
+                assert_eq!(avl.remove(&key), reference.remove(&key));
+            }
+            5 => match avl.entry(key) {
+                Entry::Occupied(entry) if value.is_multiple_of(3) => {
+                    sums.remove(&key);
+                    assert_eq!(Some(entry.remove()), reference.remove(&key));
+                }
+                entry => {
+                    *entry.or_insert(0) += value % 100;
+                    *reference.entry(key).or_insert(0) += value % 100;
+                    if !sums.modify(&key, |v| *v += value % 100) {
+                        sums.insert(key, value % 100);
+                    }
+                }
+            },
+            6 => {
+                let value = value % 1000;
+                if let Some(v) = avl.get_mut(&key) {
+                    *v = value;
+                }
+                if let Some(v) = reference.get_mut(&key) {
+                    *v = value;
+                }
+                sums.modify(&key, |v| *v = value);
+            }
+            _ => {
+                let (lo, hi) = (key.min(value % 512), key.max(value % 512));
+                assert!(avl.range(lo..hi).eq(reference.range(lo..hi)));
+                assert!(avl.range(lo..=hi).rev().eq(reference.range(lo..=hi).rev()));
+                assert!(avl.range((Bound::Excluded(lo), Bound::Unbounded)).eq(reference.range((Bound::Excluded(lo), Bound::Unbounded))));
+                assert_eq!(avl.count_range(lo..hi), reference.range(lo..hi).count());
+                assert_eq!(sums.summarize_range(lo..=hi), reference.range(lo..=hi).map(|(_, v)| v).sum::<u64>());
+            }
+        }
+
+        assert_eq!(avl.rank(&key), reference.range(..key).count());
+        let k = (value % 600) as usize;
+        assert_eq!(avl.select(k), reference.iter().nth(k));
+
+        assert_eq!(avl.len(), reference.len());
+        assert_eq!(avl.get(&key), reference.get(&key));
+        assert_eq!(avl.first(), reference.first_key_value());
+        assert_eq!(avl.last(), reference.last_key_value());
+        assert_eq!(avl.floor(&key), reference.range(..=key).next_back());
+        assert_eq!(avl.ceiling(&key), reference.range(key..).next());
+        if step % 64 == 0 {
+            AVLMap::<u64, u64>::check_balanced(&avl.root).unwrap();
+            AVLMap::<u64, u64, SubtreeSum>::check_balanced(&sums.root).unwrap();
+            assert!(avl.iter().eq(reference.iter()));
+            assert!(avl.iter().rev().eq(reference.iter().rev()));
+        }
     }
+}
+
+fn build<A: Augment<u64, u64>>(model: &BTreeMap<u64, u64>) -> AVLMap<u64, u64, A> {
+    let mut map = AVLMap::new();
+    for (&k, &v) in model {
+        map.insert(k, v);
+    }
+    map
+}
+
+fn check_against(map: &AVLMap<u64, u64, SubtreeSum>, model: &BTreeMap<u64, u64>) {
+    AVLMap::<u64, u64, SubtreeSum>::check_balanced(&map.root).unwrap();
+    assert_eq!(map.len(), model.len());
+    assert!(map.iter().eq(model.iter()));
+    assert_eq!(map.summarize_range(..), model.values().sum::<u64>());
+}
+
+// Split, join, union, intersection, difference and append on random maps of very
+// different sizes, checked against BTreeMap and for balance, sizes and summaries
+fn set_algebra_check(rounds: usize, seed: u64) {
+    let mut rng = XorShift(seed);
+    for _ in 0..rounds {
+        let universe = 1 + rng.next() % 5000;
+        let random_model = |rng: &mut XorShift| -> BTreeMap<u64, u64> {
+            let n = [0, 1, 10, 100, 2000][(rng.next() % 5) as usize];
+            (0..n).map(|_| (rng.next() % universe, rng.next() % 1000)).collect()
+        };
+        let a = random_model(&mut rng);
+        let b = random_model(&mut rng);
+        let key = rng.next() % universe;
+
+        let union = build::<SubtreeSum>(&a).union(build(&b));
+        let mut expected = a.clone();
+        expected.extend(b.iter());
+        check_against(&union, &expected);
+
+        let intersection = build::<SubtreeSum>(&a).intersection(build(&b));
+        let expected: BTreeMap<u64, u64> = a.iter().filter(|(k, _)| b.contains_key(k)).map(|(&k, &v)| (k, v)).collect();
+        check_against(&intersection, &expected);
+
+        let difference = build::<SubtreeSum>(&a).difference(build(&b));
+        let expected: BTreeMap<u64, u64> = a.iter().filter(|(k, _)| !b.contains_key(k)).map(|(&k, &v)| (k, v)).collect();
+        check_against(&difference, &expected);
+
+        let (below, found, above) = build::<SubtreeSum>(&a).split(&key);
+        check_against(&below, &a.range(..key).map(|(&k, &v)| (k, v)).collect());
+        check_against(&above, &a.range(key + 1..).map(|(&k, &v)| (k, v)).collect());
+        assert_eq!(found, a.get(&key).copied());
 
-    fn inorder_rec(node: &Option<Box<Node<T>>>, result: &mut Vec<&T>) {
-        if let Some(node) = node {
-            Self::inorder_rec(&node.left, result);
-            result.push(&node.value);
-            Self::inorder_rec(&node.right, result);
+        let rejoined = AVLMap::join(below, key, 7, above);
+        let mut expected = a.clone();
+        expected.insert(key, 7);
+        check_against(&rejoined, &expected);
+
+        // Non-overlapping append in both directions, then an overlapping one
+        let (below, _, above) = build::<SubtreeSum>(&a).split(&key);
+        let (mut low, mut high) = (below, above);
+        if rng.next().is_multiple_of(2) {
+            low.append(high);
+            check_against(&low, &a.iter().filter(|(&k, _)| k != key).map(|(&k, &v)| (k, v)).collect());
+        } else {
+            high.append(low);
+            check_against(&high, &a.iter().filter(|(&k, _)| k != key).map(|(&k, &v)| (k, v)).collect());
         }
+        let mut appended = build::<SubtreeSum>(&a);
+        appended.append(build(&b));
+        let mut expected = a.clone();
+        expected.extend(b.iter());
+        check_against(&appended, &expected);
+    }
+}
+
+// Merging a small set into a large one only touches O(m log(n/m + 1)) nodes
+fn bulk_benchmark() {
+    let large: Vec<u64> = (0..200_000).map(|i| i * 2).collect();
+    for small_len in [100u64, 10_000, 200_000] {
+        let small: Vec<u64> = (0..small_len).map(|i| i * (400_000 / small_len) + 1).collect();
+
+        let mut by_insert = AVLTree::new();
+        large.iter().for_each(|&v| by_insert.insert(v));
+        let mut other = AVLTree::new();
+        small.iter().for_each(|&v| other.insert(v));
+        let mut joined = AVLTree::new();
+        large.iter().for_each(|&v| joined.insert(v));
+
+        let start = std::time::Instant::now();
+        small.iter().for_each(|&v| by_insert.insert(v));
+        let insert_time = start.elapsed();
+
+        let start = std::time::Instant::now();
+        let joined = joined.union(other);
+        let union_time = start.elapsed();
+
+        assert_eq!(joined.len(), by_insert.len());
+        println!("Merge {:>6} into 200000: insert one by one {:>10.2?}, union {:>10.2?}", small_len, insert_time, union_time);
     }
 }
 
@@ -219,4 +1298,88 @@ fn main() {
 
     println!("Inorder traversal after deletions:");
     println!("{:?}", avl_tree.inorder());
+
+    // Debugging output: the tree itself, and the double rotation behind one insert
+    print!("\nTree after deletions:\n{}", avl_tree.pretty());
+    for step in avl_tree.record_rotations(|tree| tree.insert(15)) {
+        print!("\nInserting 15 ran {}", step);
+    }
+    println!("\nGraphviz:\n{}", avl_tree.to_dot());
+
+    // Ordered map API
+    let mut scores = AVLMap::new();
+    for (name, score) in [("carol", 72), ("alice", 91), ("eve", 64), ("bob", 85), ("dave", 78)] {
+        scores.insert(name, score);
+    }
+    *scores.entry("bob").or_insert(0) += 5;
+    scores.entry("frank").or_insert(55);
+    if let Entry::Occupied(entry) = scores.entry("eve") {
+        assert_eq!(entry.remove(), 64);
+    }
+    assert_eq!(scores.get(&"eve"), None);
+
+    // Entries need no Clone on the key
+    #[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+    struct Ticket(u32);
+    let mut queue: AVLMap<Ticket, &str> = AVLMap::new();
+    for (id, name) in [(7, "g"), (3, "c"), (9, "i"), (1, "a"), (5, "e")] {
+        assert_eq!(*queue.entry(Ticket(id)).or_insert(name), name);
+    }
+    *queue.entry(Ticket(5)).or_insert("x") = "E";
+    match queue.entry(Ticket(3)) {
+        Entry::Occupied(entry) => assert_eq!(entry.remove(), "c"),
+        Entry::Vacant(_) => panic!("ticket 3 was inserted above"),
+    }
+    AVLMap::<Ticket, &str>::check_balanced(&queue.root).unwrap();
+    assert!(queue.iter().map(|(t, n)| (t.0, *n)).eq([(1, "a"), (5, "E"), (7, "g"), (9, "i")]));
+    println!("\nScores: {:?}", scores.iter().collect::<Vec<_>>());
+    println!("Reverse: {:?}", scores.iter().rev().map(|(name, _)| *name).collect::<Vec<_>>());
+    println!("Range b..=d: {:?}", scores.range("b"..="d").collect::<Vec<_>>());
+    println!("First: {:?}, Last: {:?}", scores.first(), scores.last());
+    println!("Floor of \"cat\": {:?}, Ceiling of \"cat\": {:?}", scores.floor(&"cat"), scores.ceiling(&"cat"));
+
+    // Order statistics: a leaderboard of scores
+    let mut leaderboard = AVLTree::new();
+    for score in [420, 990, 150, 870, 610, 330, 760] {
+        leaderboard.insert(score);
+    }
+    println!("\nScores below 610: {}", leaderboard.rank(&610));
+    println!("Third lowest score: {:?}", leaderboard.select(2));
+    println!("Scores in 300..=800: {}", leaderboard.count_range(&300, &800));
+
+    // Augmented maps: per-subtree sums and maxima answer range queries in O(log n)
+    let mut sales: AVLMap<u32, u64, SubtreeSum> = AVLMap::new();
+    let mut peaks: AVLMap<u32, u64, SubtreeMax> = AVLMap::new();
+    for (day, amount) in [(1, 120), (2, 80), (3, 310), (4, 45), (5, 200), (6, 95)] {
+        sales.insert(day, amount);
+        peaks.insert(day, amount);
+    }
+    sales.modify(&4, |amount| *amount += 100);
+    println!("Sales on days 2..=5: {}", sales.summarize_range(2..=5));
+    println!("Peak sale on days 4..: {:?}", peaks.summarize_range(4..));
+
+    // Bulk operations on whole trees
+    let evens: AVLTree<u32> = (0..20).step_by(2).fold(AVLTree::new(), |mut t, v| { t.insert(v); t });
+    let threes: AVLTree<u32> = (0..20).step_by(3).fold(AVLTree::new(), |mut t, v| { t.insert(v); t });
+    let (low, found, high) = evens.split(&10);
+    println!("\nSplit evens at 10: {:?}, found {}, {:?}", low.inorder(), found, high.inorder());
+    let mut evens = AVLTree::join(low, 10, high);
+    let mut tail = AVLTree::new();
+    tail.insert(40);
+    tail.insert(50);
+    evens.append(tail);
+    println!("Rejoined and appended: {:?}", evens.inorder());
+    let both = evens.union(threes);
+    println!("Union with multiples of 3: {:?}", both.inorder());
+    let mut sixes = AVLTree::new();
+    for v in [0, 6, 12, 18, 24] {
+        sixes.insert(v);
+    }
+    println!("Without multiples of 6: {:?}", both.difference(sixes).inorder());
+
+    compare_with_btreemap(20_000, 0x2545F4914F6CDD1D);
+    println!("\nAVLMap matches BTreeMap over 20000 random operations");
+    set_algebra_check(300, 0x9E3779B97F4A7C15);
+    println!("Split, join, union, intersection, difference and append match BTreeMap over 300 random pairs");
+    bulk_benchmark();
 }
@@ -1,134 +1,668 @@
-use std::fmt::Debug;
+use std::collections::BTreeMap as StdBTreeMap;
 use std::cmp::Ordering;
+use std::fmt::Debug;
+use std::mem;
+use std::ops::{Bound, RangeBounds, RangeFull};
+
+#[path = "rust-tree-printer.rs"]
+#[allow(dead_code)]
+mod tree_printer;
 
-const B: usize = 6; // B is the minimum degree of the B-tree
-const MAX_KEYS: usize = 2 * B - 1;
-const MIN_KEYS: usize = B - 1;
+use tree_printer::TreeShape;
 
-#[derive(Debug)]
-struct Node<T: Ord + Debug> {
-    keys: Vec<T>,
-    children: Vec<Node<T>>,
+// B is the minimum degree of the B-tree: every node except the root holds between
+// B - 1 and 2B - 1 keys, and every internal node has one more child than keys
+#[derive(Debug, Clone)]
+struct Node<K, V> {
+    keys: Vec<K>,
+    values: Vec<V>,
+    children: Vec<Node<K, V>>,
     leaf: bool,
 }
 
-impl<T: Ord + Debug> Node<T> {
-    fn new(leaf: bool) -> Self {
+impl<K, V> Node<K, V> {
+    fn new(leaf: bool, max_keys: usize) -> Self {
         Node {
-            keys: Vec::with_capacity(MAX_KEYS),
-            children: if leaf { Vec::new() } else { Vec::with_capacity(MAX_KEYS + 1) },
+            keys: Vec::with_capacity(max_keys),
+            values: Vec::with_capacity(max_keys),
+            children: if leaf { Vec::new() } else { Vec::with_capacity(max_keys + 1) },
             leaf,
         }
     }
 }
 
-pub struct BTree<T: Ord + Debug> {
-    root: Node<T>,
+impl<K, V> TreeShape for Node<K, V> {
+    fn children(&self) -> Vec<Option<&Self>> {
+        self.children.iter().map(Some).collect()
+    }
+
+    fn annotation(&self) -> String {
+        String::new()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct BTreeMap<K, V, const B: usize = 6> {
+    root: Node<K, V>,
+    len: usize,
+}
+
+impl<K: Ord, V, const B: usize> Default for BTreeMap<K, V, B> {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
-impl<T: Ord + Debug> BTree<T> {
+impl<K: Ord, V, const B: usize> BTreeMap<K, V, B> {
+    const MAX_KEYS: usize = 2 * B - 1;
+    const MIN_KEYS: usize = B - 1;
+
     pub fn new() -> Self {
-        BTree {
-            root: Node::new(true),
+        const { assert!(B >= 2, "the minimum degree of a B-tree must be at least 2") };
+        BTreeMap {
+            root: Node::new(true, Self::MAX_KEYS),
+            len: 0,
         }
     }
 
-    pub fn insert(&mut self, key: T) {
-        let root = &mut self.root;
-        if root.keys.len() == MAX_KEYS {
-            let mut new_root = Node::new(false);
-            new_root.children.push(std::mem::replace(root, Node::new(true)));
-            Self::split_child(&mut new_root, 0);
-            self.root = new_root;
-            Self::insert_non_full(&mut self.root, key);
-        } else {
-            Self::insert_non_full(root, key);
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    // Number of edges from the root down to the leaves
+    pub fn height(&self) -> usize {
+        let mut height = 0;
+        let mut node = &self.root;
+        while !node.leaf {
+            node = &node.children[0];
+            height += 1;
         }
+        height
     }
 
-    fn insert_non_full(node: &mut Node<T>, key: T) {
-        let mut i = node.keys.len();
-        if node.leaf {
-            node.keys.push(key);
-            while i > 0 && key < node.keys[i - 1] {
-                node.keys.swap(i, i - 1);
-                i -= 1;
-            }
-        } else {
-            while i > 0 && key < node.keys[i - 1] {
-                i -= 1;
+    // Insert a key/value pair, returning the previous value for the key
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        if self.root.keys.len() == Self::MAX_KEYS {
+            let old_root = mem::replace(&mut self.root, Node::new(false, Self::MAX_KEYS));
+            self.root.children.push(old_root);
+            Self::split_child(&mut self.root, 0);
+        }
+        let old = Self::insert_non_full(&mut self.root, key, value);
+        if old.is_none() {
+            self.len += 1;
+        }
+        old
+    }
+
+    fn insert_non_full(node: &mut Node<K, V>, key: K, value: V) -> Option<V> {
+        match node.keys.binary_search(&key) {
+            Ok(i) => Some(mem::replace(&mut node.values[i], value)),
+            Err(i) if node.leaf => {
+                node.keys.insert(i, key);
+                node.values.insert(i, value);
+                None
             }
-            if node.children[i].keys.len() == MAX_KEYS {
-                Self::split_child(node, i);
-                if key > node.keys[i] {
-                    i += 1;
+            Err(mut i) => {
+                if node.children[i].keys.len() == Self::MAX_KEYS {
+                    Self::split_child(node, i);
+                    match key.cmp(&node.keys[i]) {
+                        Ordering::Equal => return Some(mem::replace(&mut node.values[i], value)),
+                        Ordering::Greater => i += 1,
+                        Ordering::Less => {}
+                    }
                 }
+                Self::insert_non_full(&mut node.children[i], key, value)
             }
-            Self::insert_non_full(&mut node.children[i], key);
         }
     }
 
-    fn split_child(parent: &mut Node<T>, index: usize) {
+    fn split_child(parent: &mut Node<K, V>, index: usize) {
         let child = &mut parent.children[index];
-        let mut new_child = Node::new(child.leaf);
-        
-        // Move the upper half of the keys to the new child
-        new_child.keys = child.keys.split_off(MIN_KEYS);
-        
+        let mut new_child = Node::new(child.leaf, Self::MAX_KEYS);
+
+        // Move the upper half of the keys to the new child, leaving the median last in child
+        new_child.keys = child.keys.split_off(B);
+        new_child.values = child.values.split_off(B);
+
         // If not a leaf, move the corresponding children as well
         if !child.leaf {
-            new_child.children = child.children.split_off(MIN_KEYS + 1);
+            new_child.children = child.children.split_off(B);
         }
-        
+
         // Insert the middle key into the parent
         parent.keys.insert(index, child.keys.pop().unwrap());
-        
+        parent.values.insert(index, child.values.pop().unwrap());
+
         // Insert the new child into the parent's children
         parent.children.insert(index + 1, new_child);
     }
 
-    pub fn search(&self, key: &T) -> bool {
-        Self::search_node(&self.root, key)
+    pub fn get(&self, key: &K) -> Option<&V> {
+        let mut node = &self.root;
+        loop {
+            match node.keys.binary_search(key) {
+                Ok(i) => return Some(&node.values[i]),
+                Err(_) if node.leaf => return None,
+                Err(i) => node = &node.children[i],
+            }
+        }
     }
 
-    fn search_node(node: &Node<T>, key: &T) -> bool {
-        let mut i = 0;
-        while i < node.keys.len() && key > &node.keys[i] {
-            i += 1;
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        let mut node = &mut self.root;
+        loop {
+            match node.keys.binary_search(key) {
+                Ok(i) => return Some(&mut node.values[i]),
+                Err(_) if node.leaf => return None,
+                Err(i) => node = &mut node.children[i],
+            }
         }
-        if i < node.keys.len() && key == &node.keys[i] {
-            true
-        } else if node.leaf {
-            false
+    }
+
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.get(key).is_some()
+    }
+
+    // Remove a key, returning its value. Follows CLRS: the descent only enters a
+    // child that has at least B keys, borrowing from or merging with a sibling
+    // first, so a single top-down pass never has to back up to fix an underflow
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        let removed = Self::remove_from(&mut self.root, key);
+        self.shrink_root();
+        removed.map(|(_, value)| {
+            self.len -= 1;
+            value
+        })
+    }
+
+    pub fn pop_first(&mut self) -> Option<(K, V)> {
+        if self.is_empty() {
+            return None;
+        }
+        let entry = Self::remove_min(&mut self.root);
+        self.shrink_root();
+        self.len -= 1;
+        Some(entry)
+    }
+
+    pub fn pop_last(&mut self) -> Option<(K, V)> {
+        if self.is_empty() {
+            return None;
+        }
+        let entry = Self::remove_max(&mut self.root);
+        self.shrink_root();
+        self.len -= 1;
+        Some(entry)
+    }
+
+    // A merge at the root can leave it with no keys and a single child
+    fn shrink_root(&mut self) {
+        if self.root.keys.is_empty() && !self.root.leaf {
+            self.root = self.root.children.pop().unwrap();
+        }
+    }
+
+    fn remove_from(node: &mut Node<K, V>, key: &K) -> Option<(K, V)> {
+        match node.keys.binary_search(key) {
+            // Case 1: the key is in a leaf
+            Ok(i) if node.leaf => Some((node.keys.remove(i), node.values.remove(i))),
+            // Case 2a: replace the key with its predecessor from a child with a spare key
+            Ok(i) if node.children[i].keys.len() > Self::MIN_KEYS => {
+                let (k, v) = Self::remove_max(&mut node.children[i]);
+                Some(Self::replace_entry(node, i, k, v))
+            }
+            // Case 2b: or with its successor
+            Ok(i) if node.children[i + 1].keys.len() > Self::MIN_KEYS => {
+                let (k, v) = Self::remove_min(&mut node.children[i + 1]);
+                Some(Self::replace_entry(node, i, k, v))
+            }
+            // Case 2c: both neighbours are minimal, so merge them around the key and recurse
+            Ok(i) => {
+                Self::merge_children(node, i);
+                Self::remove_from(&mut node.children[i], key)
+            }
+            Err(_) if node.leaf => None,
+            // Case 3: make sure the child we descend into can lose a key
+            Err(i) => {
+                let i = Self::fill_child(node, i);
+                Self::remove_from(&mut node.children[i], key)
+            }
+        }
+    }
+
+    fn replace_entry(node: &mut Node<K, V>, i: usize, key: K, value: V) -> (K, V) {
+        (mem::replace(&mut node.keys[i], key), mem::replace(&mut node.values[i], value))
+    }
+
+    fn remove_min(node: &mut Node<K, V>) -> (K, V) {
+        if node.leaf {
+            return (node.keys.remove(0), node.values.remove(0));
+        }
+        let i = Self::fill_child(node, 0);
+        Self::remove_min(&mut node.children[i])
+    }
+
+    fn remove_max(node: &mut Node<K, V>) -> (K, V) {
+        if node.leaf {
+            return (node.keys.pop().unwrap(), node.values.pop().unwrap());
+        }
+        let i = Self::fill_child(node, node.children.len() - 1);
+        Self::remove_max(&mut node.children[i])
+    }
+
+    // Case 3a/3b: give children[i] at least B keys, returning the index of the child
+    // that now covers the same key range (it moves left after merging with a left sibling)
+    fn fill_child(node: &mut Node<K, V>, i: usize) -> usize {
+        if node.children[i].keys.len() > Self::MIN_KEYS {
+            i
+        } else if i > 0 && node.children[i - 1].keys.len() > Self::MIN_KEYS {
+            Self::borrow_from_left(node, i);
+            i
+        } else if i + 1 < node.children.len() && node.children[i + 1].keys.len() > Self::MIN_KEYS {
+            Self::borrow_from_right(node, i);
+            i
+        } else if i + 1 < node.children.len() {
+            Self::merge_children(node, i);
+            i
         } else {
-            Self::search_node(&node.children[i], key)
+            Self::merge_children(node, i - 1);
+            i - 1
         }
     }
 
-    pub fn traverse(&self) -> Vec<&T> {
-        let mut result = Vec::new();
-        Self::traverse_node(&self.root, &mut result);
-        result
+    // Rotate the separator down into children[i] and the left sibling's last key up
+    fn borrow_from_left(node: &mut Node<K, V>, i: usize) {
+        let (left, right) = node.children.split_at_mut(i);
+        let (sibling, child) = (&mut left[i - 1], &mut right[0]);
+        let key = mem::replace(&mut node.keys[i - 1], sibling.keys.pop().unwrap());
+        let value = mem::replace(&mut node.values[i - 1], sibling.values.pop().unwrap());
+        child.keys.insert(0, key);
+        child.values.insert(0, value);
+        if !child.leaf {
+            child.children.insert(0, sibling.children.pop().unwrap());
+        }
+    }
+
+    // Rotate the separator down into children[i] and the right sibling's first key up
+    fn borrow_from_right(node: &mut Node<K, V>, i: usize) {
+        let (left, right) = node.children.split_at_mut(i + 1);
+        let (child, sibling) = (&mut left[i], &mut right[0]);
+        let key = mem::replace(&mut node.keys[i], sibling.keys.remove(0));
+        let value = mem::replace(&mut node.values[i], sibling.values.remove(0));
+        child.keys.push(key);
+        child.values.push(value);
+        if !child.leaf {
+            child.children.push(sibling.children.remove(0));
+        }
+    }
+
+    // Merge children[i], keys[i] and children[i + 1] into children[i]
+    fn merge_children(node: &mut Node<K, V>, i: usize) {
+        let right = node.children.remove(i + 1);
+        let key = node.keys.remove(i);
+        let value = node.values.remove(i);
+        let child = &mut node.children[i];
+        child.keys.push(key);
+        child.values.push(value);
+        child.keys.extend(right.keys);
+        child.values.extend(right.values);
+        child.children.extend(right.children);
+    }
+
+    // Build a tree from entries sorted by strictly increasing key in O(n), without
+    // any splits: each subtree's keys are divided as evenly as possible among the
+    // fewest children that fit, which keeps every node within its key bounds
+    pub fn from_sorted(entries: impl IntoIterator<Item = (K, V)>) -> Self {
+        let entries: Vec<(K, V)> = entries.into_iter().collect();
+        assert!(
+            entries.windows(2).all(|w| w[0].0 < w[1].0),
+            "from_sorted requires strictly increasing keys"
+        );
+        let len = entries.len();
+        let mut height = 0;
+        while Self::max_keys_at(height) < len {
+            height += 1;
+        }
+        let mut entries = entries.into_iter();
+        let root = Self::build(&mut entries, len, height, true);
+        BTreeMap { root, len }
+    }
+
+    // Capacity of a completely full subtree of the given height: (2B)^(height + 1) - 1
+    fn max_keys_at(height: usize) -> usize {
+        (0..=height).fold(1usize, |acc, _| acc.saturating_mul(2 * B)) - 1
+    }
+
+    fn build(entries: &mut impl Iterator<Item = (K, V)>, count: usize, height: usize, is_root: bool) -> Node<K, V> {
+        let mut node = Node::new(height == 0, Self::MAX_KEYS);
+        if height == 0 {
+            for (k, v) in entries.take(count) {
+                node.keys.push(k);
+                node.values.push(v);
+            }
+            return node;
+        }
+
+        let child_capacity = Self::max_keys_at(height - 1) + 1;
+        let min_children = if is_root { 2 } else { B };
+        let children = (count + 1).div_ceil(child_capacity).max(min_children);
+        let per_child = (count - (children - 1)) / children;
+        let extra = (count - (children - 1)) % children;
+
+        for c in 0..children {
+            let size = per_child + usize::from(c < extra);
+            node.children.push(Self::build(entries, size, height - 1, false));
+            if c + 1 < children {
+                let (k, v) = entries.next().unwrap();
+                node.keys.push(k);
+                node.values.push(v);
+            }
+        }
+        node
+    }
+
+    // In-order iteration over the entries whose keys fall in the range
+    pub fn range<R: RangeBounds<K>>(&self, range: R) -> Range<'_, K, V, R> {
+        let mut stack = Vec::new();
+        let mut node = &self.root;
+        loop {
+            let start = node.keys.partition_point(|k| !after_start(range.start_bound(), k));
+            stack.push((node, start));
+            if node.leaf {
+                break;
+            }
+            node = &node.children[start];
+        }
+        Range { stack, range }
+    }
+
+    pub fn iter(&self) -> Range<'_, K, V, RangeFull> {
+        self.range(..)
     }
 
-    fn traverse_node<'a>(node: &'a Node<T>, result: &mut Vec<&'a T>) {
-        let mut i = 0;
-        while i < node.keys.len() {
+    // Check the B-tree invariants: key counts per node, sorted and separated keys,
+    // one more child than keys in internal nodes, all leaves at the same depth
+    pub fn validate(&self) -> Result<(), String> {
+        let mut count = 0;
+        Self::validate_node(&self.root, true, None, None, &mut count)?;
+        if count != self.len {
+            return Err(format!("len is {} but the tree holds {} keys", self.len, count));
+        }
+        Ok(())
+    }
+
+    // Returns the height of the subtree
+    fn validate_node(node: &Node<K, V>, is_root: bool, lo: Option<&K>, hi: Option<&K>, count: &mut usize) -> Result<usize, String> {
+        let n = node.keys.len();
+        if n > Self::MAX_KEYS || (!is_root && n < Self::MIN_KEYS) || (is_root && !node.leaf && n == 0) {
+            return Err(format!("node holds {} keys, outside the bounds for B = {}", n, B));
+        }
+        if node.values.len() != n {
+            return Err("keys and values are out of step".to_string());
+        }
+        if !node.keys.windows(2).all(|w| w[0] < w[1]) {
+            return Err("keys within a node are not sorted".to_string());
+        }
+        if lo.is_some_and(|lo| node.keys[0] <= *lo) || hi.is_some_and(|hi| node.keys[n - 1] >= *hi) {
+            return Err("keys are not separated by the parent".to_string());
+        }
+        *count += n;
+        if node.leaf {
+            return if node.children.is_empty() { Ok(0) } else { Err("leaf has children".to_string()) };
+        }
+        if node.children.len() != n + 1 {
+            return Err(format!("internal node has {} keys but {} children", n, node.children.len()));
+        }
+        let mut height = None;
+        for (i, child) in node.children.iter().enumerate() {
+            let child_lo = if i == 0 { lo } else { Some(&node.keys[i - 1]) };
+            let child_hi = if i == n { hi } else { Some(&node.keys[i]) };
+            let h = Self::validate_node(child, false, child_lo, child_hi, count)?;
+            if height.is_some_and(|height| height != h) {
+                return Err("leaves are at different depths".to_string());
+            }
+            height = Some(h);
+        }
+        Ok(height.unwrap() + 1)
+    }
+
+    // One line per node with its keys, children numbered from the left
+    pub fn pretty(&self) -> String
+    where
+        K: Debug,
+    {
+        let root = (self.len > 0).then_some(&self.root);
+        tree_printer::ascii(root, |n| format!("{:?}", n.keys))
+    }
+
+    pub fn to_dot(&self) -> String
+    where
+        K: Debug,
+    {
+        let root = (self.len > 0).then_some(&self.root);
+        tree_printer::dot(root, |n| format!("{:?}", n.keys))
+    }
+}
+
+fn after_start<K: Ord>(bound: Bound<&K>, key: &K) -> bool {
+    match bound {
+        Bound::Included(start) => key >= start,
+        Bound::Excluded(start) => key > start,
+        Bound::Unbounded => true,
+    }
+}
+
+fn before_end<K: Ord>(bound: Bound<&K>, key: &K) -> bool {
+    match bound {
+        Bound::Included(end) => key <= end,
+        Bound::Excluded(end) => key < end,
+        Bound::Unbounded => true,
+    }
+}
+
+// Iterator over a key range; the stack holds each node on the current path with
+// the index of the next key to yield from it
+pub struct Range<'a, K, V, R> {
+    stack: Vec<(&'a Node<K, V>, usize)>,
+    range: R,
+}
+
+impl<'a, K: Ord, V, R: RangeBounds<K>> Iterator for Range<'a, K, V, R> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (node, index) = self.stack.last_mut()?;
+            let node: &'a Node<K, V> = node;
+            if *index == node.keys.len() {
+                self.stack.pop();
+                continue;
+            }
+            let i = *index;
+            *index += 1;
+            if !before_end(self.range.end_bound(), &node.keys[i]) {
+                self.stack.clear();
+                return None;
+            }
+            // Everything in children[i + 1] comes before the next key of this node
             if !node.leaf {
-                Self::traverse_node(&node.children[i], result);
+                let mut child = &node.children[i + 1];
+                loop {
+                    self.stack.push((child, 0));
+                    if child.leaf {
+                        break;
+                    }
+                    child = &child.children[0];
+                }
             }
-            result.push(&node.keys[i]);
-            i += 1;
+            return Some((&node.keys[i], &node.values[i]));
+        }
+    }
+}
+
+// Set of keys on top of the map, keeping the original insert/search/traverse API
+pub struct BTree<T, const B: usize = 6> {
+    map: BTreeMap<T, (), B>,
+}
+
+impl<T: Ord, const B: usize> Default for BTree<T, B> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Ord, const B: usize> BTree<T, B> {
+    pub fn new() -> Self {
+        BTree { map: BTreeMap::new() }
+    }
+
+    pub fn insert(&mut self, key: T) {
+        self.map.insert(key, ());
+    }
+
+    pub fn delete(&mut self, key: &T) -> bool {
+        self.map.remove(key).is_some()
+    }
+
+    pub fn search(&self, key: &T) -> bool {
+        self.map.contains_key(key)
+    }
+
+    pub fn traverse(&self) -> Vec<&T> {
+        self.map.iter().map(|(k, _)| k).collect()
+    }
+
+    pub fn pretty(&self) -> String
+    where
+        T: Debug,
+    {
+        self.map.pretty()
+    }
+
+    pub fn to_dot(&self) -> String
+    where
+        T: Debug,
+    {
+        self.map.to_dot()
+    }
+
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+}
+
+// Small xorshift generator for the randomized checks below
+struct XorShift(u64);
+
+impl XorShift {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+}
+
+fn check<K: Ord, V, const B: usize>(tree: &BTreeMap<K, V, B>) {
+    if let Err(e) = tree.validate() {
+        panic!("B-tree invariant broken (B = {}): {}", B, e);
+    }
+}
+
+// Replays random operations against std's BTreeMap, validating after each one
+fn randomized_check<const B: usize>(operations: usize, seed: u64) {
+    let mut rng = XorShift(seed);
+    let mut tree: BTreeMap<u64, u64, B> = BTreeMap::new();
+    let mut model = StdBTreeMap::new();
+
+    for step in 0..operations as u64 {
+        let key = rng.next() % 500;
+        match rng.next() % 8 {
+            0..=3 => assert_eq!(tree.insert(key, step), model.insert(key, step)),
+            4 | 5 => assert_eq!(tree.remove(&key), model.remove(&key)),
+            6 => assert_eq!(tree.pop_first(), model.pop_first()),
+            _ => assert_eq!(tree.pop_last(), model.pop_last()),
+        }
+        check(&tree);
+        assert_eq!(tree.get(&key), model.get(&key));
+
+        let (a, b) = (rng.next() % 520, rng.next() % 520);
+        let (lo, hi) = (a.min(b), a.max(b));
+        assert!(tree.range(lo..hi).eq(model.range(lo..hi)));
+        assert!(tree.range((Bound::Excluded(lo), Bound::Included(hi))).eq(model.range((Bound::Excluded(lo), Bound::Included(hi)))));
+    }
+    assert!(tree.iter().eq(model.iter()));
+}
+
+// Deleting from a tree where every node is minimal must merge at every level on
+// the way down, and the root finally collapses; deleting every key of every small
+// tree exercises borrowing from both siblings and merging at each level as well
+fn underflow_check() {
+    // Every node of a B = 2 tree at minimum occupancy has one key and two children
+    fn minimal_node(height: usize, next: &mut u32) -> Node<u32, u32> {
+        let mut node = Node::new(height == 0, 3);
+        if height > 0 {
+            node.children.push(minimal_node(height - 1, next));
         }
-        if !node.leaf {
-            Self::traverse_node(&node.children[i], result);
+        node.keys.push(*next);
+        node.values.push(*next);
+        *next += 1;
+        if height > 0 {
+            node.children.push(minimal_node(height - 1, next));
         }
+        node
+    }
+    let minimal: BTreeMap<u32, u32, 2> = BTreeMap { root: minimal_node(3, &mut 0), len: 15 };
+    check(&minimal);
+    assert_eq!(minimal.height(), 3);
+    for key in 0..15 {
+        let mut tree = minimal.clone();
+        assert_eq!(tree.remove(&key), Some(key));
+        check(&tree);
+        assert_eq!(tree.height(), 2);
+        assert!(tree.iter().map(|(k, _)| *k).eq((0..15).filter(|&k| k != key)));
+    }
+
+    for n in 1..=120u32 {
+        let mut built: BTreeMap<u32, u32, 2> = BTreeMap::new();
+        for k in (0..n).step_by(2).chain((1..n).step_by(2).rev()) {
+            built.insert(k, k);
+        }
+        for tree in [built, BTreeMap::from_sorted((0..n).map(|k| (k, k)))] {
+            for key in 0..n {
+                let mut tree = tree.clone();
+                assert!(tree.remove(&key).is_some());
+                check(&tree);
+                assert_eq!(tree.remove(&key), None);
+                assert_eq!(tree.len(), n as usize - 1);
+            }
+        }
+    }
+}
+
+// Bulk loading must produce a valid tree of the minimum possible height
+fn bulk_load_check<const B: usize>() {
+    for n in 0..2000usize {
+        let tree: BTreeMap<usize, usize, B> = BTreeMap::from_sorted((0..n).map(|k| (k, k * 2)));
+        check(&tree);
+        assert_eq!(tree.len(), n);
+        assert!(tree.iter().map(|(k, v)| (*k, *v)).eq((0..n).map(|k| (k, k * 2))));
+        let min_height = (0..).find(|&h| BTreeMap::<usize, usize, B>::max_keys_at(h) >= n).unwrap();
+        assert_eq!(tree.height(), min_height);
     }
 }
 
 fn main() {
-    let mut tree = BTree::new();
-    
+    let mut tree: BTree<i32> = BTree::new();
+
     // Insert some values
     for &value in &[3, 7, 1, 5, 2, 6, 4, 8, 9, 10, 11, 12, 13, 14, 15] {
         tree.insert(value);
@@ -140,4 +674,35 @@ fn main() {
 
     // Traverse the tree
     println!("Inorder traversal: {:?}", tree.traverse());
+
+    // Delete some values
+    tree.delete(&6);
+    tree.delete(&1);
+    println!("After deleting 6 and 1: {:?}", tree.traverse());
+
+    // Key/value map with a small minimum degree so the tree grows several levels
+    let mut stock: BTreeMap<&str, u32, 2> = BTreeMap::new();
+    for (item, count) in [("apple", 5), ("pear", 2), ("fig", 9), ("kiwi", 4), ("lime", 7), ("plum", 1), ("date", 3)] {
+        stock.insert(item, count);
+    }
+    stock.insert("kiwi", 6);
+    if let Some(count) = stock.get_mut(&"fig") {
+        *count -= 1;
+    }
+    println!("Stock: {:?}", stock.iter().collect::<Vec<_>>());
+    println!("Items from 'f' to 'l': {:?}", stock.range("f".."m").collect::<Vec<_>>());
+    println!("Removed pear: {:?}, height: {}", stock.remove(&"pear"), stock.height());
+    print!("Node layout:\n{}", stock.pretty());
+
+    // Bulk loading from sorted input
+    let squares: BTreeMap<u32, u32> = BTreeMap::from_sorted((1..=1000).map(|n| (n, n * n)));
+    println!("Bulk loaded {} squares, height {}, 30^2 = {:?}", squares.len(), squares.height(), squares.get(&30));
+
+    randomized_check::<2>(20_000, 0x2545F4914F6CDD1D);
+    randomized_check::<3>(20_000, 0x9E3779B97F4A7C15);
+    randomized_check::<6>(20_000, 0xD1B54A32D192ED03);
+    bulk_load_check::<2>();
+    bulk_load_check::<3>();
+    underflow_check();
+    println!("Randomized, bulk loading and underflow checks passed");
 }
@@ -0,0 +1,1000 @@
+use std::cell::{Cell, RefCell};
+use std::collections::BTreeSet;
+use std::hash::Hash;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+// LRU, LFU and TTL caches built from MyHashMap (rust-hash-structures.rs) and
+// IndexList (rust-linked-list.rs). The map takes a key to the handle of its
+// entry in the list, and the list order decides what is evicted, so every
+// operation is O(1) apart from the TTL cache's expiry index.
+//
+// Every cache has a capacity in units of weight. By default each entry weighs
+// 1, so the capacity is an entry count; `with_weigher` gives entries their own
+// weight (bytes of a value, say). When an insert pushes the total over the
+// capacity, entries are evicted from the cold end of the list until it fits;
+// the entry just inserted is never the one evicted, and an entry heavier than
+// the whole capacity is turned away. An eviction listener sees every entry the
+// cache drops on its own, with the cause, but not those taken out by `remove`.
+
+#[path = "rust-hash-structures.rs"]
+#[allow(dead_code)]
+mod hash_structures;
+
+#[path = "rust-linked-list.rs"]
+#[allow(dead_code)]
+mod linked_list;
+
+use hash_structures::MyHashMap;
+use linked_list::IndexList;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum EvictionCause {
+    // Dropped to make room, or too heavy to fit at all
+    Capacity,
+    // Its time to live ran out
+    Expired,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub insertions: u64,
+    pub evictions: u64,
+    pub expirations: u64,
+}
+
+impl CacheStats {
+    pub fn hit_rate(&self) -> f64 {
+        let lookups = self.hits + self.misses;
+        if lookups == 0 {
+            0.0
+        } else {
+            self.hits as f64 / lookups as f64
+        }
+    }
+}
+
+type Weigher<K, V> = Box<dyn Fn(&K, &V) -> usize>;
+type EvictionListener<K, V> = Box<dyn FnMut(K, V, EvictionCause)>;
+
+// Source of the current time for the TTL cache, as time since some fixed start
+pub trait Clock {
+    fn now(&self) -> Duration;
+}
+
+pub struct SystemClock {
+    start: Instant,
+}
+
+impl Default for SystemClock {
+    fn default() -> Self {
+        SystemClock { start: Instant::now() }
+    }
+}
+
+impl Clock for SystemClock {
+    fn now(&self) -> Duration {
+        self.start.elapsed()
+    }
+}
+
+// A clock that only moves when told to. Clones share the same time, so a test
+// can keep one and hand another to the cache.
+#[derive(Clone, Default)]
+pub struct ManualClock {
+    now: Rc<Cell<Duration>>,
+}
+
+impl ManualClock {
+    pub fn advance(&self, by: Duration) {
+        self.now.set(self.now.get() + by);
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Duration {
+        self.now.get()
+    }
+}
+
+// 1. LRU Cache
+//
+// The list runs from most recently used (front) to least (back). A hit or an
+// insert moves the entry to the front, and eviction takes from the back.
+struct LruEntry<K, V> {
+    key: K,
+    value: V,
+    weight: usize,
+}
+
+pub struct LruCache<K, V> {
+    map: MyHashMap<K, usize>,
+    order: IndexList<LruEntry<K, V>>,
+    capacity: usize,
+    weight: usize,
+    weigher: Weigher<K, V>,
+    listener: Option<EvictionListener<K, V>>,
+    stats: CacheStats,
+}
+
+impl<K: Hash + Eq + Clone, V> LruCache<K, V> {
+    pub fn new(capacity: usize) -> Self {
+        LruCache {
+            map: MyHashMap::new(),
+            order: IndexList::new(),
+            capacity,
+            weight: 0,
+            weigher: Box::new(|_, _| 1),
+            listener: None,
+            stats: CacheStats::default(),
+        }
+    }
+
+    pub fn with_weigher(mut self, weigher: impl Fn(&K, &V) -> usize + 'static) -> Self {
+        self.weigher = Box::new(weigher);
+        self
+    }
+
+    pub fn with_eviction_listener(mut self, listener: impl FnMut(K, V, EvictionCause) + 'static) -> Self {
+        self.listener = Some(Box::new(listener));
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.order.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.order.is_empty()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    // Total weight of the entries held
+    pub fn weight(&self) -> usize {
+        self.weight
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        self.stats
+    }
+
+    // Looks up `key`, counting a hit or miss and marking the entry as just used
+    pub fn get<Q>(&mut self, key: &Q) -> Option<&V>
+    where
+        K: std::borrow::Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        match self.map.get(key) {
+            Some(&handle) => {
+                self.stats.hits += 1;
+                self.order.move_to_front(handle);
+                Some(&self.order.get(handle).value)
+            }
+            None => {
+                self.stats.misses += 1;
+                None
+            }
+        }
+    }
+
+    // Looks up `key` without touching the statistics or the eviction order
+    pub fn peek<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: std::borrow::Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.map.get(key).map(|&handle| &self.order.get(handle).value)
+    }
+
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: std::borrow::Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.map.get(key).is_some()
+    }
+
+    // Inserts or replaces, returning the value replaced
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let weight = (self.weigher)(&key, &value);
+        self.stats.insertions += 1;
+        if weight > self.capacity {
+            let old = self.remove(&key);
+            self.stats.evictions += 1;
+            if let Some(listener) = &mut self.listener {
+                listener(key, value, EvictionCause::Capacity);
+            }
+            return old;
+        }
+        let (handle, old) = match self.map.get(&key) {
+            Some(&handle) => {
+                self.order.move_to_front(handle);
+                let entry = self.order.get_mut(handle);
+                self.weight = self.weight - entry.weight + weight;
+                entry.weight = weight;
+                (handle, Some(std::mem::replace(&mut entry.value, value)))
+            }
+            None => {
+                let handle = self.order.push_front(LruEntry { key: key.clone(), value, weight });
+                self.map.insert(key, handle);
+                self.weight += weight;
+                (handle, None)
+            }
+        };
+        self.evict_to_fit(handle);
+        old
+    }
+
+    // Evict from the back until the weight fits, passing over `keep`
+    fn evict_to_fit(&mut self, keep: usize) {
+        while self.weight > self.capacity {
+            let back = self.order.back().unwrap();
+            let victim = if back == keep { self.order.prev(back).unwrap() } else { back };
+            let entry = self.order.remove(victim);
+            self.map.remove(&entry.key);
+            self.weight -= entry.weight;
+            self.stats.evictions += 1;
+            if let Some(listener) = &mut self.listener {
+                listener(entry.key, entry.value, EvictionCause::Capacity);
+            }
+        }
+    }
+
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: std::borrow::Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let handle = self.map.remove(key)?;
+        let entry = self.order.remove(handle);
+        self.weight -= entry.weight;
+        Some(entry.value)
+    }
+
+    // Drops every entry without calling the listener; the statistics are kept
+    pub fn clear(&mut self) {
+        self.map.clear();
+        self.order.clear();
+        self.weight = 0;
+    }
+
+    // Entries from most to least recently used
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
+        self.order.iter().map(|entry| (&entry.key, &entry.value))
+    }
+}
+
+// 2. LFU Cache
+//
+// Evicts the entry used the fewest times, the least recently used among equals.
+// All entries sit in one list grouped by use count: higher counts towards the
+// front, and within a group the most recently used first. `heads` keeps the
+// front entry of every group, so a use (count f to f + 1) is O(1): the entry
+// moves to the front of group f + 1 if there is one, and otherwise to the front
+// of its own group, which is exactly where group f + 1 begins. Eviction takes
+// from the back, the least used group's least recent entry.
+struct LfuEntry<K, V> {
+    key: K,
+    value: V,
+    weight: usize,
+    uses: u64,
+}
+
+pub struct LfuCache<K, V> {
+    map: MyHashMap<K, usize>,
+    order: IndexList<LfuEntry<K, V>>,
+    heads: MyHashMap<u64, usize>,
+    capacity: usize,
+    weight: usize,
+    weigher: Weigher<K, V>,
+    listener: Option<EvictionListener<K, V>>,
+    stats: CacheStats,
+}
+
+impl<K: Hash + Eq + Clone, V> LfuCache<K, V> {
+    pub fn new(capacity: usize) -> Self {
+        LfuCache {
+            map: MyHashMap::new(),
+            order: IndexList::new(),
+            heads: MyHashMap::new(),
+            capacity,
+            weight: 0,
+            weigher: Box::new(|_, _| 1),
+            listener: None,
+            stats: CacheStats::default(),
+        }
+    }
+
+    pub fn with_weigher(mut self, weigher: impl Fn(&K, &V) -> usize + 'static) -> Self {
+        self.weigher = Box::new(weigher);
+        self
+    }
+
+    pub fn with_eviction_listener(mut self, listener: impl FnMut(K, V, EvictionCause) + 'static) -> Self {
+        self.listener = Some(Box::new(listener));
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.order.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.order.is_empty()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn weight(&self) -> usize {
+        self.weight
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        self.stats
+    }
+
+    // How many times `key` has been inserted or hit since it entered the cache
+    pub fn uses<Q>(&self, key: &Q) -> Option<u64>
+    where
+        K: std::borrow::Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.map.get(key).map(|&handle| self.order.get(handle).uses)
+    }
+
+    // Take an entry out of its group, fixing the group's head
+    fn leave_group(&mut self, handle: usize) {
+        let uses = self.order.get(handle).uses;
+        if self.heads.get(&uses) == Some(&handle) {
+            match self.order.next(handle) {
+                Some(next) if self.order.get(next).uses == uses => {
+                    self.heads.insert(uses, next);
+                }
+                _ => {
+                    self.heads.remove(&uses);
+                }
+            }
+        }
+    }
+
+    // Count one more use and move the entry to the front of its new group
+    fn touch(&mut self, handle: usize) {
+        self.leave_group(handle);
+        let uses = self.order.get(handle).uses;
+        let before = self.heads.get(&(uses + 1)).or(self.heads.get(&uses)).copied();
+        // With neither group left the entry already sits where group uses + 1 goes
+        if before.is_some() {
+            self.order.move_before(handle, before);
+        }
+        self.order.get_mut(handle).uses = uses + 1;
+        self.heads.insert(uses + 1, handle);
+    }
+
+    pub fn get<Q>(&mut self, key: &Q) -> Option<&V>
+    where
+        K: std::borrow::Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        match self.map.get(key) {
+            Some(&handle) => {
+                self.stats.hits += 1;
+                self.touch(handle);
+                Some(&self.order.get(handle).value)
+            }
+            None => {
+                self.stats.misses += 1;
+                None
+            }
+        }
+    }
+
+    pub fn peek<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: std::borrow::Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.map.get(key).map(|&handle| &self.order.get(handle).value)
+    }
+
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: std::borrow::Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.map.get(key).is_some()
+    }
+
+    // Inserts or replaces, returning the value replaced. Replacing counts as a
+    // use; a new entry starts with one.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let weight = (self.weigher)(&key, &value);
+        self.stats.insertions += 1;
+        if weight > self.capacity {
+            let old = self.remove(&key);
+            self.stats.evictions += 1;
+            if let Some(listener) = &mut self.listener {
+                listener(key, value, EvictionCause::Capacity);
+            }
+            return old;
+        }
+        let (handle, old) = match self.map.get(&key) {
+            Some(&handle) => {
+                self.touch(handle);
+                let entry = self.order.get_mut(handle);
+                self.weight = self.weight - entry.weight + weight;
+                entry.weight = weight;
+                (handle, Some(std::mem::replace(&mut entry.value, value)))
+            }
+            None => {
+                let entry = LfuEntry { key: key.clone(), value, weight, uses: 1 };
+                let handle = match self.heads.get(&1) {
+                    Some(&head) => self.order.insert_before(head, entry),
+                    None => self.order.push_back(entry),
+                };
+                self.heads.insert(1, handle);
+                self.map.insert(key, handle);
+                self.weight += weight;
+                (handle, None)
+            }
+        };
+        self.evict_to_fit(handle);
+        old
+    }
+
+    fn evict_to_fit(&mut self, keep: usize) {
+        while self.weight > self.capacity {
+            let back = self.order.back().unwrap();
+            let victim = if back == keep { self.order.prev(back).unwrap() } else { back };
+            let entry = self.remove_entry(victim);
+            self.stats.evictions += 1;
+            if let Some(listener) = &mut self.listener {
+                listener(entry.key, entry.value, EvictionCause::Capacity);
+            }
+        }
+    }
+
+    fn remove_entry(&mut self, handle: usize) -> LfuEntry<K, V> {
+        self.leave_group(handle);
+        let entry = self.order.remove(handle);
+        self.map.remove(&entry.key);
+        self.weight -= entry.weight;
+        entry
+    }
+
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: std::borrow::Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let handle = *self.map.get(key)?;
+        Some(self.remove_entry(handle).value)
+    }
+
+    pub fn clear(&mut self) {
+        self.map.clear();
+        self.order.clear();
+        self.heads.clear();
+        self.weight = 0;
+    }
+
+    // Entries from most to least used, most recent first among equals
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
+        self.order.iter().map(|entry| (&entry.key, &entry.value))
+    }
+}
+
+// 3. TTL Cache
+//
+// An LRU cache whose entries also expire a fixed time after they were last
+// inserted. Expired entries are dropped lazily: a lookup that finds one removes
+// it and misses, and every insert first purges all expired entries, so room is
+// made from those before any live entry is evicted. Expiry times are indexed in
+// a BTreeSet of (expires at, handle), which makes purging O(log n) per entry.
+// The clock is a parameter so tests can drive time by hand.
+struct TtlEntry<K, V> {
+    key: K,
+    value: V,
+    weight: usize,
+    expires_at: Duration,
+}
+
+pub struct TtlCache<K, V, C = SystemClock> {
+    map: MyHashMap<K, usize>,
+    order: IndexList<TtlEntry<K, V>>,
+    expiry: BTreeSet<(Duration, usize)>,
+    ttl: Duration,
+    clock: C,
+    capacity: usize,
+    weight: usize,
+    weigher: Weigher<K, V>,
+    listener: Option<EvictionListener<K, V>>,
+    stats: CacheStats,
+}
+
+impl<K: Hash + Eq + Clone, V> TtlCache<K, V, SystemClock> {
+    pub fn new(capacity: usize, ttl: Duration) -> Self {
+        Self::with_clock(capacity, ttl, SystemClock::default())
+    }
+}
+
+impl<K: Hash + Eq + Clone, V, C: Clock> TtlCache<K, V, C> {
+    pub fn with_clock(capacity: usize, ttl: Duration, clock: C) -> Self {
+        TtlCache {
+            map: MyHashMap::new(),
+            order: IndexList::new(),
+            expiry: BTreeSet::new(),
+            ttl,
+            clock,
+            capacity,
+            weight: 0,
+            weigher: Box::new(|_, _| 1),
+            listener: None,
+            stats: CacheStats::default(),
+        }
+    }
+
+    pub fn with_weigher(mut self, weigher: impl Fn(&K, &V) -> usize + 'static) -> Self {
+        self.weigher = Box::new(weigher);
+        self
+    }
+
+    pub fn with_eviction_listener(mut self, listener: impl FnMut(K, V, EvictionCause) + 'static) -> Self {
+        self.listener = Some(Box::new(listener));
+        self
+    }
+
+    // Entries held, including expired ones not purged yet
+    pub fn len(&self) -> usize {
+        self.order.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.order.is_empty()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn weight(&self) -> usize {
+        self.weight
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        self.stats
+    }
+
+    fn remove_entry(&mut self, handle: usize) -> TtlEntry<K, V> {
+        let entry = self.order.remove(handle);
+        self.expiry.remove(&(entry.expires_at, handle));
+        self.map.remove(&entry.key);
+        self.weight -= entry.weight;
+        entry
+    }
+
+    fn expire(&mut self, handle: usize) {
+        let entry = self.remove_entry(handle);
+        self.stats.expirations += 1;
+        if let Some(listener) = &mut self.listener {
+            listener(entry.key, entry.value, EvictionCause::Expired);
+        }
+    }
+
+    // Drops every expired entry, returning how many there were
+    pub fn purge_expired(&mut self) -> usize {
+        let now = self.clock.now();
+        let mut purged = 0;
+        while let Some(&(expires_at, handle)) = self.expiry.first() {
+            if expires_at > now {
+                break;
+            }
+            self.expire(handle);
+            purged += 1;
+        }
+        purged
+    }
+
+    pub fn get<Q>(&mut self, key: &Q) -> Option<&V>
+    where
+        K: std::borrow::Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let handle = match self.map.get(key) {
+            Some(&handle) => handle,
+            None => {
+                self.stats.misses += 1;
+                return None;
+            }
+        };
+        if self.order.get(handle).expires_at <= self.clock.now() {
+            self.expire(handle);
+            self.stats.misses += 1;
+            return None;
+        }
+        self.stats.hits += 1;
+        self.order.move_to_front(handle);
+        Some(&self.order.get(handle).value)
+    }
+
+    pub fn peek<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: std::borrow::Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let entry = self.order.get(*self.map.get(key)?);
+        (entry.expires_at > self.clock.now()).then_some(&entry.value)
+    }
+
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: std::borrow::Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.peek(key).is_some()
+    }
+
+    // Time left before `key` expires
+    pub fn time_to_live<Q>(&self, key: &Q) -> Option<Duration>
+    where
+        K: std::borrow::Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let entry = self.order.get(*self.map.get(key)?);
+        entry.expires_at.checked_sub(self.clock.now()).filter(|left| !left.is_zero())
+    }
+
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        self.insert_with_ttl(key, value, self.ttl)
+    }
+
+    // Inserts or replaces with its own time to live, returning the live value
+    // replaced (an expired one is reported to the listener instead)
+    pub fn insert_with_ttl(&mut self, key: K, value: V, ttl: Duration) -> Option<V> {
+        self.purge_expired();
+        let weight = (self.weigher)(&key, &value);
+        self.stats.insertions += 1;
+        if weight > self.capacity {
+            let old = self.remove(&key);
+            self.stats.evictions += 1;
+            if let Some(listener) = &mut self.listener {
+                listener(key, value, EvictionCause::Capacity);
+            }
+            return old;
+        }
+        let expires_at = self.clock.now() + ttl;
+        let (handle, old) = match self.map.get(&key) {
+            Some(&handle) => {
+                self.order.move_to_front(handle);
+                let entry = self.order.get_mut(handle);
+                self.expiry.remove(&(entry.expires_at, handle));
+                self.expiry.insert((expires_at, handle));
+                self.weight = self.weight - entry.weight + weight;
+                entry.weight = weight;
+                entry.expires_at = expires_at;
+                (handle, Some(std::mem::replace(&mut entry.value, value)))
+            }
+            None => {
+                let handle = self.order.push_front(TtlEntry { key: key.clone(), value, weight, expires_at });
+                self.expiry.insert((expires_at, handle));
+                self.map.insert(key, handle);
+                self.weight += weight;
+                (handle, None)
+            }
+        };
+        self.evict_to_fit(handle);
+        old
+    }
+
+    // Live entries are evicted least recently used first, passing over `keep`
+    fn evict_to_fit(&mut self, keep: usize) {
+        while self.weight > self.capacity {
+            let back = self.order.back().unwrap();
+            let victim = if back == keep { self.order.prev(back).unwrap() } else { back };
+            let entry = self.remove_entry(victim);
+            self.stats.evictions += 1;
+            if let Some(listener) = &mut self.listener {
+                listener(entry.key, entry.value, EvictionCause::Capacity);
+            }
+        }
+    }
+
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: std::borrow::Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let handle = *self.map.get(key)?;
+        Some(self.remove_entry(handle).value)
+    }
+
+    pub fn clear(&mut self) {
+        self.map.clear();
+        self.order.clear();
+        self.expiry.clear();
+        self.weight = 0;
+    }
+
+    // Live entries from most to least recently used
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
+        let now = self.clock.now();
+        self.order.iter().filter(move |entry| entry.expires_at > now).map(|entry| (&entry.key, &entry.value))
+    }
+}
+
+// Small xorshift generator for the randomized checks below
+struct XorShift(u64);
+
+impl XorShift {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, n: u64) -> u64 {
+        self.next() % n
+    }
+}
+
+// Reference model shared by the checks: a plain list of entries searched
+// linearly. Each cache picks its victim from it in its own way.
+struct ModelEntry {
+    key: u64,
+    value: u64,
+    weight: usize,
+    uses: u64,
+    last_used: u64,
+    expires_at: Duration,
+}
+
+impl ModelEntry {
+    fn expired(&self, now: Duration) -> bool {
+        self.expires_at <= now
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Policy {
+    Lru,
+    Lfu,
+    Ttl,
+}
+
+struct Model {
+    policy: Policy,
+    entries: Vec<ModelEntry>,
+    capacity: usize,
+    tick: u64,
+    stats: CacheStats,
+    events: Vec<(u64, u64, EvictionCause)>,
+}
+
+impl Model {
+    fn weight(&self) -> usize {
+        self.entries.iter().map(|e| e.weight).sum()
+    }
+
+    fn position(&self, key: u64) -> Option<usize> {
+        self.entries.iter().position(|e| e.key == key)
+    }
+
+    fn purge(&mut self, now: Duration) {
+        let (expired, live): (Vec<_>, Vec<_>) = self.entries.drain(..).partition(|e| e.expired(now));
+        self.entries = live;
+        for entry in expired {
+            self.stats.expirations += 1;
+            self.events.push((entry.key, entry.value, EvictionCause::Expired));
+        }
+    }
+
+    fn get(&mut self, key: u64, now: Duration) -> Option<u64> {
+        self.tick += 1;
+        let Some(i) = self.position(key) else {
+            self.stats.misses += 1;
+            return None;
+        };
+        if self.policy == Policy::Ttl && self.entries[i].expired(now) {
+            let entry = self.entries.remove(i);
+            self.stats.expirations += 1;
+            self.stats.misses += 1;
+            self.events.push((entry.key, entry.value, EvictionCause::Expired));
+            return None;
+        }
+        self.stats.hits += 1;
+        let entry = &mut self.entries[i];
+        entry.uses += 1;
+        entry.last_used = self.tick;
+        Some(entry.value)
+    }
+
+    fn insert(&mut self, key: u64, value: u64, weight: usize, expires_at: Duration, now: Duration) -> Option<u64> {
+        self.tick += 1;
+        if self.policy == Policy::Ttl {
+            self.purge(now);
+        }
+        self.stats.insertions += 1;
+        let existing = self.position(key);
+        if weight > self.capacity {
+            let old = existing.map(|i| self.entries.remove(i).value);
+            self.stats.evictions += 1;
+            self.events.push((key, value, EvictionCause::Capacity));
+            return old;
+        }
+        let old = match existing {
+            Some(i) => {
+                let entry = &mut self.entries[i];
+                entry.uses += 1;
+                entry.last_used = self.tick;
+                entry.weight = weight;
+                entry.expires_at = expires_at;
+                Some(std::mem::replace(&mut entry.value, value))
+            }
+            None => {
+                self.entries.push(ModelEntry { key, value, weight, uses: 1, last_used: self.tick, expires_at });
+                None
+            }
+        };
+        while self.weight() > self.capacity {
+            let policy = self.policy;
+            let victim = (0..self.entries.len())
+                .filter(|&i| self.entries[i].key != key)
+                .min_by_key(|&i| {
+                    let e = &self.entries[i];
+                    if policy == Policy::Lfu { (e.uses, e.last_used) } else { (0, e.last_used) }
+                })
+                .unwrap();
+            let entry = self.entries.remove(victim);
+            self.stats.evictions += 1;
+            self.events.push((entry.key, entry.value, EvictionCause::Capacity));
+        }
+        old
+    }
+
+    fn remove(&mut self, key: u64) -> Option<u64> {
+        self.position(key).map(|i| self.entries.remove(i).value)
+    }
+}
+
+// Runs the same random operations against one cache and the model and compares
+// results, sizes, statistics and listener calls after every step. The cache is
+// driven through closures so one loop serves all three types.
+struct Harness<'a> {
+    get: &'a mut dyn FnMut(u64) -> Option<u64>,
+    insert: &'a mut dyn FnMut(u64, u64, Duration) -> Option<u64>,
+    remove: &'a mut dyn FnMut(u64) -> Option<u64>,
+    summary: &'a dyn Fn() -> (usize, usize, CacheStats),
+}
+
+fn model_check(policy: Policy, operations: usize, seed: u64, events: &RefCell<Vec<(u64, u64, EvictionCause)>>, clock: &ManualClock, harness: Harness) {
+    let Harness { get, insert, remove, summary } = harness;
+    let mut rng = XorShift(seed);
+    let mut model = Model { policy, entries: Vec::new(), capacity: 40, tick: 0, stats: CacheStats::default(), events: Vec::new() };
+    for step in 0..operations {
+        let key = rng.below(30);
+        let now = clock.now();
+        match rng.below(10) {
+            0..=4 => assert_eq!(get(key), model.get(key, now), "step {}: get({})", step, key),
+            5..=8 => {
+                let value = rng.next();
+                let ttl = Duration::from_millis(1 + rng.below(50));
+                let weight = (value % 12) as usize;
+                assert_eq!(insert(key, value, ttl), model.insert(key, value, weight, now + ttl, now), "step {}: insert({})", step, key);
+            }
+            _ => assert_eq!(remove(key), model.remove(key), "step {}: remove({})", step, key),
+        }
+        if policy == Policy::Ttl && rng.below(4) == 0 {
+            clock.advance(Duration::from_millis(rng.below(10)));
+        }
+        let (len, weight, stats) = summary();
+        assert_eq!((len, weight), (model.entries.len(), model.weight()), "step {}: size", step);
+        assert_eq!(stats, model.stats, "step {}: statistics", step);
+        // Entries expiring together may be purged in any order
+        let mut seen = events.take();
+        let mut expected = std::mem::take(&mut model.events);
+        seen.sort_unstable();
+        expected.sort_unstable();
+        assert_eq!(seen, expected, "step {}: eviction listener", step);
+    }
+}
+
+fn randomized_checks(operations: usize, seed: u64) {
+    let weigh = |_: &u64, value: &u64| (value % 12) as usize;
+
+    let events = Rc::new(RefCell::new(Vec::new()));
+    let sink = Rc::clone(&events);
+    let lru = RefCell::new(LruCache::new(40).with_weigher(weigh).with_eviction_listener(move |k, v, cause| sink.borrow_mut().push((k, v, cause))));
+    model_check(Policy::Lru, operations, seed, &events, &ManualClock::default(), Harness {
+        get: &mut |key| lru.borrow_mut().get(&key).copied(),
+        insert: &mut |key, value, _| lru.borrow_mut().insert(key, value),
+        remove: &mut |key| lru.borrow_mut().remove(&key),
+        summary: &|| {
+            let lru = lru.borrow();
+            (lru.len(), lru.weight(), lru.stats())
+        },
+    });
+
+    let sink = Rc::clone(&events);
+    let lfu = RefCell::new(LfuCache::new(40).with_weigher(weigh).with_eviction_listener(move |k, v, cause| sink.borrow_mut().push((k, v, cause))));
+    model_check(Policy::Lfu, operations, seed ^ 1, &events, &ManualClock::default(), Harness {
+        get: &mut |key| lfu.borrow_mut().get(&key).copied(),
+        insert: &mut |key, value, _| lfu.borrow_mut().insert(key, value),
+        remove: &mut |key| lfu.borrow_mut().remove(&key),
+        summary: &|| {
+            let lfu = lfu.borrow();
+            (lfu.len(), lfu.weight(), lfu.stats())
+        },
+    });
+
+    let clock = ManualClock::default();
+    let sink = Rc::clone(&events);
+    let ttl = RefCell::new(
+        TtlCache::with_clock(40, Duration::from_secs(1), clock.clone())
+            .with_weigher(weigh)
+            .with_eviction_listener(move |k, v, cause| sink.borrow_mut().push((k, v, cause))),
+    );
+    model_check(Policy::Ttl, operations, seed ^ 2, &events, &clock, Harness {
+        get: &mut |key| ttl.borrow_mut().get(&key).copied(),
+        insert: &mut |key, value, time_to_live| ttl.borrow_mut().insert_with_ttl(key, value, time_to_live),
+        remove: &mut |key| ttl.borrow_mut().remove(&key),
+        summary: &|| {
+            let ttl = ttl.borrow();
+            (ttl.len(), ttl.weight(), ttl.stats())
+        },
+    });
+}
+
+fn main() {
+    // 1. LRU: the least recently used page goes first
+    let evicted = Rc::new(RefCell::new(Vec::new()));
+    let sink = Rc::clone(&evicted);
+    let mut lru = LruCache::new(3).with_eviction_listener(move |key, _, cause| sink.borrow_mut().push((key, cause)));
+    for page in ["home", "about", "blog"] {
+        lru.insert(page, page.len());
+    }
+    lru.get("home");
+    lru.insert("contact", 7);
+    println!("LRU after touching home and adding contact: {:?}", lru.iter().map(|(k, _)| *k).collect::<Vec<_>>());
+    println!("Evicted: {:?}", evicted.borrow());
+    lru.get("about");
+    println!("LRU stats: {:?}, hit rate {:.2}", lru.stats(), lru.stats().hit_rate());
+
+    // Weight-based eviction: capacity in bytes of value
+    let mut bytes: LruCache<u32, String> = LruCache::new(16).with_weigher(|_, value: &String| value.len());
+    bytes.insert(1, "twelve bytes".to_string());
+    bytes.insert(2, "six b.".to_string());
+    println!("\nByte-weighted LRU holds keys {:?} using {} of {} bytes", bytes.iter().map(|(k, _)| *k).collect::<Vec<_>>(), bytes.weight(), bytes.capacity());
+
+    // 2. LFU: a popular entry survives a scan of one-off keys
+    let mut lfu = LfuCache::new(3);
+    lfu.insert("popular", 0);
+    for _ in 0..5 {
+        lfu.get("popular");
+    }
+    for key in ["scan-1", "scan-2", "scan-3", "scan-4"] {
+        lfu.insert(key, 0);
+    }
+    println!("\nLFU after a scan: {:?}, popular used {:?} times", lfu.iter().map(|(k, _)| *k).collect::<Vec<_>>(), lfu.uses("popular"));
+
+    // 3. TTL with a clock moved by hand
+    let clock = ManualClock::default();
+    let mut sessions = TtlCache::with_clock(100, Duration::from_secs(30), clock.clone());
+    sessions.insert("alice", 1);
+    sessions.insert_with_ttl("bob", 2, Duration::from_secs(5));
+    clock.advance(Duration::from_secs(10));
+    let alice = sessions.get("alice").copied();
+    let bob = sessions.get("bob").copied();
+    println!("\nAfter 10s: alice {:?} ({:?} left), bob {:?}", alice, sessions.time_to_live("alice"), bob);
+    clock.advance(Duration::from_secs(25));
+    println!("After 35s: {} expired by purge, stats {:?}", sessions.purge_expired(), sessions.stats());
+
+    randomized_checks(20_000, 0x9E3779B97F4A7C15);
+    println!("\nRandomized check passed: LRU, LFU and TTL caches matched the reference model");
+}
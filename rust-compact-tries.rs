@@ -0,0 +1,517 @@
+use std::collections::BTreeSet;
+use std::mem::size_of;
+
+// Compact tries for large dictionaries. `Trie` in rust-trie.rs spends a HashMap
+// and a completion cache on every character, which adds up to hundreds of bytes
+// per node. Two leaner layouts with the same lookups:
+//
+// - RadixTree (Patricia tree) collapses every chain of single-child nodes into
+//   one edge labelled with the whole substring, and keeps children in a sorted
+//   Vec. It supports insert and delete like Trie.
+// - DoubleArrayTrie is built once from a word list and never changes. Every
+//   state is an index into two integer arrays: the child of state s along code c
+//   sits at base[s] + c, and check[] records its parent so lookups can verify it.
+//   A transition costs two array reads and no pointer chasing.
+//
+// Trie is pulled in from its own file, so this program builds standalone:
+//
+//     rustc -O --edition 2021 rust-compact-tries.rs
+//     ./rust-compact-tries 500000
+
+#[path = "rust-trie.rs"]
+#[allow(dead_code)]
+mod trie;
+
+use trie::Trie;
+
+struct RadixNode {
+    // Substring on the edge into this node; empty only at the root
+    label: Box<str>,
+    // Sorted by the first character of their labels, which are all different
+    children: Vec<RadixNode>,
+    is_end_of_word: bool,
+}
+
+impl RadixNode {
+    fn new(label: &str, is_end_of_word: bool) -> Self {
+        RadixNode {
+            label: label.into(),
+            children: Vec::new(),
+            is_end_of_word,
+        }
+    }
+
+    fn first_char(&self) -> char {
+        self.label.chars().next().unwrap()
+    }
+
+    fn child_index(&self, ch: char) -> Result<usize, usize> {
+        self.children.binary_search_by(|child| child.first_char().cmp(&ch))
+    }
+
+    // Cut the label after `at` bytes, moving the rest of it and everything below
+    // into a single new child
+    fn split(&mut self, at: usize) {
+        let tail = RadixNode {
+            label: self.label[at..].into(),
+            children: std::mem::take(&mut self.children),
+            is_end_of_word: self.is_end_of_word,
+        };
+        self.label = self.label[..at].into();
+        self.children = vec![tail];
+        self.is_end_of_word = false;
+    }
+
+    // Undo a split once this node has neither a word nor a branch of its own
+    fn merge_only_child(&mut self) {
+        let child = self.children.pop().unwrap();
+        self.label = format!("{}{}", self.label, child.label).into();
+        self.children = child.children;
+        self.is_end_of_word = child.is_end_of_word;
+    }
+
+    fn heap_bytes(&self) -> usize {
+        self.label.len()
+            + self.children.capacity() * size_of::<RadixNode>()
+            + self.children.iter().map(RadixNode::heap_bytes).sum::<usize>()
+    }
+}
+
+// Length in bytes of the longest common prefix, always on a character boundary
+fn common_prefix_len(a: &str, b: &str) -> usize {
+    a.char_indices()
+        .zip(b.chars())
+        .find(|&((_, x), y)| x != y)
+        .map_or(a.len().min(b.len()), |((i, _), _)| i)
+}
+
+pub struct RadixTree {
+    root: RadixNode,
+    len: usize,
+}
+
+impl Default for RadixTree {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RadixTree {
+    pub fn new() -> Self {
+        RadixTree {
+            root: RadixNode::new("", false),
+            len: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    // Returns false if the word was already present
+    pub fn insert(&mut self, word: &str) -> bool {
+        let inserted = Self::insert_recursive(&mut self.root, word);
+        if inserted {
+            self.len += 1;
+        }
+        inserted
+    }
+
+    fn insert_recursive(node: &mut RadixNode, rest: &str) -> bool {
+        let ch = match rest.chars().next() {
+            None => return !std::mem::replace(&mut node.is_end_of_word, true),
+            Some(ch) => ch,
+        };
+        match node.child_index(ch) {
+            Err(i) => {
+                node.children.insert(i, RadixNode::new(rest, true));
+                true
+            }
+            Ok(i) => {
+                let child = &mut node.children[i];
+                let common = common_prefix_len(&child.label, rest);
+                if common < child.label.len() {
+                    child.split(common);
+                }
+                Self::insert_recursive(child, &rest[common..])
+            }
+        }
+    }
+
+    // The node that `key` leads to, with the part of its edge label beyond the
+    // end of `key` when `key` stops partway along an edge
+    fn find(&self, key: &str) -> Option<(&RadixNode, &str)> {
+        let mut node = &self.root;
+        let mut rest = key;
+        while let Some(ch) = rest.chars().next() {
+            let child = &node.children[node.child_index(ch).ok()?];
+            if let Some(remaining) = rest.strip_prefix(&*child.label) {
+                rest = remaining;
+                node = child;
+            } else if let Some(tail) = child.label.strip_prefix(rest) {
+                return Some((child, tail));
+            } else {
+                return None;
+            }
+        }
+        Some((node, ""))
+    }
+
+    pub fn search(&self, word: &str) -> bool {
+        self.find(word).is_some_and(|(node, tail)| tail.is_empty() && node.is_end_of_word)
+    }
+
+    pub fn starts_with(&self, prefix: &str) -> bool {
+        self.find(prefix).is_some()
+    }
+
+    // Every word starting with `prefix`, in alphabetical order
+    pub fn find_words_with_prefix(&self, prefix: &str) -> Vec<String> {
+        let mut result = Vec::new();
+        if let Some((node, tail)) = self.find(prefix) {
+            Self::dfs(node, &mut format!("{}{}", prefix, tail), &mut result);
+        }
+        result
+    }
+
+    fn dfs(node: &RadixNode, current_word: &mut String, result: &mut Vec<String>) {
+        if node.is_end_of_word {
+            result.push(current_word.clone());
+        }
+        for child in &node.children {
+            current_word.push_str(&child.label);
+            Self::dfs(child, current_word, result);
+            current_word.truncate(current_word.len() - child.label.len());
+        }
+    }
+
+    // Returns whether the word was present. Nodes left without a word or a
+    // branch are removed or merged into their only child, so the tree has the
+    // same shape as if the word had never been inserted.
+    pub fn delete(&mut self, word: &str) -> bool {
+        let deleted = Self::delete_recursive(&mut self.root, word);
+        if deleted {
+            self.len -= 1;
+        }
+        deleted
+    }
+
+    fn delete_recursive(node: &mut RadixNode, rest: &str) -> bool {
+        let ch = match rest.chars().next() {
+            None => return std::mem::replace(&mut node.is_end_of_word, false),
+            Some(ch) => ch,
+        };
+        let i = match node.child_index(ch) {
+            Err(_) => return false,
+            Ok(i) => i,
+        };
+        let child = &mut node.children[i];
+        let remaining = match rest.strip_prefix(&*child.label) {
+            None => return false,
+            Some(remaining) => remaining,
+        };
+        if !Self::delete_recursive(child, remaining) {
+            return false;
+        }
+        if !child.is_end_of_word {
+            match child.children.len() {
+                0 => {
+                    node.children.remove(i);
+                }
+                1 => child.merge_only_child(),
+                _ => {}
+            }
+        }
+        true
+    }
+
+    pub fn node_count(&self) -> usize {
+        fn count(node: &RadixNode) -> usize {
+            1 + node.children.iter().map(count).sum::<usize>()
+        }
+        count(&self.root)
+    }
+
+    pub fn heap_bytes(&self) -> usize {
+        self.root.heap_bytes()
+    }
+
+    // Check the invariants: sorted, distinct first characters, no empty labels
+    // below the root, and no removable node (wordless with fewer than two children)
+    fn validate(&self) {
+        fn check(node: &RadixNode, is_root: bool) -> usize {
+            if !is_root {
+                assert!(!node.label.is_empty(), "empty edge label");
+                assert!(node.is_end_of_word || node.children.len() >= 2, "node {:?} should have been merged", node.label);
+            }
+            for pair in node.children.windows(2) {
+                assert!(pair[0].first_char() < pair[1].first_char(), "children out of order");
+            }
+            usize::from(node.is_end_of_word) + node.children.iter().map(|child| check(child, false)).sum::<usize>()
+        }
+        assert_eq!(check(&self.root, true), self.len, "len out of sync");
+    }
+}
+
+// Free slots have a negative check
+const FREE: i32 = -1;
+const ROOT: usize = 0;
+// Code 0 marks the end of a word; byte b of the UTF-8 encoding is code b + 1
+const END: usize = 0;
+const CODES: usize = 257;
+
+pub struct DoubleArrayTrie {
+    base: Vec<i32>,
+    check: Vec<i32>,
+    len: usize,
+}
+
+impl DoubleArrayTrie {
+    // Lay the words out state by state, breadth first. The children of a state
+    // are the distinct codes at the current depth among the (sorted) words below
+    // it; the state gets the first base where every one of those slots is free.
+    pub fn build<S: AsRef<str>>(words: &[S]) -> Self {
+        let words: Vec<&[u8]> = words.iter().map(|word| word.as_ref().as_bytes()).collect::<BTreeSet<_>>().into_iter().collect();
+        let mut trie = DoubleArrayTrie {
+            base: vec![0; CODES],
+            check: vec![FREE; CODES],
+            len: words.len(),
+        };
+        trie.check[ROOT] = ROOT as i32;
+        // Positions below this are all taken, so the search for a base starts here
+        let mut first_free = 1;
+
+        let mut pending = std::collections::VecDeque::new();
+        if !words.is_empty() {
+            pending.push_back((ROOT, 0, words.len(), 0));
+        }
+        while let Some((state, start, end, depth)) = pending.pop_front() {
+            // (code, start, end) for each distinct code at `depth` in words[start..end]
+            let mut groups: Vec<(usize, usize, usize)> = Vec::new();
+            for (i, word) in words.iter().enumerate().take(end).skip(start) {
+                let code = word.get(depth).map_or(END, |&byte| byte as usize + 1);
+                match groups.last_mut() {
+                    Some(group) if group.0 == code => group.2 = i + 1,
+                    _ => groups.push((code, i, i + 1)),
+                }
+            }
+
+            while trie.check[first_free] != FREE {
+                first_free += 1;
+            }
+            let lowest = groups[0].0;
+            let mut base = first_free.max(lowest + 1) - lowest;
+            loop {
+                trie.reserve(base + CODES);
+                if groups.iter().all(|&(code, _, _)| trie.check[base + code] == FREE) {
+                    break;
+                }
+                base += 1;
+            }
+
+            trie.base[state] = base as i32;
+            for &(code, start, end) in &groups {
+                trie.check[base + code] = state as i32;
+                if code != END {
+                    pending.push_back((base + code, start, end, depth + 1));
+                }
+            }
+        }
+
+        let used = trie.check.iter().rposition(|&check| check != FREE).map_or(1, |last| last + 1);
+        trie.base.truncate(used);
+        trie.check.truncate(used);
+        trie.base.shrink_to_fit();
+        trie.check.shrink_to_fit();
+        trie
+    }
+
+    fn reserve(&mut self, size: usize) {
+        if self.check.len() < size {
+            let size = size.max(self.check.len() * 2);
+            self.base.resize(size, 0);
+            self.check.resize(size, FREE);
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    fn child(&self, state: usize, code: usize) -> Option<usize> {
+        let next = self.base[state] as usize + code;
+        (self.check.get(next) == Some(&(state as i32)) && next != ROOT).then_some(next)
+    }
+
+    fn find(&self, prefix: &str) -> Option<usize> {
+        prefix.bytes().try_fold(ROOT, |state, byte| self.child(state, byte as usize + 1))
+    }
+
+    pub fn search(&self, word: &str) -> bool {
+        self.find(word).is_some_and(|state| self.child(state, END).is_some())
+    }
+
+    pub fn starts_with(&self, prefix: &str) -> bool {
+        self.find(prefix).is_some()
+    }
+
+    // Every word starting with `prefix`, in alphabetical order: codes are tried
+    // in byte order, and byte order of UTF-8 is character order
+    pub fn find_words_with_prefix(&self, prefix: &str) -> Vec<String> {
+        let mut result = Vec::new();
+        if let Some(state) = self.find(prefix) {
+            self.dfs(state, &mut prefix.as_bytes().to_vec(), &mut result);
+        }
+        result
+    }
+
+    fn dfs(&self, state: usize, current_word: &mut Vec<u8>, result: &mut Vec<String>) {
+        if self.child(state, END).is_some() {
+            // Only whole words end here, so the bytes are valid UTF-8
+            result.push(String::from_utf8(current_word.clone()).unwrap());
+        }
+        for code in 1..CODES {
+            if let Some(next) = self.child(state, code) {
+                current_word.push((code - 1) as u8);
+                self.dfs(next, current_word, result);
+                current_word.pop();
+            }
+        }
+    }
+
+    pub fn heap_bytes(&self) -> usize {
+        (self.base.capacity() + self.check.capacity()) * size_of::<i32>()
+    }
+
+    // Share of array slots holding a state; the rest are holes left by the layout
+    pub fn fill_ratio(&self) -> f64 {
+        self.check.iter().filter(|&&check| check != FREE).count() as f64 / self.check.len() as f64
+    }
+}
+
+// Small xorshift generator for the checks below
+struct XorShift(u64);
+
+impl XorShift {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+}
+
+// Dictionary-like words: a few syllables each, so many words share prefixes,
+// with the odd accented or non-Latin syllable to keep the byte handling honest
+fn random_word(rng: &mut XorShift) -> String {
+    const SYLLABLES: [&str; 24] = [
+        "an", "be", "con", "de", "er", "ex", "fi", "ga", "in", "ka", "la", "ment", "mo", "ne", "or", "pre", "ra", "re",
+        "sta", "ti", "un", "ve", "zé", "ст",
+    ];
+    let syllables = 1 + rng.next() % 4;
+    (0..syllables).map(|_| SYLLABLES[(rng.next() % SYLLABLES.len() as u64) as usize]).collect()
+}
+
+// Random inserts and deletes on the radix tree against a BTreeSet, with the
+// double-array trie rebuilt from the model now and then
+fn randomized_check(operations: usize, seed: u64) {
+    let mut rng = XorShift(seed);
+    let mut tree = RadixTree::new();
+    let mut model = BTreeSet::new();
+    for step in 0..operations {
+        let word = if rng.next().is_multiple_of(3) {
+            // Reuse a prefix of an existing word to hit the split and merge paths
+            let existing: String = model.iter().nth((rng.next() % (model.len() as u64 + 1)) as usize).cloned().unwrap_or_default();
+            let chars: Vec<char> = existing.chars().collect();
+            chars[..(rng.next() % (chars.len() as u64 + 1)) as usize].iter().collect()
+        } else {
+            random_word(&mut rng)
+        };
+        if rng.next().is_multiple_of(3) {
+            assert_eq!(tree.delete(&word), model.remove(&word), "delete {:?}", word);
+        } else {
+            assert_eq!(tree.insert(&word), model.insert(word.clone()), "insert {:?}", word);
+        }
+        tree.validate();
+
+        let prefix: String = word.chars().take((rng.next() % 4) as usize).collect();
+        let expected: Vec<String> = model.iter().filter(|w| w.starts_with(&prefix)).cloned().collect();
+        assert_eq!(tree.find_words_with_prefix(&prefix), expected);
+        assert_eq!(tree.starts_with(&prefix), !expected.is_empty());
+        assert_eq!(tree.search(&word), model.contains(&word));
+
+        if step % 500 == 499 {
+            let words: Vec<&String> = model.iter().collect();
+            let array = DoubleArrayTrie::build(&words);
+            assert_eq!(array.len(), model.len());
+            assert_eq!(array.find_words_with_prefix(""), tree.find_words_with_prefix(""));
+            for probe in model.iter().take(50) {
+                let chars: Vec<char> = probe.chars().collect();
+                let cut: String = chars[..chars.len() / 2].iter().collect();
+                assert!(array.search(probe));
+                assert_eq!(array.search(&cut), model.contains(&cut));
+                assert_eq!(array.find_words_with_prefix(&cut), tree.find_words_with_prefix(&cut));
+            }
+        }
+    }
+    assert_eq!(tree.len(), model.len());
+}
+
+fn main() {
+    let mut tree = RadixTree::new();
+    for word in ["romane", "romanus", "romulus", "rubens", "ruber", "rubicon", "rubicundus"] {
+        tree.insert(word);
+    }
+    println!("Radix tree nodes for 7 words: {}", tree.node_count());
+    println!("Words with prefix 'rub': {:?}", tree.find_words_with_prefix("rub"));
+    println!("Starts with 'rom': {}", tree.starts_with("rom")); // true
+    println!("Search 'roman': {}", tree.search("roman")); // false
+    tree.delete("romulus");
+    println!("After deleting 'romulus': {:?}", tree.find_words_with_prefix("ro"));
+
+    let array = DoubleArrayTrie::build(&["apple", "app", "apply", "banana", "bath", "bat", "bâtir"]);
+    println!("Double-array search 'apply': {}", array.search("apply")); // true
+    println!("Double-array words with prefix 'b': {:?}", array.find_words_with_prefix("b"));
+
+    randomized_check(5_000, 0x2545F4914F6CDD1D);
+    println!("Randomized check passed: radix tree and double-array trie matched a BTreeSet");
+
+    // Memory report: words in the dictionary can be given as the first argument
+    let n: usize = std::env::args().nth(1).and_then(|arg| arg.parse().ok()).unwrap_or(100_000);
+    let mut rng = XorShift(0xDEAD_BEEF_CAFE_F00D);
+    let words: Vec<String> = (0..n).map(|_| random_word(&mut rng)).collect();
+
+    let mut trie = Trie::new();
+    let mut tree = RadixTree::new();
+    for word in &words {
+        trie.insert(word);
+        tree.insert(word);
+    }
+    let array = DoubleArrayTrie::build(&words);
+    let distinct = tree.len();
+    let text: usize = tree.find_words_with_prefix("").iter().map(String::len).sum();
+    assert_eq!(array.len(), distinct);
+    for word in words.iter().take(1_000) {
+        let prefix: String = word.chars().take(3).collect();
+        assert!(trie.search(word) && tree.search(word) && array.search(word));
+        assert_eq!(tree.find_words_with_prefix(&prefix), trie.find_words_with_prefix(&prefix));
+        assert_eq!(array.find_words_with_prefix(&prefix), trie.find_words_with_prefix(&prefix));
+    }
+
+    println!("\nMemory for {} distinct words ({} bytes of text):", distinct, text);
+    let row = |name: &str, bytes: usize| {
+        println!("{:<20}{:>14} bytes{:>10.1} bytes/word", name, bytes, bytes as f64 / distinct as f64);
+    };
+    row("Trie", trie.heap_bytes());
+    row("RadixTree", tree.heap_bytes());
+    row("DoubleArrayTrie", array.heap_bytes());
+    println!("Radix tree nodes: {}, double-array fill ratio: {:.2}", tree.node_count(), array.fill_ratio());
+}
@@ -0,0 +1,472 @@
+use std::collections::hash_map::RandomState;
+use std::collections::HashSet;
+use std::hash::{BuildHasher, Hash};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering as AtomicOrdering};
+use std::sync::{Barrier, Mutex, RwLock, RwLockReadGuard, RwLockWriteGuard};
+use std::thread;
+use std::time::Instant;
+
+// Concurrent hash map: a fixed number of shards, each a MyHashMap from
+// rust-hash-structures.rs behind its own RwLock. A key's hash picks its shard,
+// so threads working on different shards never wait for each other, and readers
+// of the same shard only wait for its writers. Every method takes &self, so one
+// map can be shared between threads by reference or through an Arc.
+//
+//     rustc -O --edition 2021 rust-concurrent-hash-map.rs
+//     ./rust-concurrent-hash-map 1000000
+//
+// Operations on a single key are linearizable: each runs entirely under its
+// shard's lock. Operations that visit several shards (len, for_each, snapshot)
+// lock one shard at a time, so each shard is seen at a single instant but
+// different shards may be seen at different instants.
+
+#[path = "rust-hash-structures.rs"]
+#[allow(dead_code)]
+mod hash_structures;
+
+use hash_structures::MyHashMap;
+
+pub struct ShardedMap<K, V, S = RandomState> {
+    shards: Box<[RwLock<MyHashMap<K, V, S>>]>,
+    // Picks the shard. Each shard hashes again with its own seed to pick a bucket.
+    hash_builder: S,
+}
+
+impl<K, V> ShardedMap<K, V, RandomState> {
+    // Four shards per core keeps two threads on the same shard unlikely
+    pub fn new() -> Self {
+        let cores = thread::available_parallelism().map_or(1, |c| c.get());
+        Self::with_shards(cores * 4)
+    }
+
+    pub fn with_shards(shard_count: usize) -> Self {
+        Self::with_shards_and_hasher(shard_count, RandomState::new())
+    }
+}
+
+impl<K, V> Default for ShardedMap<K, V, RandomState> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, V, S: Default> ShardedMap<K, V, S> {
+    pub fn with_shards_and_hasher(shard_count: usize, hash_builder: S) -> Self {
+        ShardedMap {
+            shards: (0..shard_count.max(1)).map(|_| RwLock::new(MyHashMap::default())).collect(),
+            hash_builder,
+        }
+    }
+}
+
+impl<K, V, S> ShardedMap<K, V, S> {
+    pub fn shard_count(&self) -> usize {
+        self.shards.len()
+    }
+
+    // A panic while a shard is write-locked (in a `compute` closure, say)
+    // poisons that shard, and later operations on it panic too
+    fn read_shard(&self, index: usize) -> RwLockReadGuard<'_, MyHashMap<K, V, S>> {
+        self.shards[index].read().unwrap()
+    }
+
+    fn write_shard(&self, index: usize) -> RwLockWriteGuard<'_, MyHashMap<K, V, S>> {
+        self.shards[index].write().unwrap()
+    }
+
+    // Sum of the shard sizes, each read at a different instant; while writers
+    // are active this is an estimate
+    pub fn len(&self) -> usize {
+        (0..self.shards.len()).map(|index| self.read_shard(index).len()).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        (0..self.shards.len()).all(|index| self.read_shard(index).is_empty())
+    }
+
+    pub fn clear(&self) {
+        for index in 0..self.shards.len() {
+            self.write_shard(index).clear();
+        }
+    }
+
+    // Calls `f` on every entry, holding each shard's read lock while its entries
+    // are visited. Writers to that shard wait, so `f` should be quick and must
+    // not write to this map.
+    pub fn for_each(&self, mut f: impl FnMut(&K, &V)) {
+        for index in 0..self.shards.len() {
+            for (key, value) in self.read_shard(index).iter() {
+                f(key, value);
+            }
+        }
+    }
+
+    // Copies of every entry, consistent within each shard
+    pub fn snapshot(&self) -> Vec<(K, V)>
+    where
+        K: Clone,
+        V: Clone,
+    {
+        let mut entries = Vec::new();
+        self.for_each(|key, value| entries.push((key.clone(), value.clone())));
+        entries
+    }
+}
+
+impl<K, V, S> ShardedMap<K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher + Default,
+{
+    // Maps the hash onto 0..shards by its high bits (multiply and keep the top
+    // word), leaving the low bits to the shard's own bucket choice
+    fn shard_index<Q: Hash + ?Sized>(&self, key: &Q) -> usize {
+        let hash = self.hash_builder.hash_one(key);
+        ((hash as u128 * self.shards.len() as u128) >> 64) as usize
+    }
+
+    pub fn insert(&self, key: K, value: V) -> Option<V> {
+        self.write_shard(self.shard_index(&key)).insert(key, value)
+    }
+
+    pub fn get<Q>(&self, key: &Q) -> Option<V>
+    where
+        K: std::borrow::Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+        V: Clone,
+    {
+        self.read_shard(self.shard_index(key)).get(key).cloned()
+    }
+
+    // Like `get`, but hands `f` a reference instead of cloning the value
+    pub fn get_with<Q, R>(&self, key: &Q, f: impl FnOnce(&V) -> R) -> Option<R>
+    where
+        K: std::borrow::Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.read_shard(self.shard_index(key)).get(key).map(f)
+    }
+
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: std::borrow::Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.read_shard(self.shard_index(key)).get(key).is_some()
+    }
+
+    pub fn remove<Q>(&self, key: &Q) -> Option<V>
+    where
+        K: std::borrow::Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.write_shard(self.shard_index(key)).remove(key)
+    }
+
+    // Atomically replaces the value of `key` with `f(current)`: Some(value)
+    // stores it, None removes the key. Returns the previous value. The shard
+    // stays write-locked while `f` runs, so `f` must not touch this map.
+    pub fn compute(&self, key: K, f: impl FnOnce(Option<&V>) -> Option<V>) -> Option<V> {
+        let mut shard = self.write_shard(self.shard_index(&key));
+        match shard.get_mut(&key) {
+            Some(current) => match f(Some(current)) {
+                Some(value) => Some(std::mem::replace(current, value)),
+                None => shard.remove(&key),
+            },
+            None => {
+                if let Some(value) = f(None) {
+                    shard.insert(key, value);
+                }
+                None
+            }
+        }
+    }
+}
+
+// Small xorshift generator for the stress tests below
+struct XorShift(u64);
+
+impl XorShift {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, n: u64) -> u64 {
+        self.next() % n
+    }
+}
+
+// 1. Atomic read-modify-write
+//
+// Threads bump shared counters through `compute`. A lost update anywhere
+// (two threads reading the same old value) leaves a total that is too low.
+fn counter_check(threads: usize, increments: usize) {
+    let map: ShardedMap<u64, u64> = ShardedMap::with_shards(8);
+    let keys = 64;
+    let per_thread: Vec<Vec<u64>> = thread::scope(|scope| {
+        let handles: Vec<_> = (0..threads)
+            .map(|t| {
+                let map = &map;
+                scope.spawn(move || {
+                    let mut rng = XorShift(0x9E3779B97F4A7C15 ^ (t as u64 + 1));
+                    let mut counts = vec![0; keys];
+                    for _ in 0..increments {
+                        let key = rng.below(keys as u64);
+                        map.compute(key, |count| Some(count.map_or(1, |c| c + 1)));
+                        counts[key as usize] += 1;
+                    }
+                    counts
+                })
+            })
+            .collect();
+        handles.into_iter().map(|handle| handle.join().unwrap()).collect()
+    });
+    for key in 0..keys {
+        let expected: u64 = per_thread.iter().map(|counts| counts[key]).sum();
+        assert_eq!(map.get(&(key as u64)).unwrap_or(0), expected, "lost updates on key {}", key);
+    }
+    assert_eq!(map.len(), keys);
+}
+
+// 2. Linearizability
+//
+// Threads run short random histories against a few keys, stamping each
+// operation with a shared clock when it is called and when it returns. A
+// history is linearizable if the operations can be put in one order that
+// respects those stamps (an operation that returned before another was called
+// comes first) and in which every result matches a plain sequential map.
+// Linearizability is local, so each key's history is checked on its own with
+// the Wing-Gong search: repeatedly pick an operation that could have taken
+// effect first, apply it to the model, and backtrack on a mismatch.
+#[derive(Clone, Copy, Debug)]
+enum Op {
+    Insert(u64),
+    Remove,
+    Get,
+    // compute(|v| Some(v + 1)), inserting 1 when absent
+    Increment,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Event {
+    key: u64,
+    op: Op,
+    result: Option<u64>,
+    call: u64,
+    ret: u64,
+}
+
+// The sequential model of one key: the new state and the operation's result
+fn apply(op: Op, state: Option<u64>) -> (Option<u64>, Option<u64>) {
+    match op {
+        Op::Insert(value) => (Some(value), state),
+        Op::Remove => (None, state),
+        Op::Get => (state, state),
+        Op::Increment => (Some(state.map_or(1, |v| v + 1)), state),
+    }
+}
+
+// One key's events, at most 64 so a bit set can mark the linearized ones
+fn linearizable(events: &[Event]) -> bool {
+    fn search(events: &[Event], done: u64, state: Option<u64>, failed: &mut HashSet<(u64, Option<u64>)>) -> bool {
+        let all = if events.len() == 64 { u64::MAX } else { (1 << events.len()) - 1 };
+        if done == all {
+            return true;
+        }
+        if failed.contains(&(done, state)) {
+            return false;
+        }
+        let pending = || (0..events.len()).filter(|&i| done & (1 << i) == 0);
+        // Nothing can go first if it was called after some pending operation returned
+        let horizon = pending().map(|i| events[i].ret).min().unwrap();
+        for i in pending().filter(|&i| events[i].call < horizon) {
+            let (next, result) = apply(events[i].op, state);
+            if result == events[i].result && search(events, done | (1 << i), next, failed) {
+                return true;
+            }
+        }
+        failed.insert((done, state));
+        false
+    }
+    assert!(events.len() <= 64);
+    search(events, 0, None, &mut HashSet::new())
+}
+
+fn linearizability_check(rounds: usize, threads: usize, ops_per_thread: usize, keys: u64) {
+    // The checker itself must reject a history no sequential order explains:
+    // a get that saw 7 although the only insert of 7 was called after it returned
+    let impossible = [
+        Event { key: 0, op: Op::Get, result: Some(7), call: 0, ret: 1 },
+        Event { key: 0, op: Op::Insert(7), result: None, call: 2, ret: 3 },
+    ];
+    assert!(!linearizable(&impossible));
+    assert!(linearizable(&[impossible[1], Event { call: 4, ret: 5, ..impossible[0] }]));
+
+    for round in 0..rounds {
+        // Few shards so the threads actually contend
+        let map: ShardedMap<u64, u64> = ShardedMap::with_shards(2);
+        let clock = AtomicU64::new(0);
+        let barrier = Barrier::new(threads);
+        let events: Vec<Event> = thread::scope(|scope| {
+            let handles: Vec<_> = (0..threads)
+                .map(|t| {
+                    let (map, clock, barrier) = (&map, &clock, &barrier);
+                    scope.spawn(move || {
+                        let mut rng = XorShift((round as u64 + 1).wrapping_mul(0x2545F4914F6CDD1D) ^ (t as u64 + 1));
+                        let mut events = Vec::with_capacity(ops_per_thread);
+                        barrier.wait();
+                        for _ in 0..ops_per_thread {
+                            let key = rng.below(keys);
+                            let op = match rng.below(4) {
+                                0 => Op::Insert(rng.below(100)),
+                                1 => Op::Remove,
+                                2 => Op::Get,
+                                _ => Op::Increment,
+                            };
+                            let call = clock.fetch_add(1, AtomicOrdering::SeqCst);
+                            let result = match op {
+                                Op::Insert(value) => map.insert(key, value),
+                                Op::Remove => map.remove(&key),
+                                Op::Get => map.get(&key),
+                                Op::Increment => map.compute(key, |v| Some(v.map_or(1, |v| v + 1))),
+                            };
+                            let ret = clock.fetch_add(1, AtomicOrdering::SeqCst);
+                            events.push(Event { key, op, result, call, ret });
+                        }
+                        events
+                    })
+                })
+                .collect();
+            handles.into_iter().flat_map(|handle| handle.join().unwrap()).collect()
+        });
+        for key in 0..keys {
+            let history: Vec<Event> = events.iter().filter(|e| e.key == key).copied().collect();
+            assert!(linearizable(&history), "round {}: history of key {} is not linearizable: {:?}", round, key, history);
+        }
+    }
+}
+
+// 3. Per-shard snapshots
+//
+// One writer inserts 0, 1, 2, ... in order, so within any shard the keys present
+// at an instant are always a prefix of that shard's keys. Readers check that
+// every shard they visit looks like that, even while shards resize underneath.
+fn snapshot_check(keys: u64, readers: usize) {
+    let map: ShardedMap<u64, u64> = ShardedMap::with_shards(8);
+    let shard_of: Vec<usize> = (0..keys).map(|key| map.shard_index(&key)).collect();
+    let snapshots_taken = AtomicU64::new(0);
+    let writer_done = AtomicBool::new(false);
+    thread::scope(|scope| {
+        let (map, shard_of, snapshots_taken, writer_done) = (&map, &shard_of, &snapshots_taken, &writer_done);
+        scope.spawn(move || {
+            for key in 0..keys {
+                map.insert(key, key * key);
+            }
+            writer_done.store(true, AtomicOrdering::Release);
+        });
+        for _ in 0..readers {
+            scope.spawn(move || loop {
+                let done = writer_done.load(AtomicOrdering::Acquire);
+                let mut seen: Vec<Vec<u64>> = vec![Vec::new(); map.shard_count()];
+                map.for_each(|&key, &value| {
+                    assert_eq!(value, key * key);
+                    seen[shard_of[key as usize]].push(key);
+                });
+                for (shard, keys_seen) in seen.iter_mut().enumerate() {
+                    keys_seen.sort_unstable();
+                    let expected = shard_of.iter().enumerate().filter(|&(_, &s)| s == shard).map(|(key, _)| key as u64);
+                    assert!(keys_seen.iter().copied().eq(expected.take(keys_seen.len())), "shard {} was not seen at one instant", shard);
+                }
+                snapshots_taken.fetch_add(1, AtomicOrdering::Relaxed);
+                if done {
+                    assert_eq!(seen.iter().map(Vec::len).sum::<usize>(), keys as usize);
+                    break;
+                }
+            });
+        }
+    });
+    assert!(snapshots_taken.load(AtomicOrdering::Relaxed) >= readers as u64);
+}
+
+// Throughput of a read-mostly mix (90% get, 10% insert) against one map shared
+// by `threads` threads, in millions of operations per second
+fn throughput(threads: usize, ops: usize, keys: u64, op: &(dyn Fn(bool, u64) + Sync)) -> f64 {
+    let per_thread = ops / threads;
+    let start = Instant::now();
+    thread::scope(|scope| {
+        for t in 0..threads {
+            scope.spawn(move || {
+                let mut rng = XorShift(0xDEAD_BEEF_CAFE_F00D ^ (t as u64 + 1));
+                for _ in 0..per_thread {
+                    let write = rng.below(10) == 0;
+                    op(write, rng.below(keys));
+                }
+            });
+        }
+    });
+    (per_thread * threads) as f64 / start.elapsed().as_secs_f64() / 1e6
+}
+
+fn main() {
+    // Basic usage
+    let map: ShardedMap<String, u32> = ShardedMap::new();
+    thread::scope(|scope| {
+        for word in ["apple", "banana", "apple", "cherry", "banana", "apple"] {
+            let map = &map;
+            scope.spawn(move || {
+                map.compute(word.to_string(), |count| Some(count.map_or(1, |c| c + 1)));
+            });
+        }
+    });
+    let mut counts = map.snapshot();
+    counts.sort();
+    println!("Word counts from 6 threads: {:?}", counts);
+    println!("apple: {:?}, durian: {:?}", map.get("apple"), map.get("durian"));
+    println!("banana, read in place with get_with: {:?}", map.get_with("banana", |count| format!("{} bananas", count)));
+    map.compute("cherry".to_string(), |_| None);
+    println!("After computing cherry away: contains {}, len {}", map.contains_key("cherry"), map.len());
+
+    counter_check(8, 20_000);
+    println!("\nCounter check passed: no lost updates from 8 threads");
+    linearizability_check(500, 3, 8, 3);
+    println!("Linearizability check passed: 500 random 3-thread histories");
+    snapshot_check(50_000, 3);
+    println!("Snapshot check passed: every shard was seen at a single instant");
+
+    // One global Mutex against RwLock shards
+    let ops: usize = std::env::args().nth(1).and_then(|arg| arg.parse().ok()).unwrap_or(400_000);
+    let keys = 100_000;
+    let cores = thread::available_parallelism().map_or(1, |c| c.get());
+    let mut thread_counts = vec![1, 2, 4, 8];
+    thread_counts.retain(|&t| t == 1 || t <= cores.max(2));
+    println!("\nMops/s, 90% get and 10% insert over {} keys:", keys);
+    println!("{:>8} {:>16} {:>16}", "threads", "Mutex<MyHashMap>", "ShardedMap");
+    for threads in thread_counts {
+        let mut prefilled = MyHashMap::new();
+        let sharded: ShardedMap<u64, u64> = ShardedMap::new();
+        for key in 0..keys {
+            prefilled.insert(key, key);
+            sharded.insert(key, key);
+        }
+        let global = Mutex::new(prefilled);
+        let locked = throughput(threads, ops, keys, &|write, key| {
+            let mut map = global.lock().unwrap();
+            if write {
+                map.insert(key, key);
+            } else {
+                std::hint::black_box(map.get(&key));
+            }
+        });
+        let concurrent = throughput(threads, ops, keys, &|write, key| {
+            if write {
+                sharded.insert(key, key);
+            } else {
+                std::hint::black_box(sharded.get(&key));
+            }
+        });
+        println!("{:>8} {:>16.2} {:>16.2}", threads, locked, concurrent);
+    }
+}
@@ -76,30 +76,60 @@ fn coin_change(coins: &[i32], amount: i32) -> i32 {
 }
 
 // 5. Edit Distance
-// Time Complexity: O(mn), Space Complexity: O(mn)
-fn edit_distance(word1: &str, word2: &str) -> usize {
-    let m = word1.len();
-    let n = word2.len();
-    let mut dp = vec![vec![0; n + 1]; m + 1];
-    
-    for i in 0..=m {
-        dp[i][0] = i;
+// Time Complexity: O(mn), Space Complexity: O(n)
+// Row i of the DP table holds the distance from the first i characters of word1
+// to every prefix of word2, and only depends on row i - 1, so one row is kept at
+// a time. The row step is public so a trie can extend a row per edge it walks.
+pub fn edit_distance(word1: &str, word2: &str) -> usize {
+    let target: Vec<char> = word2.chars().collect();
+    let mut row = edit_distance_first_row(target.len());
+    for c in word1.chars() {
+        row = edit_distance_next_row(&row, c, &target, None);
     }
-    for j in 0..=n {
-        dp[0][j] = j;
+    row[target.len()]
+}
+
+// Restricted Damerau-Levenshtein (optimal string alignment): swapping two
+// adjacent characters counts as a single edit
+pub fn damerau_distance(word1: &str, word2: &str) -> usize {
+    let target: Vec<char> = word2.chars().collect();
+    let mut before = Vec::new();
+    let mut row = edit_distance_first_row(target.len());
+    let mut previous = None;
+    for c in word1.chars() {
+        let transposition = previous.map(|p| (before.as_slice(), p));
+        let next = edit_distance_next_row(&row, c, &target, transposition);
+        before = std::mem::replace(&mut row, next);
+        previous = Some(c);
     }
-    
-    for (i, c1) in word1.chars().enumerate() {
-        for (j, c2) in word2.chars().enumerate() {
-            if c1 == c2 {
-                dp[i + 1][j + 1] = dp[i][j];
-            } else {
-                dp[i + 1][j + 1] = 1 + dp[i][j].min(dp[i + 1][j]).min(dp[i][j + 1]);
+    row[target.len()]
+}
+
+// Distances from the empty string to every prefix of the target
+pub fn edit_distance_first_row(target_len: usize) -> Vec<usize> {
+    (0..=target_len).collect()
+}
+
+// Extend the source by character `c`: given `prev[j]` = distance(source, target[..j]),
+// return distance(source + c, target[..j]) for every j. For transpositions, pass the
+// row before `prev` and the last character of the source.
+pub fn edit_distance_next_row(prev: &[usize], c: char, target: &[char], transposition: Option<(&[usize], char)>) -> Vec<usize> {
+    let mut row = Vec::with_capacity(prev.len());
+    row.push(prev[0] + 1);
+    for (j, &t) in target.iter().enumerate() {
+        let mut value = if c == t {
+            prev[j]
+        } else {
+            1 + prev[j].min(row[j]).min(prev[j + 1])
+        };
+        if let Some((before, previous)) = transposition {
+            if j > 0 && c == target[j - 1] && previous == t {
+                value = value.min(before[j - 1] + 1);
             }
         }
+        row.push(value);
     }
-    
-    dp[m][n]
+    row
 }
 
 // 6. Longest Common Subsequence (LCS)
@@ -156,6 +186,7 @@ fn main() {
     println!("Coin Change (amount 11): {}", coin_change(&coins, 11));
     
     println!("Edit Distance between 'horse' and 'ros': {}", edit_distance("horse", "ros"));
+    println!("Edit Distance between 'form' and 'from': {} (Damerau: {})", edit_distance("form", "from"), damerau_distance("form", "from"));
     
     println!("Longest Common Subsequence of 'abcde' and 'ace': {}", longest_common_subsequence("abcde", "ace"));
     
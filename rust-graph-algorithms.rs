@@ -1,24 +1,89 @@
 use std::collections::{HashMap, HashSet, VecDeque, BinaryHeap};
 use std::cmp::Ordering;
+use std::fmt;
 
 // Graph representation using adjacency list
-type Graph = HashMap<usize, Vec<(usize, i32)>>;
+pub type Graph = HashMap<usize, Vec<(usize, i32)>>;
+
+// Errors reported by the graph and pathfinding algorithms, carrying the offending
+// node, edge or cycle. rust-pathfinding-algorithms.rs and
+// complex-a-star-pathfinding.rs include this file to share it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GraphError<N> {
+    UnknownNode(N),
+    BlockedNode(N),
+    DanglingEdge { from: N, to: N },
+    NegativeWeight { from: N, to: N, weight: i32 },
+    NoPath { from: N, to: N },
+    NegativeCycle(Vec<N>),
+    CycleDetected(Vec<N>),
+}
+
+impl<N: fmt::Debug> fmt::Display for GraphError<N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GraphError::UnknownNode(node) => write!(f, "unknown node {:?}", node),
+            GraphError::BlockedNode(node) => write!(f, "node {:?} is blocked", node),
+            GraphError::DanglingEdge { from, to } => {
+                write!(f, "edge {:?} -> {:?} points outside the graph", from, to)
+            }
+            GraphError::NegativeWeight { from, to, weight } => {
+                write!(f, "edge {:?} -> {:?} has negative weight {}", from, to, weight)
+            }
+            GraphError::NoPath { from, to } => write!(f, "no path from {:?} to {:?}", from, to),
+            GraphError::NegativeCycle(cycle) => write!(f, "negative-weight cycle {:?}", cycle),
+            GraphError::CycleDetected(cycle) => write!(f, "cycle detected {:?}", cycle),
+        }
+    }
+}
+
+impl<N: fmt::Debug> std::error::Error for GraphError<N> {}
+
+// Every node is a key of the adjacency list, sinks included with no edges, so
+// an edge whose target is not a key is dangling
+fn validate(graph: &Graph) -> Result<(), GraphError<usize>> {
+    for (&u, edges) in graph {
+        for &(v, _) in edges {
+            if !graph.contains_key(&v) {
+                return Err(GraphError::DanglingEdge { from: u, to: v });
+            }
+        }
+    }
+    Ok(())
+}
+
+fn check_node(graph: &Graph, node: usize) -> Result<(), GraphError<usize>> {
+    if graph.contains_key(&node) {
+        Ok(())
+    } else {
+        Err(GraphError::UnknownNode(node))
+    }
+}
 
 // 1. Depth-First Search (DFS)
-fn dfs(graph: &Graph, start: usize, visited: &mut HashSet<usize>) {
-    if !visited.insert(start) {
+fn dfs(graph: &Graph, start: usize, visited: &mut HashSet<usize>) -> Result<(), GraphError<usize>> {
+    validate(graph)?;
+    check_node(graph, start)?;
+    dfs_visit(graph, start, visited);
+    Ok(())
+}
+
+fn dfs_visit(graph: &Graph, node: usize, visited: &mut HashSet<usize>) {
+    if !visited.insert(node) {
         return;
     }
-    println!("Visited: {}", start);
-    if let Some(neighbors) = graph.get(&start) {
+    println!("Visited: {}", node);
+    if let Some(neighbors) = graph.get(&node) {
         for &(next, _) in neighbors {
-            dfs(graph, next, visited);
+            dfs_visit(graph, next, visited);
         }
     }
 }
 
 // 2. Breadth-First Search (BFS)
-fn bfs(graph: &Graph, start: usize) {
+fn bfs(graph: &Graph, start: usize) -> Result<(), GraphError<usize>> {
+    validate(graph)?;
+    check_node(graph, start)?;
     let mut visited = HashSet::new();
     let mut queue = VecDeque::new();
 
@@ -35,6 +100,8 @@ fn bfs(graph: &Graph, start: usize) {
             }
         }
     }
+
+    Ok(())
 }
 
 // 3. Dijkstra's Shortest Path Algorithm
@@ -56,7 +123,19 @@ impl PartialOrd for State {
     }
 }
 
-fn dijkstra(graph: &Graph, start: usize, end: usize) -> Option<(Vec<usize>, i32)> {
+pub fn dijkstra(graph: &Graph, start: usize, end: usize) -> Result<(Vec<usize>, i32), GraphError<usize>> {
+    validate(graph)?;
+    check_node(graph, start)?;
+    check_node(graph, end)?;
+    // Dijkstra is only correct for non-negative weights
+    for (&u, edges) in graph {
+        for &(v, weight) in edges {
+            if weight < 0 {
+                return Err(GraphError::NegativeWeight { from: u, to: v, weight });
+            }
+        }
+    }
+
     let mut dist: HashMap<usize, i32> = HashMap::new();
     let mut heap = BinaryHeap::new();
     let mut prev: HashMap<usize, usize> = HashMap::new();
@@ -73,7 +152,7 @@ fn dijkstra(graph: &Graph, start: usize, end: usize) -> Option<(Vec<usize>, i32)
                 current = previous;
             }
             path.reverse();
-            return Some((path, cost));
+            return Ok((path, cost));
         }
 
         if cost > *dist.get(&node).unwrap_or(&i32::MAX) {
@@ -92,18 +171,28 @@ fn dijkstra(graph: &Graph, start: usize, end: usize) -> Option<(Vec<usize>, i32)
         }
     }
 
-    None
+    Err(GraphError::NoPath { from: start, to: end })
 }
 
 // 4. Bellman-Ford Algorithm
-fn bellman_ford(graph: &Graph, start: usize, n: usize) -> Option<HashMap<usize, i32>> {
+fn bellman_ford(graph: &Graph, start: usize, n: usize) -> Result<HashMap<usize, i32>, GraphError<usize>> {
+    validate(graph)?;
+    if start >= n {
+        return Err(GraphError::UnknownNode(start));
+    }
+    if let Some(&u) = graph.keys().find(|&&u| u >= n) {
+        return Err(GraphError::UnknownNode(u));
+    }
+
     let mut dist: HashMap<usize, i32> = (0..n).map(|i| (i, if i == start { 0 } else { i32::MAX })).collect();
+    let mut prev: HashMap<usize, usize> = HashMap::new();
 
     for _ in 0..n-1 {
         for (&u, edges) in graph {
             for &(v, weight) in edges {
                 if dist[&u] != i32::MAX && dist[&u] + weight < dist[&v] {
                     dist.insert(v, dist[&u] + weight);
+                    prev.insert(v, u);
                 }
             }
         }
@@ -113,21 +202,49 @@ fn bellman_ford(graph: &Graph, start: usize, n: usize) -> Option<HashMap<usize,
     for (&u, edges) in graph {
         for &(v, weight) in edges {
             if dist[&u] != i32::MAX && dist[&u] + weight < dist[&v] {
-                return None; // Negative-weight cycle detected
+                prev.insert(v, u);
+                return Err(GraphError::NegativeCycle(negative_cycle(&prev, v, n)));
             }
         }
     }
 
-    Some(dist)
+    Ok(dist)
+}
+
+// Walk n predecessor links to land inside the cycle, then collect it
+fn negative_cycle(prev: &HashMap<usize, usize>, from: usize, n: usize) -> Vec<usize> {
+    let mut node = from;
+    for _ in 0..n {
+        node = prev[&node];
+    }
+    let mut cycle = vec![node];
+    let mut current = prev[&node];
+    while current != node {
+        cycle.push(current);
+        current = prev[&current];
+    }
+    cycle.reverse();
+    close_cycle(cycle)
+}
+
+// Rotate a cycle to start at its smallest node and repeat that node at the end,
+// so a cycle reads the same whichever node the search entered it from
+fn close_cycle(mut cycle: Vec<usize>) -> Vec<usize> {
+    let smallest = (0..cycle.len()).min_by_key(|&i| cycle[i]).unwrap();
+    cycle.rotate_left(smallest);
+    cycle.push(cycle[0]);
+    cycle
 }
 
 // 5. Prim's Minimum Spanning Tree Algorithm
-fn prim_mst(graph: &Graph) -> Vec<(usize, usize, i32)> {
+fn prim_mst(graph: &Graph) -> Result<Vec<(usize, usize, i32)>, GraphError<usize>> {
     let mut mst = Vec::new();
     let mut visited = HashSet::new();
     let mut heap = BinaryHeap::new();
 
     // Start with node 0
+    validate(graph)?;
+    check_node(graph, 0)?;
     visited.insert(0);
     if let Some(edges) = graph.get(&0) {
         for &(v, weight) in edges {
@@ -148,11 +265,12 @@ fn prim_mst(graph: &Graph) -> Vec<(usize, usize, i32)> {
         }
     }
 
-    mst
+    Ok(mst)
 }
 
 // 6. Topological Sort
-fn topological_sort(graph: &Graph) -> Option<Vec<usize>> {
+fn topological_sort(graph: &Graph) -> Result<Vec<usize>, GraphError<usize>> {
+    validate(graph)?;
     let mut in_degree: HashMap<usize, usize> = HashMap::new();
     for edges in graph.values() {
         for &(v, _) in edges {
@@ -177,7 +295,33 @@ fn topological_sort(graph: &Graph) -> Option<Vec<usize>> {
         }
     }
 
-    if result.len() == graph.len() { Some(result) } else { None }
+    if result.len() == graph.len() {
+        return Ok(result);
+    }
+
+    // Every node left over still has an incoming edge from another left-over node,
+    // so walking those edges backwards must eventually revisit a node
+    let remaining: HashSet<usize> = in_degree.iter().filter(|&(_, &d)| d > 0).map(|(&k, _)| k).collect();
+    let mut parent: HashMap<usize, usize> = HashMap::new();
+    for (&u, edges) in graph {
+        if remaining.contains(&u) {
+            for &(v, _) in edges {
+                if remaining.contains(&v) {
+                    parent.insert(v, u);
+                }
+            }
+        }
+    }
+    let mut seen = Vec::new();
+    let mut node = *remaining.iter().min().unwrap();
+    while !seen.contains(&node) {
+        seen.push(node);
+        node = parent[&node];
+    }
+    let start = seen.iter().position(|&n| n == node).unwrap();
+    let mut cycle: Vec<usize> = seen[start..].to_vec();
+    cycle.reverse();
+    Err(GraphError::CycleDetected(close_cycle(cycle)))
 }
 
 fn main() {
@@ -191,42 +335,106 @@ fn main() {
     // 1. DFS
     println!("DFS:");
     let mut visited = HashSet::new();
-    dfs(&graph, 0, &mut visited);
+    if let Err(e) = dfs(&graph, 0, &mut visited) {
+        println!("Error: {}", e);
+    }
 
     // 2. BFS
     println!("\nBFS:");
-    bfs(&graph, 0);
+    if let Err(e) = bfs(&graph, 0) {
+        println!("Error: {}", e);
+    }
 
     // 3. Dijkstra's Algorithm
     println!("\nDijkstra's Shortest Path:");
-    if let Some((path, cost)) = dijkstra(&graph, 0, 3) {
-        println!("Path: {:?}, Cost: {}", path, cost);
-    } else {
-        println!("No path found");
+    match dijkstra(&graph, 0, 3) {
+        Ok((path, cost)) => println!("Path: {:?}, Cost: {}", path, cost),
+        Err(e) => println!("Error: {}", e),
+    }
+    match dijkstra(&graph, 3, 0) {
+        Ok((path, cost)) => println!("Path: {:?}, Cost: {}", path, cost),
+        Err(e) => println!("Error: {}", e),
     }
 
     // 4. Bellman-Ford Algorithm
     println!("\nBellman-Ford Shortest Paths:");
-    if let Some(distances) = bellman_ford(&graph, 0, 4) {
-        for (node, dist) in distances {
-            println!("Node {}: Distance {}", node, dist);
+    match bellman_ford(&graph, 0, 4) {
+        Ok(distances) => {
+            for (node, dist) in distances {
+                println!("Node {}: Distance {}", node, dist);
+            }
         }
-    } else {
-        println!("Negative cycle detected");
+        Err(e) => println!("Error: {}", e),
     }
 
     // 5. Prim's MST Algorithm
     println!("\nPrim's Minimum Spanning Tree:");
-    let mst = prim_mst(&graph);
-    for (u, v, weight) in mst {
-        println!("Edge ({}, {}): Weight {}", u, v, weight);
+    match prim_mst(&graph) {
+        Ok(mst) => {
+            for (u, v, weight) in mst {
+                println!("Edge ({}, {}): Weight {}", u, v, weight);
+            }
+        }
+        Err(e) => println!("Error: {}", e),
     }
 
     // 6. Topological Sort
     println!("\nTopological Sort:");
-    if let Some(order) = topological_sort(&graph) {
-        println!("Topological Order: {:?}", order);
-    } else {
-        println!("Graph contains a cycle");
+    match topological_sort(&graph) {
+        Ok(order) => println!("Topological Order: {:?}", order),
+        Err(e) => println!("Error: {}", e),
+    }
+
+    assert_eq!(dijkstra(&graph, 0, 3), Ok((vec![0, 2, 1, 3], 4)));
+    assert_eq!(dijkstra(&graph, 3, 0), Err(GraphError::NoPath { from: 3, to: 0 }));
+    assert_eq!(bellman_ford(&graph, 0, 4).map(|d| d[&3]), Ok(4));
+    assert_eq!(topological_sort(&graph), Ok(vec![0, 2, 1, 3]));
+
+    // Error cases
+    println!("\nError Reporting:");
+    let mut cyclic: Graph = HashMap::new();
+    cyclic.insert(0, vec![(1, 1)]);
+    cyclic.insert(1, vec![(2, -3)]);
+    cyclic.insert(2, vec![(0, 1)]);
+    for result in [bfs(&cyclic, 7), dfs(&cyclic, 7, &mut HashSet::new())] {
+        println!("Search from 7: {}", result.clone().unwrap_err());
+        assert_eq!(result, Err(GraphError::UnknownNode(7)));
     }
+    assert_eq!(dijkstra(&cyclic, 0, 7), Err(GraphError::UnknownNode(7)));
+    assert_eq!(bellman_ford(&cyclic, 5, 3), Err(GraphError::UnknownNode(5)));
+    assert_eq!(bellman_ford(&cyclic, 0, 2), Err(GraphError::UnknownNode(2)));
+
+    let negative = dijkstra(&cyclic, 0, 2);
+    println!("Dijkstra: {}", negative.clone().unwrap_err());
+    assert_eq!(negative, Err(GraphError::NegativeWeight { from: 1, to: 2, weight: -3 }));
+
+    let cycle = bellman_ford(&cyclic, 0, 3);
+    println!("Bellman-Ford: {}", cycle.clone().unwrap_err());
+    assert_eq!(cycle, Err(GraphError::NegativeCycle(vec![0, 1, 2, 0])));
+    let order = topological_sort(&cyclic);
+    println!("Topological Sort: {}", order.clone().unwrap_err());
+    assert_eq!(order, Err(GraphError::CycleDetected(vec![0, 1, 2, 0])));
+
+    // A cycle reached through a tail is reported without the tail, from its smallest node
+    let mut tailed: Graph = HashMap::new();
+    tailed.insert(0, vec![(1, 2)]);
+    tailed.insert(1, vec![(2, 1)]);
+    tailed.insert(2, vec![(3, -4)]);
+    tailed.insert(3, vec![(1, 1), (4, 1)]);
+    tailed.insert(4, vec![]);
+    assert_eq!(bellman_ford(&tailed, 0, 5), Err(GraphError::NegativeCycle(vec![1, 2, 3, 1])));
+    assert_eq!(topological_sort(&tailed), Err(GraphError::CycleDetected(vec![1, 2, 3, 1])));
+
+    // A sink must still be a key; every function rejects the edge into it the same way
+    let mut dangling: Graph = HashMap::new();
+    dangling.insert(0, vec![(1, 1)]);
+    let expected = Err(GraphError::DanglingEdge { from: 0, to: 1 });
+    println!("Dangling edge: {}", expected.clone().unwrap_err());
+    assert_eq!(bfs(&dangling, 0), expected);
+    assert_eq!(dfs(&dangling, 0, &mut HashSet::new()), expected);
+    assert_eq!(dijkstra(&dangling, 0, 0).map(|_| ()), expected);
+    assert_eq!(bellman_ford(&dangling, 0, 2).map(|_| ()), expected);
+    assert_eq!(prim_mst(&dangling).map(|_| ()), expected);
+    assert_eq!(topological_sort(&dangling).map(|_| ()), expected);
+    println!("All error cases reported as expected");
 }
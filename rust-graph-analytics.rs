@@ -0,0 +1,347 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+
+// Graph representation using adjacency list (same shape as rust-graph-algorithms.rs)
+type Graph = HashMap<usize, Vec<(usize, i32)>>;
+
+// Every node id that appears as a source or a target, in ascending order
+fn nodes(graph: &Graph) -> Vec<usize> {
+    let mut all: BTreeSet<usize> = graph.keys().copied().collect();
+    for edges in graph.values() {
+        all.extend(edges.iter().map(|&(v, _)| v));
+    }
+    all.into_iter().collect()
+}
+
+fn successors(graph: &Graph, u: usize) -> impl Iterator<Item = usize> + '_ {
+    graph.get(&u).into_iter().flatten().map(|&(v, _)| v)
+}
+
+// Neighbor sets with edge direction ignored
+fn undirected_neighbors(graph: &Graph) -> BTreeMap<usize, BTreeSet<usize>> {
+    let mut adj: BTreeMap<usize, BTreeSet<usize>> = nodes(graph).into_iter().map(|u| (u, BTreeSet::new())).collect();
+    for (&u, edges) in graph {
+        for &(v, _) in edges {
+            if u != v {
+                adj.get_mut(&u).unwrap().insert(v);
+                adj.get_mut(&v).unwrap().insert(u);
+            }
+        }
+    }
+    adj
+}
+
+// 1. PageRank
+// Rank held by nodes without out-edges is spread evenly over all nodes.
+// Iteration stops once the L1 change between rounds drops below `tolerance`.
+fn pagerank(graph: &Graph, damping: f64, tolerance: f64, max_iterations: usize) -> HashMap<usize, f64> {
+    let nodes = nodes(graph);
+    let n = nodes.len() as f64;
+    let mut rank: HashMap<usize, f64> = nodes.iter().map(|&u| (u, 1.0 / n)).collect();
+
+    for _ in 0..max_iterations {
+        let dangling: f64 = nodes.iter().filter(|&&u| successors(graph, u).next().is_none()).map(|u| rank[u]).sum();
+        let base = (1.0 - damping) / n + damping * dangling / n;
+        let mut next: HashMap<usize, f64> = nodes.iter().map(|&u| (u, base)).collect();
+        for &u in &nodes {
+            let out: Vec<usize> = successors(graph, u).collect();
+            for v in &out {
+                *next.get_mut(v).unwrap() += damping * rank[&u] / out.len() as f64;
+            }
+        }
+        let delta: f64 = nodes.iter().map(|u| (rank[u] - next[u]).abs()).sum();
+        rank = next;
+        if delta < tolerance {
+            break;
+        }
+    }
+
+    rank
+}
+
+// 2. Betweenness Centrality (Brandes), treating edges as unweighted.
+// For an undirected graph stored with both directions, pass `undirected` so each
+// pair of endpoints is only counted once.
+fn betweenness_centrality(graph: &Graph, undirected: bool) -> HashMap<usize, f64> {
+    let nodes = nodes(graph);
+    let mut centrality: HashMap<usize, f64> = nodes.iter().map(|&u| (u, 0.0)).collect();
+
+    for &s in &nodes {
+        let mut order = Vec::new();
+        let mut preds: HashMap<usize, Vec<usize>> = HashMap::new();
+        let mut sigma: HashMap<usize, f64> = HashMap::from([(s, 1.0)]);
+        let mut dist: HashMap<usize, usize> = HashMap::from([(s, 0)]);
+        let mut queue = VecDeque::from([s]);
+
+        while let Some(v) = queue.pop_front() {
+            order.push(v);
+            for w in successors(graph, v) {
+                if !dist.contains_key(&w) {
+                    dist.insert(w, dist[&v] + 1);
+                    queue.push_back(w);
+                }
+                if dist[&w] == dist[&v] + 1 {
+                    *sigma.entry(w).or_insert(0.0) += sigma[&v];
+                    preds.entry(w).or_default().push(v);
+                }
+            }
+        }
+
+        // Accumulate dependencies in order of non-increasing distance from s
+        let mut delta: HashMap<usize, f64> = HashMap::new();
+        for &w in order.iter().rev() {
+            let dw = delta.get(&w).copied().unwrap_or(0.0);
+            for &v in preds.get(&w).into_iter().flatten() {
+                *delta.entry(v).or_insert(0.0) += sigma[&v] / sigma[&w] * (1.0 + dw);
+            }
+            if w != s {
+                *centrality.get_mut(&w).unwrap() += dw;
+            }
+        }
+    }
+
+    if undirected {
+        for value in centrality.values_mut() {
+            *value /= 2.0;
+        }
+    }
+    centrality
+}
+
+// 3. Closeness Centrality: (reachable - 1) / sum of hop distances to reachable nodes
+fn closeness_centrality(graph: &Graph) -> HashMap<usize, f64> {
+    nodes(graph)
+        .into_iter()
+        .map(|s| {
+            let mut dist: HashMap<usize, usize> = HashMap::from([(s, 0)]);
+            let mut queue = VecDeque::from([s]);
+            while let Some(v) = queue.pop_front() {
+                for w in successors(graph, v) {
+                    if !dist.contains_key(&w) {
+                        dist.insert(w, dist[&v] + 1);
+                        queue.push_back(w);
+                    }
+                }
+            }
+            let total: usize = dist.values().sum();
+            let closeness = if total == 0 { 0.0 } else { (dist.len() - 1) as f64 / total as f64 };
+            (s, closeness)
+        })
+        .collect()
+}
+
+// 4. Local Clustering Coefficients: the fraction of a node's neighbor pairs that are
+// themselves connected, with edge direction ignored
+fn clustering_coefficients(graph: &Graph) -> HashMap<usize, f64> {
+    let adj = undirected_neighbors(graph);
+    adj.iter()
+        .map(|(&u, neighbors)| {
+            let k = neighbors.len();
+            if k < 2 {
+                return (u, 0.0);
+            }
+            let links = neighbors
+                .iter()
+                .map(|v| adj[v].iter().filter(|w| *w > v && neighbors.contains(w)).count())
+                .sum::<usize>();
+            (u, 2.0 * links as f64 / (k * (k - 1)) as f64)
+        })
+        .collect()
+}
+
+fn average_clustering(graph: &Graph) -> f64 {
+    let coefficients = clustering_coefficients(graph);
+    if coefficients.is_empty() {
+        return 0.0;
+    }
+    coefficients.values().sum::<f64>() / coefficients.len() as f64
+}
+
+// 5. Louvain Community Detection
+// Edges are treated as undirected with their weight as strength. Each level moves
+// nodes greedily to the neighboring community with the best modularity gain, then
+// collapses communities into single nodes; this repeats until nothing moves.
+// Nodes and communities are visited in ascending order, so the result is deterministic.
+fn louvain(graph: &Graph) -> HashMap<usize, usize> {
+    let ids = nodes(graph);
+    let index: HashMap<usize, usize> = ids.iter().enumerate().map(|(i, &u)| (u, i)).collect();
+    let mut adj: Vec<BTreeMap<usize, f64>> = vec![BTreeMap::new(); ids.len()];
+    for (&u, edges) in graph {
+        for &(v, w) in edges {
+            let (i, j) = (index[&u], index[&v]);
+            *adj[i].entry(j).or_insert(0.0) += w as f64;
+            *adj[j].entry(i).or_insert(0.0) += w as f64;
+        }
+    }
+
+    let mut membership: Vec<usize> = (0..ids.len()).collect();
+    loop {
+        let (community, moved) = louvain_level(&adj);
+        if !moved {
+            break;
+        }
+        // Renumber communities densely in order of first appearance
+        let mut renumber: HashMap<usize, usize> = HashMap::new();
+        let community: Vec<usize> = community
+            .into_iter()
+            .map(|c| {
+                let next = renumber.len();
+                *renumber.entry(c).or_insert(next)
+            })
+            .collect();
+        for m in membership.iter_mut() {
+            *m = community[*m];
+        }
+        let mut collapsed: Vec<BTreeMap<usize, f64>> = vec![BTreeMap::new(); renumber.len()];
+        for (i, edges) in adj.iter().enumerate() {
+            for (&j, &w) in edges {
+                *collapsed[community[i]].entry(community[j]).or_insert(0.0) += w;
+            }
+        }
+        adj = collapsed;
+    }
+
+    ids.iter().enumerate().map(|(i, &u)| (u, membership[i])).collect()
+}
+
+fn louvain_level(adj: &[BTreeMap<usize, f64>]) -> (Vec<usize>, bool) {
+    let n = adj.len();
+    let degree: Vec<f64> = adj.iter().map(|edges| edges.values().sum()).collect();
+    let m2: f64 = degree.iter().sum();
+    let mut community: Vec<usize> = (0..n).collect();
+    let mut total = degree.clone();
+    let mut moved_any = false;
+    if m2 == 0.0 {
+        return (community, false);
+    }
+
+    loop {
+        let mut moved = false;
+        for i in 0..n {
+            let current = community[i];
+            total[current] -= degree[i];
+
+            let mut links: BTreeMap<usize, f64> = BTreeMap::new();
+            for (&j, &w) in &adj[i] {
+                if j != i {
+                    *links.entry(community[j]).or_insert(0.0) += w;
+                }
+            }
+            let gain = |c: usize, w: f64| w - total[c] * degree[i] / m2;
+            let mut best = current;
+            let mut best_gain = gain(current, links.get(&current).copied().unwrap_or(0.0));
+            for (&c, &w) in &links {
+                let g = gain(c, w);
+                if g > best_gain + 1e-12 {
+                    best = c;
+                    best_gain = g;
+                }
+            }
+
+            total[best] += degree[i];
+            if best != current {
+                community[i] = best;
+                moved = true;
+                moved_any = true;
+            }
+        }
+        if !moved {
+            break;
+        }
+    }
+
+    (community, moved_any)
+}
+
+// Newman modularity of a partition, with the same undirected reading as `louvain`
+fn modularity(graph: &Graph, community: &HashMap<usize, usize>) -> f64 {
+    let mut inside: HashMap<usize, f64> = HashMap::new();
+    let mut total: HashMap<usize, f64> = HashMap::new();
+    let mut m2 = 0.0;
+    for (&u, edges) in graph {
+        for &(v, w) in edges {
+            let w = w as f64;
+            m2 += 2.0 * w;
+            *total.entry(community[&u]).or_insert(0.0) += w;
+            *total.entry(community[&v]).or_insert(0.0) += w;
+            if community[&u] == community[&v] {
+                *inside.entry(community[&u]).or_insert(0.0) += 2.0 * w;
+            }
+        }
+    }
+    if m2 == 0.0 {
+        return 0.0;
+    }
+    total.iter().map(|(c, &t)| inside.get(c).copied().unwrap_or(0.0) / m2 - (t / m2).powi(2)).sum()
+}
+
+fn undirected(edges: &[(usize, usize)]) -> Graph {
+    let mut graph: Graph = HashMap::new();
+    for &(u, v) in edges {
+        graph.entry(u).or_default().push((v, 1));
+        graph.entry(v).or_default().push((u, 1));
+    }
+    graph
+}
+
+fn close(a: f64, b: f64) -> bool {
+    (a - b).abs() < 1e-6
+}
+
+fn main() {
+    // Example graph
+    let mut graph: Graph = HashMap::new();
+    graph.insert(0, vec![(1, 4), (2, 1)]);
+    graph.insert(1, vec![(3, 1)]);
+    graph.insert(2, vec![(1, 2), (3, 5)]);
+    graph.insert(3, vec![(0, 1)]);
+
+    let mut ranks: Vec<(usize, f64)> = pagerank(&graph, 0.85, 1e-10, 100).into_iter().collect();
+    ranks.sort_by_key(|&(u, _)| u);
+    println!("PageRank: {:?}", ranks);
+
+    // 1. PageRank is uniform on a directed cycle
+    let mut cycle: Graph = HashMap::new();
+    cycle.insert(0, vec![(1, 1)]);
+    cycle.insert(1, vec![(2, 1)]);
+    cycle.insert(2, vec![(0, 1)]);
+    let cycle_ranks = pagerank(&cycle, 0.85, 1e-12, 100);
+    assert!(cycle_ranks.values().all(|&r| close(r, 1.0 / 3.0)));
+    println!("Cycle PageRank: {:?}", cycle_ranks.iter().collect::<BTreeMap<_, _>>());
+
+    // 2. Betweenness: the center of a star lies on all C(4, 2) = 6 leaf-to-leaf paths
+    let star = undirected(&[(0, 1), (0, 2), (0, 3), (0, 4)]);
+    let star_betweenness = betweenness_centrality(&star, true);
+    assert!(close(star_betweenness[&0], 6.0));
+    assert!((1..=4).all(|leaf| close(star_betweenness[&leaf], 0.0)));
+    println!("Star Betweenness: center {}, leaf {}", star_betweenness[&0], star_betweenness[&1]);
+
+    let path = undirected(&[(0, 1), (1, 2), (2, 3)]);
+    let path_betweenness = betweenness_centrality(&path, true);
+    assert!(close(path_betweenness[&1], 2.0) && close(path_betweenness[&2], 2.0));
+
+    // 3. Closeness on the path 0-1-2-3: ends see distances 1+2+3, middles 1+1+2
+    let closeness = closeness_centrality(&path);
+    assert!(close(closeness[&0], 0.5) && close(closeness[&1], 0.75));
+    println!("Path Closeness: end {}, middle {}", closeness[&0], closeness[&1]);
+
+    // 4. Clustering: a triangle with a pendant node attached to vertex 2
+    let kite = undirected(&[(0, 1), (1, 2), (2, 0), (2, 3)]);
+    let clustering = clustering_coefficients(&kite);
+    assert!(close(clustering[&0], 1.0) && close(clustering[&2], 1.0 / 3.0) && close(clustering[&3], 0.0));
+    println!("Kite Clustering: {:?}, average {:.4}", clustering.iter().collect::<BTreeMap<_, _>>(), average_clustering(&kite));
+
+    // 5. Louvain: two triangles joined by a single bridge split into two communities
+    let barbell = undirected(&[(0, 1), (1, 2), (2, 0), (3, 4), (4, 5), (5, 3), (2, 3)]);
+    let communities = louvain(&barbell);
+    assert_eq!(communities[&0], communities[&1]);
+    assert_eq!(communities[&1], communities[&2]);
+    assert_eq!(communities[&3], communities[&4]);
+    assert_eq!(communities[&4], communities[&5]);
+    assert_ne!(communities[&0], communities[&3]);
+    let q = modularity(&barbell, &communities);
+    assert!(close(q, 5.0 / 14.0));
+    println!("Barbell Communities: {:?}, modularity {:.4}", communities.iter().collect::<BTreeMap<_, _>>(), q);
+
+    let groups: HashSet<usize> = louvain(&graph).into_values().collect();
+    println!("Example graph has {} communities", groups.len());
+}
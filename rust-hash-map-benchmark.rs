@@ -0,0 +1,296 @@
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
+use std::hash::BuildHasher;
+use std::hint::black_box;
+use std::time::Instant;
+
+// Hash map benchmarks: the chained MyHashMap and the open-addressing
+// RobinHoodMap from rust-hash-structures.rs against std::collections::HashMap.
+// By default all three hash with SipHash, so the numbers compare table layouts
+// rather than hash functions. With the fast-hashers feature each map is also
+// run with FxHash, FNV-1a and the folded-multiply hasher:
+//
+//     rustc -O --edition 2021 rust-hash-map-benchmark.rs
+//     rustc -O --edition 2021 --cfg 'feature="fast-hashers"' rust-hash-map-benchmark.rs
+//     ./rust-hash-map-benchmark 1000000
+//
+// Like the ordered set benchmarks there is no Cargo manifest to hang criterion
+// off, so every workload is generated up front, run several times per map with
+// std::time::Instant, and the median nanoseconds per operation is reported.
+
+#[path = "rust-hash-structures.rs"]
+#[allow(dead_code)]
+mod hash_structures;
+
+use hash_structures::{MyHashMap, RobinHoodMap};
+
+#[cfg(feature = "fast-hashers")]
+use hash_structures::fast_hashers::{FnvBuildHasher, FoldBuildHasher, FxBuildHasher};
+
+// Name of a hasher in the result rows
+trait BenchHasher: BuildHasher + Default {
+    const NAME: &'static str;
+}
+
+impl BenchHasher for RandomState {
+    const NAME: &'static str = "SipHash";
+}
+
+#[cfg(feature = "fast-hashers")]
+impl BenchHasher for FxBuildHasher {
+    const NAME: &'static str = "FxHash";
+}
+
+#[cfg(feature = "fast-hashers")]
+impl BenchHasher for FnvBuildHasher {
+    const NAME: &'static str = "FNV-1a";
+}
+
+#[cfg(feature = "fast-hashers")]
+impl BenchHasher for FoldBuildHasher {
+    const NAME: &'static str = "folded";
+}
+
+trait BenchMap {
+    const NAME: &'static str;
+
+    const HASHER: &'static str;
+
+    fn new() -> Self;
+
+    fn insert(&mut self, key: u64, value: u64) -> Option<u64>;
+
+    fn get(&self, key: u64) -> Option<u64>;
+
+    fn remove(&mut self, key: u64) -> Option<u64>;
+
+    fn len(&self) -> usize;
+}
+
+impl<S: BenchHasher> BenchMap for MyHashMap<u64, u64, S> {
+    const NAME: &'static str = "MyHashMap";
+
+    const HASHER: &'static str = S::NAME;
+
+    fn new() -> Self {
+        MyHashMap::default()
+    }
+
+    fn insert(&mut self, key: u64, value: u64) -> Option<u64> {
+        MyHashMap::insert(self, key, value)
+    }
+
+    fn get(&self, key: u64) -> Option<u64> {
+        MyHashMap::get(self, &key).copied()
+    }
+
+    fn remove(&mut self, key: u64) -> Option<u64> {
+        MyHashMap::remove(self, &key)
+    }
+
+    fn len(&self) -> usize {
+        MyHashMap::len(self)
+    }
+}
+
+impl<S: BenchHasher> BenchMap for RobinHoodMap<u64, u64, S> {
+    const NAME: &'static str = "RobinHoodMap";
+
+    const HASHER: &'static str = S::NAME;
+
+    fn new() -> Self {
+        RobinHoodMap::default()
+    }
+
+    fn insert(&mut self, key: u64, value: u64) -> Option<u64> {
+        RobinHoodMap::insert(self, key, value)
+    }
+
+    fn get(&self, key: u64) -> Option<u64> {
+        RobinHoodMap::get(self, &key).copied()
+    }
+
+    fn remove(&mut self, key: u64) -> Option<u64> {
+        RobinHoodMap::remove(self, &key)
+    }
+
+    fn len(&self) -> usize {
+        RobinHoodMap::len(self)
+    }
+}
+
+impl<S: BenchHasher> BenchMap for HashMap<u64, u64, S> {
+    const NAME: &'static str = "std HashMap";
+
+    const HASHER: &'static str = S::NAME;
+
+    fn new() -> Self {
+        HashMap::default()
+    }
+
+    fn insert(&mut self, key: u64, value: u64) -> Option<u64> {
+        HashMap::insert(self, key, value)
+    }
+
+    fn get(&self, key: u64) -> Option<u64> {
+        HashMap::get(self, &key).copied()
+    }
+
+    fn remove(&mut self, key: u64) -> Option<u64> {
+        HashMap::remove(self, &key)
+    }
+
+    fn len(&self) -> usize {
+        HashMap::len(self)
+    }
+}
+
+// Small xorshift generator for the workloads
+struct XorShift(u64);
+
+impl XorShift {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, n: u64) -> u64 {
+        self.next() % n
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Op {
+    Insert(u64),
+    Get(u64),
+    Remove(u64),
+}
+
+struct Workload {
+    name: &'static str,
+    prefill: Vec<u64>,
+    ops: Vec<Op>,
+}
+
+// Random odd keys, so the even keys of `lookup_miss` are never present
+fn random_keys(n: usize, rng: &mut XorShift) -> Vec<u64> {
+    (0..n).map(|_| rng.next() | 1).collect()
+}
+
+// Inserts of fresh keys into an empty map, growing it all the way
+fn insert_fresh(n: usize, rng: &mut XorShift) -> Workload {
+    Workload { name: "insert", prefill: Vec::new(), ops: random_keys(n, rng).into_iter().map(Op::Insert).collect() }
+}
+
+// Lookups of keys that are present
+fn lookup_hit(n: usize, rng: &mut XorShift) -> Workload {
+    let prefill = random_keys(n, rng);
+    let ops = (0..n).map(|_| Op::Get(prefill[rng.below(n as u64) as usize])).collect();
+    Workload { name: "lookup-hit", prefill, ops }
+}
+
+// Lookups of keys that are absent (even keys; every prefilled key is odd)
+fn lookup_miss(n: usize, rng: &mut XorShift) -> Workload {
+    let prefill = random_keys(n, rng);
+    let ops = (0..n).map(|_| Op::Get(rng.next() & !1)).collect();
+    Workload { name: "lookup-miss", prefill, ops }
+}
+
+// Removal of every key, in a different order than inserted
+fn remove_all(n: usize, rng: &mut XorShift) -> Workload {
+    let prefill = random_keys(n, rng);
+    let mut order = prefill.clone();
+    for i in (1..order.len()).rev() {
+        order.swap(i, rng.below(i as u64 + 1) as usize);
+    }
+    Workload { name: "remove", prefill, ops: order.into_iter().map(Op::Remove).collect() }
+}
+
+// Prefilled map kept at a steady size: a third inserts of new keys, a third
+// removals of present ones and a third lookups
+fn churn(n: usize, rng: &mut XorShift) -> Workload {
+    let prefill = random_keys(n, rng);
+    let mut live = prefill.clone();
+    let mut ops = Vec::with_capacity(n);
+    while ops.len() < n {
+        let key = rng.next() | 1;
+        live.push(key);
+        ops.push(Op::Insert(key));
+        let victim = live.swap_remove(rng.below(live.len() as u64) as usize);
+        ops.push(Op::Remove(victim));
+        ops.push(Op::Get(live[rng.below(live.len() as u64) as usize]));
+    }
+    Workload { name: "churn", prefill, ops }
+}
+
+// Median nanoseconds per operation over `runs` runs; the prefill is not timed
+fn measure<M: BenchMap>(workload: &Workload, runs: usize) -> f64 {
+    let mut samples: Vec<f64> = (0..runs)
+        .map(|_| {
+            let mut map = M::new();
+            for &key in &workload.prefill {
+                map.insert(key, key);
+            }
+            let start = Instant::now();
+            for &op in &workload.ops {
+                let result = match op {
+                    Op::Insert(key) => map.insert(key, key),
+                    Op::Get(key) => map.get(key),
+                    Op::Remove(key) => map.remove(key),
+                };
+                black_box(result);
+            }
+            let elapsed = start.elapsed();
+            black_box(map.len());
+            elapsed.as_nanos() as f64 / workload.ops.len() as f64
+        })
+        .collect();
+    samples.sort_by(|a, b| a.total_cmp(b));
+    samples[runs / 2]
+}
+
+fn bench_row<M: BenchMap>(workloads: &[Workload], runs: usize) {
+    print!("{:<14}{:<10}", M::NAME, M::HASHER);
+    for workload in workloads {
+        print!("{:>14.1}", measure::<M>(workload, runs));
+    }
+    println!();
+}
+
+fn main() {
+    // Operations per workload can be given as the first argument
+    let n: usize = std::env::args().nth(1).and_then(|arg| arg.parse().ok()).unwrap_or(100_000);
+    let runs = 5;
+    let mut rng = XorShift(0xDEAD_BEEF_CAFE_F00D);
+    let workloads = [
+        insert_fresh(n, &mut rng),
+        lookup_hit(n, &mut rng),
+        lookup_miss(n, &mut rng),
+        remove_all(n, &mut rng),
+        churn(n, &mut rng),
+    ];
+
+    println!("Median ns/op over {} runs, about {} operations per workload:", runs, n);
+    print!("{:<24}", "");
+    for workload in &workloads {
+        print!("{:>14}", workload.name);
+    }
+    println!();
+    bench_row::<MyHashMap<u64, u64>>(&workloads, runs);
+    bench_row::<RobinHoodMap<u64, u64>>(&workloads, runs);
+    bench_row::<HashMap<u64, u64>>(&workloads, runs);
+    #[cfg(feature = "fast-hashers")]
+    {
+        bench_row::<MyHashMap<u64, u64, FxBuildHasher>>(&workloads, runs);
+        bench_row::<RobinHoodMap<u64, u64, FxBuildHasher>>(&workloads, runs);
+        bench_row::<HashMap<u64, u64, FxBuildHasher>>(&workloads, runs);
+        bench_row::<MyHashMap<u64, u64, FnvBuildHasher>>(&workloads, runs);
+        bench_row::<RobinHoodMap<u64, u64, FnvBuildHasher>>(&workloads, runs);
+        bench_row::<HashMap<u64, u64, FnvBuildHasher>>(&workloads, runs);
+        bench_row::<MyHashMap<u64, u64, FoldBuildHasher>>(&workloads, runs);
+        bench_row::<RobinHoodMap<u64, u64, FoldBuildHasher>>(&workloads, runs);
+        bench_row::<HashMap<u64, u64, FoldBuildHasher>>(&workloads, runs);
+    }
+}
@@ -1,6 +1,7 @@
+use std::borrow::Borrow;
 use std::collections::HashMap;
-use std::hash::{Hash, Hasher};
-use std::collections::hash_map::DefaultHasher;
+use std::collections::hash_map::{DefaultHasher, RandomState};
+use std::hash::{BuildHasher, Hash, Hasher};
 
 // 1. Using Rust's standard HashMap
 fn standard_hashmap_example() {
@@ -59,26 +60,91 @@ impl Hash for Person {
     }
 }
 
-// 3. Simple Custom HashMap Implementation
+// 3. Simple Custom HashMap Implementation (separate chaining)
+//
+// Hashing goes through a BuildHasher, std's randomly keyed SipHash by default,
+// so bucket positions differ from process to process and cannot be precomputed
+// by someone choosing keys (HashDoS). Should a chain still grow far past what
+// random hashing produces, the map assumes its seed has been worked out, draws
+// a new hasher from `S::default()` and rehashes everything. That only helps for
+// hashers whose Default picks a fresh random seed, as all of the ones here do.
 const INITIAL_BUCKETS: usize = 16;
+// Double the bucket count once there are more than 3 items per 4 buckets
+const MAX_LOAD_NUMERATOR: usize = 3;
+const MAX_LOAD_DENOMINATOR: usize = 4;
+// At 3/4 load a chain this long turns up by chance with probability around
+// 1e-16 per bucket, so it is taken as a sign of crafted keys
+const MAX_CHAIN: usize = 16;
+// If this many new seeds in a row leave a chain too long, the keys collide for
+// every seed (a weak hasher or equal hashes) and rehashing cannot help
+const MAX_RESEEDS: usize = 3;
 
-struct MyHashMap<K, V> {
+pub struct MyHashMap<K, V, S = RandomState> {
     buckets: Vec<Vec<(K, V)>>,
     items: usize,
+    hash_builder: S,
+    reseeds: usize,
+    failed_reseeds: usize,
 }
 
-impl<K, V> MyHashMap<K, V>
-where
-    K: Hash + Eq,
-{
-    fn new() -> Self {
+impl<K, V, S: Default> Default for MyHashMap<K, V, S> {
+    fn default() -> Self {
+        Self::with_hasher(S::default())
+    }
+}
+
+impl<K, V> MyHashMap<K, V, RandomState> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<K, V, S> MyHashMap<K, V, S> {
+    pub fn with_hasher(hash_builder: S) -> Self {
         MyHashMap {
-            buckets: vec![Vec::new(); INITIAL_BUCKETS],
+            buckets: (0..INITIAL_BUCKETS).map(|_| Vec::new()).collect(),
             items: 0,
+            hash_builder,
+            reseeds: 0,
+            failed_reseeds: 0,
         }
     }
 
-    fn insert(&mut self, key: K, value: V) -> Option<V> {
+    pub fn len(&self) -> usize {
+        self.items
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items == 0
+    }
+
+    // How many times a pathological chain made the map switch to a new seed
+    pub fn reseeds(&self) -> usize {
+        self.reseeds
+    }
+
+    pub fn longest_chain(&self) -> usize {
+        self.buckets.iter().map(Vec::len).max().unwrap_or(0)
+    }
+
+    // Entries in bucket order, which changes whenever the map resizes or reseeds
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
+        self.buckets.iter().flatten().map(|(key, value)| (key, value))
+    }
+
+    // Removes every entry but keeps the buckets allocated
+    pub fn clear(&mut self) {
+        self.buckets.iter_mut().for_each(Vec::clear);
+        self.items = 0;
+    }
+}
+
+impl<K, V, S> MyHashMap<K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher + Default,
+{
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
         let bucket = self.get_bucket(&key);
         for &mut (ref ekey, ref mut evalue) in &mut self.buckets[bucket] {
             if ekey == &key {
@@ -87,32 +153,947 @@ where
         }
         self.buckets[bucket].push((key, value));
         self.items += 1;
+        if self.items * MAX_LOAD_DENOMINATOR > self.buckets.len() * MAX_LOAD_NUMERATOR {
+            self.resize(self.buckets.len() * 2);
+        } else if self.buckets[bucket].len() > MAX_CHAIN && self.failed_reseeds < MAX_RESEEDS {
+            self.reseed();
+        }
         None
     }
 
-    fn get(&self, key: &K) -> Option<&V> {
+    // Switch to a freshly seeded hasher and move every entry to its new bucket
+    fn reseed(&mut self) {
+        self.hash_builder = S::default();
+        self.reseeds += 1;
+        self.resize(self.buckets.len());
+        if self.longest_chain() > MAX_CHAIN {
+            self.failed_reseeds += 1;
+        } else {
+            self.failed_reseeds = 0;
+        }
+    }
+}
+
+impl<K, V, S> MyHashMap<K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+{
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
         let bucket = self.get_bucket(key);
         self.buckets[bucket]
             .iter()
-            .find(|&&(ref ekey, _)| ekey == key)
-            .map(|&(_, ref v)| v)
+            .find(|(ekey, _)| ekey.borrow() == key)
+            .map(|(_, v)| v)
+    }
+
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let bucket = self.get_bucket(key);
+        self.buckets[bucket]
+            .iter_mut()
+            .find(|(ekey, _)| ekey.borrow() == key)
+            .map(|(_, v)| v)
     }
 
-    fn remove(&mut self, key: &K) -> Option<V> {
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
         let bucket = self.get_bucket(key);
         let bucket_vec = &mut self.buckets[bucket];
-        let index = bucket_vec.iter().position(|&(ref ekey, _)| ekey == key)?;
+        let index = bucket_vec.iter().position(|(ekey, _)| ekey.borrow() == key)?;
         self.items -= 1;
         Some(bucket_vec.swap_remove(index).1)
     }
 
-    fn get_bucket(&self, key: &K) -> usize {
+    // Move every entry into a table with `bucket_count` buckets
+    fn resize(&mut self, bucket_count: usize) {
+        let old = std::mem::replace(&mut self.buckets, (0..bucket_count).map(|_| Vec::new()).collect());
+        for (key, value) in old.into_iter().flatten() {
+            let bucket = self.get_bucket(&key);
+            self.buckets[bucket].push((key, value));
+        }
+    }
+
+    fn get_bucket<Q: Hash + ?Sized>(&self, key: &Q) -> usize {
+        (self.hash_builder.hash_one(key) % self.buckets.len() as u64) as usize
+    }
+}
+
+// 4. Robin Hood Hash Map (open addressing)
+//
+// Entries live directly in one power-of-two array of slots. A key starts probing
+// at its "home" slot (hash & mask) and walks forward; its probe distance is how
+// far it ended up from home. On insert, a key that has probed further than the
+// resident of a slot takes that slot, and the resident moves on instead ("take
+// from the rich"). This keeps probe distances short and even, and lets a lookup
+// stop as soon as it meets a resident closer to home than the key would be.
+//
+// Deletion shifts the following entries of the cluster back by one slot until
+// one is at home or the slot is empty, so there are no tombstones: lookups never
+// wade through deleted entries and the table needs no periodic cleanup.
+const MAX_LOAD_EIGHTHS: usize = 7;
+
+struct Bucket<K, V> {
+    hash: u64,
+    key: K,
+    value: V,
+}
+
+pub struct RobinHoodMap<K, V, S = RandomState> {
+    slots: Vec<Option<Bucket<K, V>>>,
+    len: usize,
+    hash_builder: S,
+}
+
+impl<K, V> RobinHoodMap<K, V, RandomState> {
+    pub fn new() -> Self {
+        Self::with_hasher(RandomState::new())
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self::with_capacity_and_hasher(capacity, RandomState::new())
+    }
+}
+
+impl<K, V, S: Default> Default for RobinHoodMap<K, V, S> {
+    fn default() -> Self {
+        Self::with_hasher(S::default())
+    }
+}
+
+impl<K, V, S> RobinHoodMap<K, V, S> {
+    // No slots are allocated until the first insert
+    pub fn with_hasher(hash_builder: S) -> Self {
+        RobinHoodMap {
+            slots: Vec::new(),
+            len: 0,
+            hash_builder,
+        }
+    }
+
+    pub fn with_capacity_and_hasher(capacity: usize, hash_builder: S) -> Self {
+        let mut map = Self::with_hasher(hash_builder);
+        if capacity > 0 {
+            map.slots = Self::empty_slots(Self::slots_for(capacity));
+        }
+        map
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    // Entries the map can hold before it has to grow
+    pub fn capacity(&self) -> usize {
+        self.slots.len() * MAX_LOAD_EIGHTHS / 8
+    }
+
+    // Smallest power-of-two slot count that holds `capacity` entries under the load limit
+    fn slots_for(capacity: usize) -> usize {
+        (capacity * 8).div_ceil(MAX_LOAD_EIGHTHS).next_power_of_two().max(8)
+    }
+
+    fn empty_slots(count: usize) -> Vec<Option<Bucket<K, V>>> {
+        (0..count).map(|_| None).collect()
+    }
+
+    fn mask(&self) -> usize {
+        self.slots.len() - 1
+    }
+
+    fn probe_distance(&self, hash: u64, index: usize) -> usize {
+        index.wrapping_sub(hash as usize) & self.mask()
+    }
+
+    pub fn clear(&mut self) {
+        self.slots.iter_mut().for_each(|slot| *slot = None);
+        self.len = 0;
+    }
+
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter { slots: self.slots.iter(), remaining: self.len }
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+        IterMut { slots: self.slots.iter_mut(), remaining: self.len }
+    }
+
+    // Removes every entry, handing them out by value; the slots stay allocated.
+    // Entries the iterator is not asked for are dropped along with it.
+    pub fn drain(&mut self) -> Drain<'_, K, V> {
+        let remaining = std::mem::replace(&mut self.len, 0);
+        Drain { slots: self.slots.iter_mut(), remaining }
+    }
+
+    // Keep only the entries for which `keep` returns true, calling it once per
+    // entry. Rejected entries are cleared in one pass, then a second pass moves
+    // every survivor back towards its home slot as far as the gaps allow, which
+    // is what one backward shift per deletion would have done.
+    pub fn retain(&mut self, mut keep: impl FnMut(&K, &mut V) -> bool) {
+        // Start at a slot that was empty before anything was removed: no
+        // cluster runs through it, so the compaction below never moves an
+        // entry past the point where it started. A slot emptied by `keep`
+        // would not do, as entries after it may have homes before it.
+        let start = match self.slots.iter().position(Option::is_none) {
+            None => return,
+            Some(start) => start,
+        };
+        for slot in self.slots.iter_mut() {
+            if let Some(bucket) = slot {
+                if !keep(&bucket.key, &mut bucket.value) {
+                    *slot = None;
+                    self.len -= 1;
+                }
+            }
+        }
+        let mask = self.mask();
+        // Slots from `free` up to the current index are empty
+        let mut free = start;
+        for offset in 1..self.slots.len() {
+            let index = (start + offset) & mask;
+            if let Some(bucket) = &self.slots[index] {
+                let gap = index.wrapping_sub(free) & mask;
+                let shift = gap.min(self.probe_distance(bucket.hash, index));
+                let target = index.wrapping_sub(shift) & mask;
+                if shift > 0 {
+                    self.slots[target] = self.slots[index].take();
+                }
+                free = (target + 1) & mask;
+            }
+        }
+    }
+
+    // Check the Robin Hood invariants: every entry sits after a slot that is
+    // occupied by an entry at most one step closer to home, unless it is at home
+    fn validate(&self) {
+        let mask = self.slots.len().wrapping_sub(1);
+        let mut count = 0;
+        for (index, slot) in self.slots.iter().enumerate() {
+            if let Some(bucket) = slot {
+                count += 1;
+                let distance = self.probe_distance(bucket.hash, index);
+                if distance > 0 {
+                    let previous = self.slots[(index + mask) & mask].as_ref().expect("gap inside a probe sequence");
+                    assert!(self.probe_distance(previous.hash, (index + mask) & mask) + 1 >= distance, "entry richer than its successor");
+                }
+            }
+        }
+        assert_eq!(count, self.len, "len out of sync");
+    }
+}
+
+impl<K, V, S> RobinHoodMap<K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+{
+    fn hash<Q: Hash + ?Sized>(&self, key: &Q) -> u64 {
+        self.hash_builder.hash_one(key)
+    }
+
+    // Slot holding `key`, stopping at an empty slot or a resident closer to home
+    fn find<Q>(&self, hash: u64, key: &Q) -> Option<usize>
+    where
+        K: Borrow<Q>,
+        Q: Eq + ?Sized,
+    {
+        if self.slots.is_empty() {
+            return None;
+        }
+        let mask = self.mask();
+        let mut index = hash as usize & mask;
+        for distance in 0.. {
+            let bucket = self.slots[index].as_ref()?;
+            if self.probe_distance(bucket.hash, index) < distance {
+                return None;
+            }
+            if bucket.hash == hash && bucket.key.borrow() == key {
+                return Some(index);
+            }
+            index = (index + 1) & mask;
+        }
+        unreachable!()
+    }
+
+    fn grow_if_full(&mut self) {
+        if (self.len + 1) * 8 > self.slots.len() * MAX_LOAD_EIGHTHS {
+            let count = (self.slots.len() * 2).max(8);
+            let old = std::mem::replace(&mut self.slots, Self::empty_slots(count));
+            for bucket in old.into_iter().flatten() {
+                self.place(bucket);
+            }
+        }
+    }
+
+    // Put a key known to be absent into the table, displacing richer residents;
+    // returns the slot where it landed
+    fn place(&mut self, bucket: Bucket<K, V>) -> usize {
+        let mask = self.mask();
+        let mut index = bucket.hash as usize & mask;
+        let mut carried = bucket;
+        let mut distance = 0;
+        let mut landed = None;
+        loop {
+            match &mut self.slots[index] {
+                None => {
+                    self.slots[index] = Some(carried);
+                    return landed.unwrap_or(index);
+                }
+                Some(resident) => {
+                    let resident_distance = index.wrapping_sub(resident.hash as usize) & mask;
+                    if resident_distance < distance {
+                        std::mem::swap(resident, &mut carried);
+                        landed.get_or_insert(index);
+                        distance = resident_distance;
+                    }
+                }
+            }
+            index = (index + 1) & mask;
+            distance += 1;
+        }
+    }
+
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        match self.entry(key) {
+            Entry::Occupied(mut entry) => Some(entry.insert(value)),
+            Entry::Vacant(entry) => {
+                entry.insert(value);
+                None
+            }
+        }
+    }
+
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let index = self.find(self.hash(key), key)?;
+        self.slots[index].as_ref().map(|bucket| &bucket.value)
+    }
+
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let index = self.find(self.hash(key), key)?;
+        self.slots[index].as_mut().map(|bucket| &mut bucket.value)
+    }
+
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.find(self.hash(key), key).is_some()
+    }
+
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let index = self.find(self.hash(key), key)?;
+        Some(self.remove_at(index).1)
+    }
+
+    // Take the entry out and shift the rest of its cluster back by one
+    fn remove_at(&mut self, index: usize) -> (K, V) {
+        let mask = self.mask();
+        let removed = self.slots[index].take().unwrap();
+        let mut hole = index;
+        loop {
+            let next = (hole + 1) & mask;
+            match &self.slots[next] {
+                Some(bucket) if self.probe_distance(bucket.hash, next) > 0 => {
+                    self.slots[hole] = self.slots[next].take();
+                    hole = next;
+                }
+                _ => break,
+            }
+        }
+        self.len -= 1;
+        (removed.key, removed.value)
+    }
+
+    pub fn entry(&mut self, key: K) -> Entry<'_, K, V, S> {
+        let hash = self.hash(&key);
+        match self.find(hash, &key) {
+            Some(index) => Entry::Occupied(OccupiedEntry { map: self, index }),
+            None => Entry::Vacant(VacantEntry { map: self, hash, key }),
+        }
+    }
+}
+
+impl<K: Hash + Eq, V, S: BuildHasher + Default> FromIterator<(K, V)> for RobinHoodMap<K, V, S> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut map = Self::default();
+        for (key, value) in iter {
+            map.insert(key, value);
+        }
+        map
+    }
+}
+
+impl<'a, K, V, S> IntoIterator for &'a RobinHoodMap<K, V, S> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+pub enum Entry<'a, K, V, S> {
+    Occupied(OccupiedEntry<'a, K, V, S>),
+    Vacant(VacantEntry<'a, K, V, S>),
+}
+
+pub struct OccupiedEntry<'a, K, V, S> {
+    map: &'a mut RobinHoodMap<K, V, S>,
+    index: usize,
+}
+
+pub struct VacantEntry<'a, K, V, S> {
+    map: &'a mut RobinHoodMap<K, V, S>,
+    hash: u64,
+    key: K,
+}
+
+impl<'a, K: Hash + Eq, V, S: BuildHasher> Entry<'a, K, V, S> {
+    pub fn key(&self) -> &K {
+        match self {
+            Entry::Occupied(entry) => entry.key(),
+            Entry::Vacant(entry) => &entry.key,
+        }
+    }
+
+    pub fn or_insert(self, default: V) -> &'a mut V {
+        self.or_insert_with(|| default)
+    }
+
+    pub fn or_insert_with(self, default: impl FnOnce() -> V) -> &'a mut V {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(default()),
+        }
+    }
+
+    pub fn or_default(self) -> &'a mut V
+    where
+        V: Default,
+    {
+        self.or_insert_with(V::default)
+    }
+
+    pub fn and_modify(mut self, f: impl FnOnce(&mut V)) -> Self {
+        if let Entry::Occupied(entry) = &mut self {
+            f(entry.get_mut());
+        }
+        self
+    }
+}
+
+impl<'a, K: Hash + Eq, V, S: BuildHasher> OccupiedEntry<'a, K, V, S> {
+    fn bucket(&self) -> &Bucket<K, V> {
+        self.map.slots[self.index].as_ref().unwrap()
+    }
+
+    pub fn key(&self) -> &K {
+        &self.bucket().key
+    }
+
+    pub fn get(&self) -> &V {
+        &self.bucket().value
+    }
+
+    pub fn get_mut(&mut self) -> &mut V {
+        &mut self.map.slots[self.index].as_mut().unwrap().value
+    }
+
+    pub fn into_mut(self) -> &'a mut V {
+        &mut self.map.slots[self.index].as_mut().unwrap().value
+    }
+
+    pub fn insert(&mut self, value: V) -> V {
+        std::mem::replace(self.get_mut(), value)
+    }
+
+    pub fn remove_entry(self) -> (K, V) {
+        self.map.remove_at(self.index)
+    }
+
+    pub fn remove(self) -> V {
+        self.remove_entry().1
+    }
+}
+
+impl<'a, K: Hash + Eq, V, S: BuildHasher> VacantEntry<'a, K, V, S> {
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+
+    pub fn insert(self, value: V) -> &'a mut V {
+        let map = self.map;
+        map.grow_if_full();
+        let index = map.place(Bucket { hash: self.hash, key: self.key, value });
+        map.len += 1;
+        &mut map.slots[index].as_mut().unwrap().value
+    }
+}
+
+pub struct Iter<'a, K, V> {
+    slots: std::slice::Iter<'a, Option<Bucket<K, V>>>,
+    remaining: usize,
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let bucket = self.slots.by_ref().flatten().next()?;
+        self.remaining -= 1;
+        Some((&bucket.key, &bucket.value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<K, V> ExactSizeIterator for Iter<'_, K, V> {}
+
+pub struct IterMut<'a, K, V> {
+    slots: std::slice::IterMut<'a, Option<Bucket<K, V>>>,
+    remaining: usize,
+}
+
+impl<'a, K, V> Iterator for IterMut<'a, K, V> {
+    type Item = (&'a K, &'a mut V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let bucket = self.slots.by_ref().flatten().next()?;
+        self.remaining -= 1;
+        Some((&bucket.key, &mut bucket.value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<K, V> ExactSizeIterator for IterMut<'_, K, V> {}
+
+pub struct Drain<'a, K, V> {
+    slots: std::slice::IterMut<'a, Option<Bucket<K, V>>>,
+    remaining: usize,
+}
+
+impl<K, V> Iterator for Drain<'_, K, V> {
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let bucket = self.slots.by_ref().find_map(Option::take)?;
+        self.remaining -= 1;
+        Some((bucket.key, bucket.value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<K, V> ExactSizeIterator for Drain<'_, K, V> {}
+
+impl<K, V> Drop for Drain<'_, K, V> {
+    fn drop(&mut self) {
+        self.slots.by_ref().for_each(|slot| *slot = None);
+    }
+}
+
+// 5. Fast Hashers
+//
+// SipHash is built to resist chosen keys, which costs time on short keys. These
+// hashers are much faster but make no such promise: Fx and FNV fold a key in
+// with a couple of arithmetic steps, and the folded-multiply hasher (after
+// aHash's fallback path) is stronger but not cryptographic. Each builder takes
+// a random seed from Default, so MyHashMap can still reseed them; use them for
+// keys that do not come from untrusted input. They are opt-in:
+//
+//     rustc -O --edition 2021 --cfg 'feature="fast-hashers"' rust-hash-structures.rs
+#[cfg(feature = "fast-hashers")]
+pub mod fast_hashers {
+    use std::collections::hash_map::RandomState;
+    use std::hash::{BuildHasher, Hasher};
+
+    // Random bits from std's per-process SipHash keys
+    fn random_seed() -> u64 {
+        RandomState::new().build_hasher().finish()
+    }
+
+    // FxHash, the hasher of rustc: rotate, xor in a word, multiply
+    const FX_SEED: u64 = 0x51_7c_c1_b7_27_22_0a_95;
+
+    pub struct FxHasher {
+        hash: u64,
+    }
+
+    impl FxHasher {
+        fn add(&mut self, word: u64) {
+            self.hash = (self.hash.rotate_left(5) ^ word).wrapping_mul(FX_SEED);
+        }
+    }
+
+    impl Hasher for FxHasher {
+        fn write(&mut self, bytes: &[u8]) {
+            let mut chunks = bytes.chunks_exact(8);
+            for chunk in &mut chunks {
+                self.add(u64::from_le_bytes(chunk.try_into().unwrap()));
+            }
+            let mut tail = [0u8; 8];
+            let rest = chunks.remainder();
+            tail[..rest.len()].copy_from_slice(rest);
+            self.add(u64::from_le_bytes(tail) ^ ((rest.len() as u64) << 59));
+        }
+
+        fn write_u64(&mut self, n: u64) {
+            self.add(n);
+        }
+
+        fn write_usize(&mut self, n: usize) {
+            self.add(n as u64);
+        }
+
+        // The multiply leaves the low bits the weakest, and MyHashMap picks a
+        // bucket from those, so rotate the well-mixed high bits down first
+        fn finish(&self) -> u64 {
+            self.hash.rotate_left(26)
+        }
+    }
+
+    #[derive(Clone)]
+    pub struct FxBuildHasher {
+        seed: u64,
+    }
+
+    impl Default for FxBuildHasher {
+        fn default() -> Self {
+            FxBuildHasher { seed: random_seed() }
+        }
+    }
+
+    impl BuildHasher for FxBuildHasher {
+        type Hasher = FxHasher;
+
+        fn build_hasher(&self) -> FxHasher {
+            FxHasher { hash: self.seed }
+        }
+    }
+
+    // FNV-1a (64-bit): xor in each byte, multiply by the FNV prime
+    const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    pub struct FnvHasher {
+        hash: u64,
+    }
+
+    impl Hasher for FnvHasher {
+        fn write(&mut self, bytes: &[u8]) {
+            for &byte in bytes {
+                self.hash = (self.hash ^ byte as u64).wrapping_mul(FNV_PRIME);
+            }
+        }
+
+        fn finish(&self) -> u64 {
+            self.hash
+        }
+    }
+
+    #[derive(Clone)]
+    pub struct FnvBuildHasher {
+        seed: u64,
+    }
+
+    impl Default for FnvBuildHasher {
+        fn default() -> Self {
+            FnvBuildHasher { seed: random_seed() }
+        }
+    }
+
+    impl BuildHasher for FnvBuildHasher {
+        type Hasher = FnvHasher;
+
+        fn build_hasher(&self) -> FnvHasher {
+            FnvHasher { hash: FNV_OFFSET_BASIS ^ self.seed }
+        }
+    }
+
+    // Folded multiply: the full 128-bit product of two words with its halves
+    // xored together, so every input bit reaches every output bit
+    const MULTIPLE: u64 = 6_364_136_223_846_793_005;
+
+    fn folded_multiply(a: u64, b: u64) -> u64 {
+        let product = a as u128 * b as u128;
+        product as u64 ^ (product >> 64) as u64
+    }
+
+    pub struct FoldHasher {
+        buffer: u64,
+        pad: u64,
+    }
+
+    impl FoldHasher {
+        fn add(&mut self, word: u64) {
+            self.buffer = folded_multiply(word ^ self.buffer, MULTIPLE);
+        }
+    }
+
+    impl Hasher for FoldHasher {
+        fn write(&mut self, bytes: &[u8]) {
+            self.add(bytes.len() as u64);
+            let mut chunks = bytes.chunks_exact(8);
+            for chunk in &mut chunks {
+                self.add(u64::from_le_bytes(chunk.try_into().unwrap()));
+            }
+            let mut tail = [0u8; 8];
+            let rest = chunks.remainder();
+            tail[..rest.len()].copy_from_slice(rest);
+            self.add(u64::from_le_bytes(tail));
+        }
+
+        fn write_u64(&mut self, n: u64) {
+            self.add(n);
+        }
+
+        fn write_usize(&mut self, n: usize) {
+            self.add(n as u64);
+        }
+
+        fn finish(&self) -> u64 {
+            let rotation = (self.buffer & 63) as u32;
+            folded_multiply(self.buffer, self.pad).rotate_left(rotation)
+        }
+    }
+
+    #[derive(Clone)]
+    pub struct FoldBuildHasher {
+        buffer: u64,
+        pad: u64,
+    }
+
+    impl Default for FoldBuildHasher {
+        fn default() -> Self {
+            FoldBuildHasher { buffer: random_seed(), pad: random_seed() }
+        }
+    }
+
+    impl BuildHasher for FoldBuildHasher {
+        type Hasher = FoldHasher;
+
+        fn build_hasher(&self) -> FoldHasher {
+            FoldHasher { buffer: self.buffer, pad: self.pad }
+        }
+    }
+}
+
+// Small xorshift generator for the randomized checks below
+struct XorShift(u64);
+
+impl XorShift {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+}
+
+// Random operations on both custom maps against std's HashMap. Keys come from a
+// small range so inserts, updates and removals of present keys all happen often.
+fn randomized_check(operations: usize, seed: u64) {
+    let mut rng = XorShift(seed);
+    let mut chained: MyHashMap<u64, u64> = MyHashMap::new();
+    let mut robin: RobinHoodMap<u64, u64> = RobinHoodMap::new();
+    let mut model: HashMap<u64, u64> = HashMap::new();
+    for step in 0..operations {
+        let key = rng.next() % 512;
+        let value = rng.next() % 1_000;
+        match rng.next() % 8 {
+            0..=2 => {
+                let expected = model.insert(key, value);
+                assert_eq!(chained.insert(key, value), expected);
+                assert_eq!(robin.insert(key, value), expected);
+            }
+            3 | 4 => {
+                let expected = model.remove(&key);
+                assert_eq!(chained.remove(&key), expected);
+                assert_eq!(robin.remove(&key), expected);
+            }
+            5 => {
+                let expected = *model.entry(key).and_modify(|v| *v += 1).or_insert(value);
+                assert_eq!(*robin.entry(key).and_modify(|v| *v += 1).or_insert(value), expected);
+                chained.insert(key, expected);
+            }
+            6 => {
+                if let Entry::Occupied(entry) = robin.entry(key) {
+                    assert_eq!(entry.remove_entry(), (key, model.remove(&key).unwrap()));
+                    chained.remove(&key);
+                }
+            }
+            _ => {
+                if let Some(v) = robin.get_mut(&key) {
+                    *v *= 2;
+                    *model.get_mut(&key).unwrap() *= 2;
+                    chained.insert(key, *v);
+                }
+            }
+        }
+        assert_eq!(chained.get(&key), model.get(&key));
+        assert_eq!(robin.get(&key), model.get(&key));
+        assert_eq!((chained.len(), robin.len()), (model.len(), model.len()));
+
+        if step % 1_000 == 999 {
+            robin.validate();
+            let threshold = rng.next() % 1_000;
+            robin.retain(|_, v| *v < threshold);
+            model.retain(|_, v| *v < threshold);
+            robin.validate();
+            for (key, value) in robin.iter_mut() {
+                *value += key % 3;
+            }
+            for (key, value) in model.iter_mut() {
+                *value += key % 3;
+            }
+            let mut contents: Vec<(u64, u64)> = robin.iter().map(|(k, v)| (*k, *v)).collect();
+            let mut expected: Vec<(u64, u64)> = model.iter().map(|(k, v)| (*k, *v)).collect();
+            contents.sort();
+            expected.sort();
+            assert_eq!(contents, expected);
+            chained = MyHashMap::new();
+            for (&key, &value) in &model {
+                chained.insert(key, value);
+            }
+        }
+    }
+
+    let mut drained: Vec<(u64, u64)> = robin.drain().collect();
+    let mut expected: Vec<(u64, u64)> = model.drain().collect();
+    drained.sort();
+    expected.sort();
+    assert_eq!(drained, expected);
+    assert!(robin.is_empty() && robin.iter().next().is_none());
+
+    // A partly consumed drain still empties the map
+    for key in 0..100 {
+        robin.insert(key, key);
+    }
+    assert_eq!(robin.drain().take(10).count(), 10);
+    assert!(robin.is_empty() && robin.iter().next().is_none());
+    robin.validate();
+}
+
+// A seeded hasher whose seed has leaked, so keys can be chosen to collide. Its
+// Default draws a new seed the attacker does not know.
+#[derive(Clone)]
+struct LeakedSeed(u64);
+
+impl Default for LeakedSeed {
+    fn default() -> Self {
+        LeakedSeed(RandomState::new().build_hasher().finish())
+    }
+}
+
+impl BuildHasher for LeakedSeed {
+    type Hasher = DefaultHasher;
+
+    fn build_hasher(&self) -> DefaultHasher {
         let mut hasher = DefaultHasher::new();
-        key.hash(&mut hasher);
-        (hasher.finish() % self.buckets.len() as u64) as usize
+        self.0.hash(&mut hasher);
+        hasher
     }
 }
 
+// Flood one bucket with keys that collide under the leaked seed and check that
+// the map notices, reseeds and spreads them out again
+fn hash_flooding_check() {
+    let leaked = LeakedSeed(42);
+    // Colliding modulo 256 means colliding modulo every smaller bucket count
+    let crafted: Vec<u64> = (0u64..).filter(|key| leaked.hash_one(key).is_multiple_of(256)).take(40).collect();
+
+    let mut map: MyHashMap<u64, u64, LeakedSeed> = MyHashMap::with_hasher(leaked);
+    for &key in &crafted {
+        map.insert(key, key * 2);
+    }
+    assert!(map.reseeds() >= 1, "the flood went unnoticed");
+    assert!(map.longest_chain() <= MAX_CHAIN, "reseeding left a chain of {}", map.longest_chain());
+    for &key in &crafted {
+        assert_eq!(map.get(&key), Some(&(key * 2)));
+    }
+
+    // Keys whose hashes are equal under every seed: the map gives up after a few tries
+    #[derive(PartialEq, Eq)]
+    struct SameHash(u32);
+    impl Hash for SameHash {
+        fn hash<H: Hasher>(&self, state: &mut H) {
+            0u8.hash(state);
+        }
+    }
+    let mut hopeless: MyHashMap<SameHash, u32> = MyHashMap::new();
+    for i in 0..200 {
+        hopeless.insert(SameHash(i), i);
+    }
+    assert_eq!(hopeless.reseeds(), MAX_RESEEDS);
+    assert_eq!(hopeless.get(&SameHash(150)), Some(&150));
+}
+
+// Each fast hasher behind MyHashMap and RobinHoodMap against std's HashMap
+#[cfg(feature = "fast-hashers")]
+fn fast_hasher_check() {
+    use fast_hashers::{FnvBuildHasher, FoldBuildHasher, FxBuildHasher};
+
+    fn check<S: BuildHasher + Default>(name: &str) {
+        let mut rng = XorShift(0x9E3779B97F4A7C15);
+        let mut chained: MyHashMap<String, u64, S> = MyHashMap::default();
+        let mut robin: RobinHoodMap<String, u64, S> = RobinHoodMap::default();
+        let mut model: HashMap<String, u64> = HashMap::new();
+        for _ in 0..20_000 {
+            let key = format!("key-{}", rng.next() % 2_000);
+            let value = rng.next();
+            if rng.next().is_multiple_of(3) {
+                let expected = model.remove(&key);
+                assert_eq!(chained.remove(key.as_str()), expected, "{}", name);
+                assert_eq!(robin.remove(key.as_str()), expected, "{}", name);
+            } else {
+                let expected = model.insert(key.clone(), value);
+                assert_eq!(chained.insert(key.clone(), value), expected, "{}", name);
+                assert_eq!(robin.insert(key, value), expected, "{}", name);
+            }
+        }
+        assert_eq!((chained.len(), robin.len()), (model.len(), model.len()));
+        assert!(chained.longest_chain() <= MAX_CHAIN, "{} made a chain of {}", name, chained.longest_chain());
+    }
+
+    check::<FxBuildHasher>("FxHash");
+    check::<FnvBuildHasher>("FNV-1a");
+    check::<FoldBuildHasher>("folded multiply");
+}
+
 fn main() {
     println!("1. Standard HashMap Example:");
     standard_hashmap_example();
@@ -124,6 +1105,7 @@ fn main() {
     person_map.insert(&person1, "Employee");
     person_map.insert(&person2, "Manager");
     println!("{:?}", person_map.get(&Person { id: 1, name: "Alice".to_string(), age: 30 }));
+    println!("{} ({}) and {} ({}) are keyed by id only", person1.name, person1.age, person2.name, person2.age);
 
     println!("\n3. Custom HashMap Implementation:");
     let mut custom_map = MyHashMap::new();
@@ -132,4 +1114,30 @@ fn main() {
     println!("Get key1: {:?}", custom_map.get(&"key1"));
     println!("Remove key2: {:?}", custom_map.remove(&"key2"));
     println!("Get key2 after removal: {:?}", custom_map.get(&"key2"));
+    let mut numbers = MyHashMap::new();
+    for i in 0..1_000 {
+        numbers.insert(i, i * i);
+    }
+    println!("1000 numbers in {} buckets, get(999): {:?}", numbers.buckets.len(), numbers.get(&999));
+
+    println!("\n4. Robin Hood HashMap:");
+    let mut word_counts: RobinHoodMap<&str, usize> = RobinHoodMap::new();
+    for word in "the quick brown fox jumps over the lazy dog the end".split_whitespace() {
+        *word_counts.entry(word).or_default() += 1;
+    }
+    println!("Count of 'the': {:?}, len: {}, capacity: {}", word_counts.get("the"), word_counts.len(), word_counts.capacity());
+    word_counts.retain(|word, _| word.len() > 3);
+    let mut remaining: Vec<_> = word_counts.iter().collect();
+    remaining.sort();
+    println!("Words longer than 3 letters: {:?}", remaining);
+
+    randomized_check(50_000, 0x2545F4914F6CDD1D);
+    println!("Randomized check passed: MyHashMap and RobinHoodMap matched std's HashMap");
+    hash_flooding_check();
+    println!("Flooding check passed: colliding keys triggered a reseed");
+    #[cfg(feature = "fast-hashers")]
+    {
+        fast_hasher_check();
+        println!("Fast hasher check passed: FxHash, FNV-1a and folded multiply");
+    }
 }
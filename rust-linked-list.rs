@@ -1,3 +1,4 @@
+use std::collections::VecDeque;
 use std::fmt::Display;
 
 // Define the Node structure
@@ -11,6 +12,12 @@ pub struct LinkedList<T> {
     head: Option<Box<Node<T>>>,
 }
 
+impl<T> Default for LinkedList<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl<T> LinkedList<T> {
     // Create a new empty linked list
     pub fn new() -> Self {
@@ -79,7 +86,7 @@ impl<T> LinkedList<T> {
     }
 
     // Create an iterator over the list
-    pub fn iter(&self) -> Iter<T> {
+    pub fn iter(&self) -> Iter<'_, T> {
         Iter { next: self.head.as_deref() }
     }
 
@@ -128,6 +135,284 @@ impl<T: Display> Display for LinkedList<T> {
     }
 }
 
+// Doubly linked list stored in a Vec and linked by indices instead of pointers.
+// Pushing an element returns a handle (its index) that stays valid until the
+// element is removed, so any element can be unlinked or moved in O(1) without
+// walking the list. Freed slots are reused, so a handle must not be used after
+// its element is removed.
+struct IndexNode<T> {
+    data: T,
+    prev: Option<usize>,
+    next: Option<usize>,
+}
+
+pub struct IndexList<T> {
+    nodes: Vec<Option<IndexNode<T>>>,
+    free: Vec<usize>,
+    head: Option<usize>,
+    tail: Option<usize>,
+    len: usize,
+}
+
+impl<T> Default for IndexList<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> IndexList<T> {
+    pub fn new() -> Self {
+        IndexList { nodes: Vec::new(), free: Vec::new(), head: None, tail: None, len: 0 }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    // Handles of the first and last elements
+    pub fn front(&self) -> Option<usize> {
+        self.head
+    }
+
+    pub fn back(&self) -> Option<usize> {
+        self.tail
+    }
+
+    fn node(&self, handle: usize) -> &IndexNode<T> {
+        self.nodes[handle].as_ref().expect("stale list handle")
+    }
+
+    fn node_mut(&mut self, handle: usize) -> &mut IndexNode<T> {
+        self.nodes[handle].as_mut().expect("stale list handle")
+    }
+
+    // Handles of the neighbours of an element
+    pub fn next(&self, handle: usize) -> Option<usize> {
+        self.node(handle).next
+    }
+
+    pub fn prev(&self, handle: usize) -> Option<usize> {
+        self.node(handle).prev
+    }
+
+    pub fn get(&self, handle: usize) -> &T {
+        &self.node(handle).data
+    }
+
+    pub fn get_mut(&mut self, handle: usize) -> &mut T {
+        &mut self.node_mut(handle).data
+    }
+
+    // Store a node in a free slot (or a new one) without linking it
+    fn allocate(&mut self, data: T) -> usize {
+        let node = Some(IndexNode { data, prev: None, next: None });
+        match self.free.pop() {
+            Some(handle) => {
+                self.nodes[handle] = node;
+                handle
+            }
+            None => {
+                self.nodes.push(node);
+                self.nodes.len() - 1
+            }
+        }
+    }
+
+    // Link an allocated node in front of `before`, or at the back for None
+    fn link(&mut self, handle: usize, before: Option<usize>) {
+        let prev = match before {
+            Some(next) => self.node(next).prev,
+            None => self.tail,
+        };
+        let node = self.node_mut(handle);
+        node.prev = prev;
+        node.next = before;
+        match prev {
+            Some(prev) => self.node_mut(prev).next = Some(handle),
+            None => self.head = Some(handle),
+        }
+        match before {
+            Some(next) => self.node_mut(next).prev = Some(handle),
+            None => self.tail = Some(handle),
+        }
+        self.len += 1;
+    }
+
+    fn unlink(&mut self, handle: usize) {
+        let (prev, next) = {
+            let node = self.node(handle);
+            (node.prev, node.next)
+        };
+        match prev {
+            Some(prev) => self.node_mut(prev).next = next,
+            None => self.head = next,
+        }
+        match next {
+            Some(next) => self.node_mut(next).prev = prev,
+            None => self.tail = prev,
+        }
+        self.len -= 1;
+    }
+
+    pub fn push_front(&mut self, data: T) -> usize {
+        let handle = self.allocate(data);
+        self.link(handle, self.head);
+        handle
+    }
+
+    pub fn push_back(&mut self, data: T) -> usize {
+        let handle = self.allocate(data);
+        self.link(handle, None);
+        handle
+    }
+
+    // Insert in front of the element `before`
+    pub fn insert_before(&mut self, before: usize, data: T) -> usize {
+        let handle = self.allocate(data);
+        self.link(handle, Some(before));
+        handle
+    }
+
+    pub fn remove(&mut self, handle: usize) -> T {
+        self.unlink(handle);
+        self.free.push(handle);
+        self.nodes[handle].take().unwrap().data
+    }
+
+    pub fn pop_back(&mut self) -> Option<T> {
+        self.tail.map(|handle| self.remove(handle))
+    }
+
+    // Move an element in front of `before` (or to the back for None); its handle stays the same
+    pub fn move_before(&mut self, handle: usize, before: Option<usize>) {
+        if before == Some(handle) {
+            return;
+        }
+        self.unlink(handle);
+        self.link(handle, before);
+    }
+
+    pub fn move_to_front(&mut self, handle: usize) {
+        if self.head != Some(handle) {
+            self.move_before(handle, self.head);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::new();
+    }
+
+    // Elements from front to back
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        std::iter::successors(self.head, move |&handle| self.node(handle).next).map(move |handle| self.get(handle))
+    }
+}
+
+// Small xorshift generator for the randomized check below
+struct XorShift(u64);
+
+impl XorShift {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+}
+
+// Random edits against a VecDeque of (handle, value) pairs. The model also
+// predicts the slot every new element lands in: the most recently freed one,
+// or a fresh slot at the end when none is free.
+fn randomized_check() {
+    let mut rng = XorShift(0x2545_f491_4f6c_dd1d);
+    let mut list: IndexList<u64> = IndexList::new();
+    let mut model: VecDeque<(usize, u64)> = VecDeque::new();
+    let mut freed: Vec<usize> = Vec::new();
+    let mut slots = 0;
+
+    for step in 0..20_000 {
+        let value = rng.next();
+        // The slot the next new element must land in
+        let mut expected_slot = || {
+            freed.pop().unwrap_or_else(|| {
+                slots += 1;
+                slots - 1
+            })
+        };
+        match rng.below(8) {
+            0 => {
+                let handle = list.push_front(value);
+                assert_eq!(handle, expected_slot(), "push_front reused the wrong slot at step {}", step);
+                model.push_front((handle, value));
+            }
+            1 => {
+                let handle = list.push_back(value);
+                assert_eq!(handle, expected_slot(), "push_back reused the wrong slot at step {}", step);
+                model.push_back((handle, value));
+            }
+            2 if !model.is_empty() => {
+                let i = rng.below(model.len());
+                let handle = list.insert_before(model[i].0, value);
+                assert_eq!(handle, expected_slot(), "insert_before reused the wrong slot at step {}", step);
+                model.insert(i, (handle, value));
+            }
+            3 | 4 if !model.is_empty() => {
+                let (handle, data) = model.remove(rng.below(model.len())).unwrap();
+                assert_eq!(list.remove(handle), data);
+                freed.push(handle);
+            }
+            5 if !model.is_empty() => {
+                let entry = model.remove(rng.below(model.len())).unwrap();
+                // Any position in the remaining list, or the back
+                let before = rng.below(model.len() + 1);
+                list.move_before(entry.0, model.get(before).map(|&(handle, _)| handle));
+                model.insert(before, entry);
+            }
+            6 if !model.is_empty() => {
+                let i = rng.below(model.len());
+                list.move_to_front(model[i].0);
+                let entry = model.remove(i).unwrap();
+                model.push_front(entry);
+            }
+            7 => {
+                let popped = model.pop_back();
+                assert_eq!(list.pop_back(), popped.map(|(_, data)| data));
+                freed.extend(popped.map(|(handle, _)| handle));
+            }
+            _ if !model.is_empty() => {
+                let i = rng.below(model.len());
+                *list.get_mut(model[i].0) = value;
+                model[i].1 = value;
+            }
+            _ => {}
+        }
+        assert_eq!(list.len(), model.len());
+        assert_eq!(list.is_empty(), model.is_empty());
+        assert!(list.iter().eq(model.iter().map(|(_, data)| data)), "contents diverged at step {}", step);
+        // Walk the handles both ways
+        let forward: Vec<usize> = std::iter::successors(list.front(), |&h| list.next(h)).collect();
+        let mut backward: Vec<usize> = std::iter::successors(list.back(), |&h| list.prev(h)).collect();
+        backward.reverse();
+        assert!(forward.iter().eq(model.iter().map(|(handle, _)| handle)), "links diverged at step {}", step);
+        assert_eq!(forward, backward);
+        if step % 1000 == 999 {
+            list.clear();
+            model.clear();
+            freed.clear();
+            slots = 0;
+        }
+    }
+    println!("Randomized check passed: IndexList matched VecDeque over 20000 operations");
+}
+
 fn main() {
     // Create a new linked list
     let mut list: LinkedList<i32> = LinkedList::new();
@@ -165,4 +450,18 @@ fn main() {
     list.clear();
     println!("List after clear: {}", list);
     println!("Is list empty? {}", list.is_empty());
+
+    // Index-linked list: handles allow O(1) removal and reordering
+    let mut recent: IndexList<&str> = IndexList::new();
+    let a = recent.push_back("a");
+    let b = recent.push_back("b");
+    let c = recent.push_back("c");
+    recent.move_to_front(c);
+    println!("\nIndex list after moving c to the front: {:?}", recent.iter().collect::<Vec<_>>());
+    recent.remove(a);
+    recent.insert_before(b, "d");
+    println!("After removing a and inserting d before b: {:?}", recent.iter().collect::<Vec<_>>());
+    println!("Popped from the back: {:?}, length now {}", recent.pop_back(), recent.len());
+
+    randomized_check();
 }
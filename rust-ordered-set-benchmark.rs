@@ -0,0 +1,533 @@
+// Each tree file brings its own copy of rust-tree-printer.rs as a private module
+#![allow(clippy::duplicate_mod)]
+
+use std::collections::BTreeSet;
+use std::hint::black_box;
+use std::time::Instant;
+
+// Ordered Set comparison: one `OrderedSet` trait over the sorted containers in
+// this repository, a conformance suite every implementation must pass, and a
+// benchmark harness that compares them on the same operation streams.
+//
+// The implementations are pulled in from their own files, so this program
+// builds standalone like every other example:
+//
+//     rustc -O --edition 2021 rust-ordered-set-benchmark.rs
+//
+// There is no Cargo manifest here to hang criterion off, so the harness times
+// with std::time::Instant instead: every workload is generated up front, run
+// several times per structure, and the median nanoseconds per operation is
+// reported. std::collections::BTreeSet is included as the baseline.
+
+#[path = "rust-avl-tree.rs"]
+#[allow(dead_code)]
+mod avl_tree;
+#[path = "rust-b-tree.rs"]
+#[allow(dead_code)]
+mod b_tree;
+#[path = "rust-red-black-tree.rs"]
+#[allow(dead_code)]
+mod red_black_tree;
+#[path = "rust-skip-list.rs"]
+#[allow(dead_code)]
+mod skip_list;
+#[path = "rust-splay-tree.rs"]
+#[allow(dead_code)]
+mod splay_tree;
+#[path = "rust-treap.rs"]
+#[allow(dead_code)]
+mod treap;
+
+use avl_tree::AVLTree;
+use b_tree::BTree;
+use red_black_tree::RedBlackTree;
+use skip_list::SkipList;
+use splay_tree::SplayTree;
+use treap::Treap;
+
+pub trait OrderedSet<T: Ord> {
+    const NAME: &'static str;
+
+    fn new() -> Self;
+
+    // Returns false if the value was already present
+    fn insert(&mut self, value: T) -> bool;
+
+    // Returns whether the value was present
+    fn remove(&mut self, value: &T) -> bool;
+
+    // Takes `&mut self` because a splay tree restructures itself on every lookup
+    fn contains(&mut self, value: &T) -> bool;
+
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    // All values in ascending order
+    fn sorted(&self) -> Vec<&T>;
+}
+
+// AVLTree and BTree return () from insert/delete, so the outcome comes from the length
+impl<T: Ord> OrderedSet<T> for AVLTree<T> {
+    const NAME: &'static str = "AVL";
+
+    fn new() -> Self {
+        AVLTree::new()
+    }
+
+    fn insert(&mut self, value: T) -> bool {
+        let before = self.len();
+        AVLTree::insert(self, value);
+        self.len() > before
+    }
+
+    fn remove(&mut self, value: &T) -> bool {
+        let before = self.len();
+        self.delete(value);
+        self.len() < before
+    }
+
+    fn contains(&mut self, value: &T) -> bool {
+        AVLTree::contains(self, value)
+    }
+
+    fn len(&self) -> usize {
+        AVLTree::len(self)
+    }
+
+    fn sorted(&self) -> Vec<&T> {
+        self.inorder()
+    }
+}
+
+impl<T: Ord> OrderedSet<T> for RedBlackTree<T> {
+    const NAME: &'static str = "Red-Black";
+
+    fn new() -> Self {
+        RedBlackTree::new()
+    }
+
+    fn insert(&mut self, value: T) -> bool {
+        let before = self.len();
+        RedBlackTree::insert(self, value);
+        self.len() > before
+    }
+
+    fn remove(&mut self, value: &T) -> bool {
+        self.delete(value)
+    }
+
+    fn contains(&mut self, value: &T) -> bool {
+        RedBlackTree::contains(self, value)
+    }
+
+    fn len(&self) -> usize {
+        RedBlackTree::len(self)
+    }
+
+    fn sorted(&self) -> Vec<&T> {
+        self.inorder_traversal()
+    }
+}
+
+impl<T: Ord> OrderedSet<T> for BTree<T> {
+    const NAME: &'static str = "B-Tree";
+
+    fn new() -> Self {
+        BTree::new()
+    }
+
+    fn insert(&mut self, value: T) -> bool {
+        let before = self.len();
+        BTree::insert(self, value);
+        self.len() > before
+    }
+
+    fn remove(&mut self, value: &T) -> bool {
+        self.delete(value)
+    }
+
+    fn contains(&mut self, value: &T) -> bool {
+        self.search(value)
+    }
+
+    fn len(&self) -> usize {
+        BTree::len(self)
+    }
+
+    fn sorted(&self) -> Vec<&T> {
+        self.traverse()
+    }
+}
+
+impl<T: Ord> OrderedSet<T> for Treap<T> {
+    const NAME: &'static str = "Treap";
+
+    fn new() -> Self {
+        Treap::new()
+    }
+
+    fn insert(&mut self, value: T) -> bool {
+        Treap::insert(self, value)
+    }
+
+    fn remove(&mut self, value: &T) -> bool {
+        self.delete(value)
+    }
+
+    fn contains(&mut self, value: &T) -> bool {
+        self.search(value)
+    }
+
+    fn len(&self) -> usize {
+        Treap::len(self)
+    }
+
+    fn sorted(&self) -> Vec<&T> {
+        self.inorder()
+    }
+}
+
+impl<T: Ord> OrderedSet<T> for SplayTree<T> {
+    const NAME: &'static str = "Splay";
+
+    fn new() -> Self {
+        SplayTree::new()
+    }
+
+    fn insert(&mut self, value: T) -> bool {
+        SplayTree::insert(self, value)
+    }
+
+    fn remove(&mut self, value: &T) -> bool {
+        self.delete(value)
+    }
+
+    fn contains(&mut self, value: &T) -> bool {
+        SplayTree::contains(self, value)
+    }
+
+    fn len(&self) -> usize {
+        SplayTree::len(self)
+    }
+
+    fn sorted(&self) -> Vec<&T> {
+        self.inorder()
+    }
+}
+
+impl<T: Ord> OrderedSet<T> for SkipList<T> {
+    const NAME: &'static str = "Skip List";
+
+    fn new() -> Self {
+        SkipList::new()
+    }
+
+    fn insert(&mut self, value: T) -> bool {
+        SkipList::insert(self, value)
+    }
+
+    fn remove(&mut self, value: &T) -> bool {
+        self.delete(value)
+    }
+
+    fn contains(&mut self, value: &T) -> bool {
+        self.search(value)
+    }
+
+    fn len(&self) -> usize {
+        SkipList::len(self)
+    }
+
+    fn sorted(&self) -> Vec<&T> {
+        self.iter().collect()
+    }
+}
+
+impl<T: Ord> OrderedSet<T> for BTreeSet<T> {
+    const NAME: &'static str = "std BTreeSet";
+
+    fn new() -> Self {
+        BTreeSet::new()
+    }
+
+    fn insert(&mut self, value: T) -> bool {
+        BTreeSet::insert(self, value)
+    }
+
+    fn remove(&mut self, value: &T) -> bool {
+        BTreeSet::remove(self, value)
+    }
+
+    fn contains(&mut self, value: &T) -> bool {
+        BTreeSet::contains(self, value)
+    }
+
+    fn len(&self) -> usize {
+        BTreeSet::len(self)
+    }
+
+    fn sorted(&self) -> Vec<&T> {
+        self.iter().collect()
+    }
+}
+
+// Small xorshift generator for the workloads and the conformance suite
+struct XorShift(u64);
+
+impl XorShift {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, n: u64) -> u64 {
+        self.next() % n
+    }
+
+    // Uniform in [0, 1)
+    fn unit(&mut self) -> f64 {
+        (self.next() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+fn check_sorted<S: OrderedSet<u32>>(set: &S, model: &BTreeSet<u32>, context: &str) {
+    let actual: Vec<u32> = set.sorted().into_iter().copied().collect();
+    let expected: Vec<u32> = model.iter().copied().collect();
+    assert_eq!(actual, expected, "{}: contents diverged ({})", S::NAME, context);
+    assert_eq!(set.len(), model.len(), "{}: len diverged ({})", S::NAME, context);
+    assert_eq!(set.is_empty(), model.is_empty(), "{}: is_empty diverged ({})", S::NAME, context);
+}
+
+// Conformance suite: fixed edge cases, sorted and reverse-sorted bulk runs,
+// then a random operation stream over a small key space (so duplicates and
+// misses are common) checked against BTreeSet after every step.
+fn conformance<S: OrderedSet<u32>>(seed: u64) {
+    // 1. Empty set
+    let mut set = S::new();
+    assert!(set.is_empty() && set.len() == 0);
+    assert!(!set.contains(&1));
+    assert!(!set.remove(&1));
+    assert!(set.sorted().is_empty());
+
+    // 2. Duplicates and single-element removal
+    assert!(set.insert(7));
+    assert!(!set.insert(7));
+    assert_eq!(set.len(), 1);
+    assert!(set.contains(&7));
+    assert!(set.remove(&7));
+    assert!(!set.remove(&7));
+    assert!(set.is_empty());
+
+    // 3. Ascending inserts, descending removals, and the reverse
+    let mut model = BTreeSet::new();
+    for v in 0..5_000 {
+        assert!(set.insert(v));
+        model.insert(v);
+    }
+    check_sorted(&set, &model, "ascending inserts");
+    for v in (0..5_000).rev().step_by(2) {
+        assert!(set.remove(&v));
+        model.remove(&v);
+    }
+    check_sorted(&set, &model, "descending removals");
+    let mut set = S::new();
+    let mut model = BTreeSet::new();
+    for v in (0..5_000).rev() {
+        set.insert(v);
+        model.insert(v);
+    }
+    for v in 0..2_500 {
+        assert!(set.remove(&v));
+        model.remove(&v);
+    }
+    check_sorted(&set, &model, "descending inserts, ascending removals");
+
+    // 4. Random operations against the model
+    let mut rng = XorShift(seed);
+    let mut set = S::new();
+    let mut model = BTreeSet::new();
+    for step in 0..20_000 {
+        let v = rng.below(512) as u32;
+        match rng.below(3) {
+            0 => assert_eq!(set.insert(v), model.insert(v), "{}: insert({}) at step {}", S::NAME, v, step),
+            1 => assert_eq!(set.remove(&v), model.remove(&v), "{}: remove({}) at step {}", S::NAME, v, step),
+            _ => assert_eq!(set.contains(&v), model.contains(&v), "{}: contains({}) at step {}", S::NAME, v, step),
+        }
+        assert_eq!(set.len(), model.len(), "{}: len at step {}", S::NAME, step);
+        if step % 1_000 == 999 {
+            check_sorted(&set, &model, "random operations");
+        }
+    }
+
+    // 5. Drain everything
+    for v in model.iter() {
+        assert!(set.remove(v));
+    }
+    assert!(set.is_empty());
+    assert!(set.sorted().is_empty());
+}
+
+#[derive(Clone, Copy)]
+enum Op {
+    Insert(u64),
+    Remove(u64),
+    Contains(u64),
+}
+
+struct Workload {
+    name: &'static str,
+    prefill: Vec<u64>,
+    ops: Vec<Op>,
+}
+
+// Spread a dense rank over the key space so neighbouring ranks are not neighbouring keys
+fn scramble(rank: u64) -> u64 {
+    rank.wrapping_mul(0x9E3779B97F4A7C15)
+}
+
+// 90% inserts of fresh random keys, 10% lookups of earlier ones
+fn insert_heavy(n: usize, rng: &mut XorShift) -> Workload {
+    let mut keys = Vec::with_capacity(n);
+    let mut ops = Vec::with_capacity(n);
+    for _ in 0..n {
+        if keys.is_empty() || rng.below(10) != 0 {
+            let key = rng.next();
+            keys.push(key);
+            ops.push(Op::Insert(key));
+        } else {
+            ops.push(Op::Contains(keys[rng.below(keys.len() as u64) as usize]));
+        }
+    }
+    Workload { name: "insert-heavy", prefill: Vec::new(), ops }
+}
+
+// Prefilled set; 90% lookups (half of them misses), 5% inserts, 5% removals
+fn lookup_heavy(n: usize, rng: &mut XorShift) -> Workload {
+    let prefill: Vec<u64> = (0..n as u64).map(|i| scramble(2 * i)).collect();
+    let ops = (0..n)
+        .map(|_| {
+            let key = scramble(rng.below(2 * n as u64));
+            match rng.below(20) {
+                0 => Op::Insert(key),
+                1 => Op::Remove(key),
+                _ => Op::Contains(key),
+            }
+        })
+        .collect();
+    Workload { name: "lookup-heavy", prefill, ops }
+}
+
+// Ascending inserts, ascending lookups, ascending removals
+fn sequential(n: usize) -> Workload {
+    let n = n as u64 / 3;
+    let ops = (0..n)
+        .map(Op::Insert)
+        .chain((0..n).map(Op::Contains))
+        .chain((0..n).map(Op::Remove))
+        .collect();
+    Workload { name: "sequential", prefill: Vec::new(), ops }
+}
+
+// Prefilled set; keys are drawn with Zipf(s = 0.99) popularity over their rank,
+// 90% lookups and 10% remove-then-reinsert pairs so the set size stays fixed
+fn zipfian(n: usize, rng: &mut XorShift) -> Workload {
+    let mut cdf = Vec::with_capacity(n);
+    let mut total = 0.0;
+    for rank in 0..n {
+        total += 1.0 / ((rank + 1) as f64).powf(0.99);
+        cdf.push(total);
+    }
+    let sample = |rng: &mut XorShift| {
+        let target = rng.unit() * total;
+        scramble(cdf.partition_point(|&c| c < target).min(n - 1) as u64)
+    };
+
+    let prefill = (0..n as u64).map(scramble).collect();
+    let mut ops = Vec::with_capacity(n);
+    while ops.len() < n {
+        let key = sample(rng);
+        if rng.below(10) == 0 {
+            ops.push(Op::Remove(key));
+            ops.push(Op::Insert(key));
+        } else {
+            ops.push(Op::Contains(key));
+        }
+    }
+    Workload { name: "zipfian", prefill, ops }
+}
+
+// Median nanoseconds per operation over `runs` runs; the prefill is not timed
+fn measure<S: OrderedSet<u64>>(workload: &Workload, runs: usize) -> f64 {
+    let mut samples: Vec<f64> = (0..runs)
+        .map(|_| {
+            let mut set = S::new();
+            for &key in &workload.prefill {
+                set.insert(key);
+            }
+            let start = Instant::now();
+            for &op in &workload.ops {
+                let hit = match op {
+                    Op::Insert(key) => set.insert(key),
+                    Op::Remove(key) => set.remove(&key),
+                    Op::Contains(key) => set.contains(&key),
+                };
+                black_box(hit);
+            }
+            let elapsed = start.elapsed();
+            black_box(set.len());
+            elapsed.as_nanos() as f64 / workload.ops.len() as f64
+        })
+        .collect();
+    samples.sort_by(|a, b| a.total_cmp(b));
+    samples[runs / 2]
+}
+
+fn bench_row<S: OrderedSet<u64>>(workloads: &[Workload], runs: usize) {
+    print!("{:<14}", S::NAME);
+    for workload in workloads {
+        print!("{:>14.1}", measure::<S>(workload, runs));
+    }
+    println!();
+}
+
+fn main() {
+    // Conformance suite
+    conformance::<AVLTree<u32>>(0x1234_5678);
+    conformance::<RedBlackTree<u32>>(0x1234_5678);
+    conformance::<BTree<u32>>(0x1234_5678);
+    conformance::<Treap<u32>>(0x1234_5678);
+    conformance::<SplayTree<u32>>(0x1234_5678);
+    conformance::<SkipList<u32>>(0x1234_5678);
+    conformance::<BTreeSet<u32>>(0x1234_5678);
+    println!("Conformance suite passed for all 7 ordered sets");
+
+    // Benchmarks: operations per workload can be given as the first argument
+    let n: usize = std::env::args().nth(1).and_then(|arg| arg.parse().ok()).unwrap_or(100_000);
+    let runs = 5;
+    let mut rng = XorShift(0xDEAD_BEEF_CAFE_F00D);
+    let workloads = [
+        insert_heavy(n, &mut rng),
+        lookup_heavy(n, &mut rng),
+        sequential(n),
+        zipfian(n, &mut rng),
+    ];
+
+    println!("\nMedian ns/op over {} runs, about {} operations per workload:", runs, n);
+    print!("{:<14}", "");
+    for workload in &workloads {
+        print!("{:>14}", workload.name);
+    }
+    println!();
+    bench_row::<AVLTree<u64>>(&workloads, runs);
+    bench_row::<RedBlackTree<u64>>(&workloads, runs);
+    bench_row::<BTree<u64>>(&workloads, runs);
+    bench_row::<Treap<u64>>(&workloads, runs);
+    bench_row::<SplayTree<u64>>(&workloads, runs);
+    bench_row::<SkipList<u64>>(&workloads, runs);
+    bench_row::<BTreeSet<u64>>(&workloads, runs);
+}
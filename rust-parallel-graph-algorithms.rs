@@ -0,0 +1,606 @@
+use std::collections::{HashMap, VecDeque, BinaryHeap};
+use std::cmp::Ordering;
+use std::ops::Range;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering as AtomicOrdering};
+use std::sync::{mpsc, Arc, Condvar, Mutex};
+use std::thread;
+use std::time::Instant;
+
+// The sequential algorithms the parallel ones are checked against, and their
+// adjacency-list graph type
+#[path = "rust-graph-algorithms.rs"]
+#[allow(dead_code)]
+mod graph_algorithms;
+
+use graph_algorithms::{GraphError, Graph};
+
+const UNVISITED: usize = usize::MAX;
+const INFINITY: u64 = u64::MAX;
+
+// Work-stealing thread pool.
+// The workers are spawned once and live until the pool is dropped. A parallel
+// region splits its index range into chunks that are dealt out to per-worker
+// deques; each worker pops from the back of its own deque and steals from the
+// front of the others once it runs dry, then sleeps until the next region.
+// Tasks outlive the call that submits them as far as the type system knows, so
+// they must be 'static: the algorithms below share their graph and state
+// through `Arc`.
+type Task = Box<dyn FnOnce() + Send>;
+
+struct Signal {
+    // Bumped after every batch of tasks is pushed, so a worker that found
+    // nothing can tell whether it may sleep
+    epoch: u64,
+    shutdown: bool,
+}
+
+struct Shared {
+    deques: Vec<Mutex<VecDeque<Task>>>,
+    signal: Mutex<Signal>,
+    wake: Condvar,
+    steals: AtomicUsize,
+}
+
+impl Shared {
+    fn find_task(&self, worker: usize) -> Option<Task> {
+        let own = self.deques[worker].lock().unwrap().pop_back();
+        own.or_else(|| {
+            let stolen = (1..self.deques.len())
+                .map(|offset| (worker + offset) % self.deques.len())
+                .find_map(|victim| self.deques[victim].lock().unwrap().pop_front());
+            if stolen.is_some() {
+                self.steals.fetch_add(1, AtomicOrdering::Relaxed);
+            }
+            stolen
+        })
+    }
+
+    fn run_worker(&self, worker: usize) {
+        loop {
+            let epoch = self.signal.lock().unwrap().epoch;
+            while let Some(task) = self.find_task(worker) {
+                // A panicking task drops its result sender unsent, and the
+                // submitting thread reports it; the worker keeps serving
+                let _ = panic::catch_unwind(AssertUnwindSafe(task));
+            }
+            let mut signal = self.signal.lock().unwrap();
+            while signal.epoch == epoch && !signal.shutdown {
+                signal = self.wake.wait(signal).unwrap();
+            }
+            if signal.shutdown {
+                return;
+            }
+        }
+    }
+}
+
+struct WorkStealingPool {
+    shared: Arc<Shared>,
+    workers: Vec<thread::JoinHandle<()>>,
+}
+
+impl WorkStealingPool {
+    // A single thread runs every region inline without spawning workers
+    fn new(threads: usize) -> Self {
+        let threads = if threads > 1 { threads } else { 0 };
+        let shared = Arc::new(Shared {
+            deques: (0..threads).map(|_| Mutex::new(VecDeque::new())).collect(),
+            signal: Mutex::new(Signal { epoch: 0, shutdown: false }),
+            wake: Condvar::new(),
+            steals: AtomicUsize::new(0),
+        });
+        let workers = (0..threads)
+            .map(|worker| {
+                let shared = Arc::clone(&shared);
+                thread::spawn(move || shared.run_worker(worker))
+            })
+            .collect();
+        WorkStealingPool { shared, workers }
+    }
+
+    // Chunks taken from another worker's deque since the pool started
+    fn steals(&self) -> usize {
+        self.shared.steals.load(AtomicOrdering::Relaxed)
+    }
+
+    // Runs `f` on every chunk of `0..len` and returns the results in chunk order,
+    // so reductions over them are deterministic regardless of scheduling.
+    // Must not be called from inside a task: the caller blocks until its chunks are done.
+    fn map_chunks<T, F>(&self, len: usize, grain: usize, f: F) -> Vec<T>
+    where
+        T: Send + 'static,
+        F: Fn(Range<usize>) -> T + Send + Sync + 'static,
+    {
+        let grain = grain.max(1);
+        let chunks = len.div_ceil(grain);
+        let chunk = move |c: usize| c * grain..len.min((c + 1) * grain);
+        if self.workers.is_empty() || chunks <= 1 {
+            return (0..chunks).map(|c| f(chunk(c))).collect();
+        }
+
+        let f = Arc::new(f);
+        let (sender, receiver) = mpsc::channel();
+        let per_worker = chunks.div_ceil(self.workers.len());
+        for c in 0..chunks {
+            let (f, sender) = (Arc::clone(&f), sender.clone());
+            let task: Task = Box::new(move || {
+                // The receiver only goes away if the submitting thread has panicked
+                let _ = sender.send((c, f(chunk(c))));
+            });
+            self.shared.deques[c / per_worker].lock().unwrap().push_back(task);
+        }
+        drop(sender);
+        self.shared.signal.lock().unwrap().epoch += 1;
+        self.shared.wake.notify_all();
+
+        let mut results: Vec<Option<T>> = (0..chunks).map(|_| None).collect();
+        for _ in 0..chunks {
+            let (c, value) = receiver.recv().expect("a task panicked in a worker thread");
+            results[c] = Some(value);
+        }
+        results.into_iter().map(Option::unwrap).collect()
+    }
+
+    fn for_each_chunk<F: Fn(Range<usize>) + Send + Sync + 'static>(&self, len: usize, grain: usize, f: F) {
+        self.map_chunks(len, grain, f);
+    }
+}
+
+impl Drop for WorkStealingPool {
+    fn drop(&mut self) {
+        self.shared.signal.lock().unwrap().shutdown = true;
+        self.shared.wake.notify_all();
+        for worker in self.workers.drain(..) {
+            worker.join().unwrap();
+        }
+    }
+}
+
+// Compressed sparse row layout: the neighbors of `u` are targets[offsets[u]..offsets[u + 1]]
+struct CsrGraph {
+    offsets: Vec<usize>,
+    targets: Vec<usize>,
+    weights: Vec<u64>,
+}
+
+impl CsrGraph {
+    // Nodes are 0..n; negative weights are rejected since delta-stepping needs them non-negative
+    fn from_graph(graph: &Graph, n: usize) -> Result<Self, String> {
+        let mut edges = Vec::new();
+        for (&u, neighbors) in graph {
+            for &(v, w) in neighbors {
+                if u >= n || v >= n {
+                    return Err(format!("edge {} -> {} is outside 0..{}", u, v, n));
+                }
+                if w < 0 {
+                    return Err(format!("edge {} -> {} has negative weight {}", u, v, w));
+                }
+                edges.push((u, v, w as u64));
+            }
+        }
+        Ok(Self::from_edges(n, &edges))
+    }
+
+    fn from_edges(n: usize, edges: &[(usize, usize, u64)]) -> Self {
+        let mut offsets = vec![0; n + 1];
+        for &(u, _, _) in edges {
+            offsets[u + 1] += 1;
+        }
+        for i in 0..n {
+            offsets[i + 1] += offsets[i];
+        }
+        let mut cursor = offsets.clone();
+        let mut targets = vec![0; edges.len()];
+        let mut weights = vec![0; edges.len()];
+        for &(u, v, w) in edges {
+            targets[cursor[u]] = v;
+            weights[cursor[u]] = w;
+            cursor[u] += 1;
+        }
+        CsrGraph { offsets, targets, weights }
+    }
+
+    // Back to an adjacency list, with every node a key as rust-graph-algorithms.rs expects
+    fn to_graph(&self) -> Graph {
+        (0..self.node_count())
+            .map(|u| (u, self.neighbors(u).map(|(v, w)| (v, w as i32)).collect()))
+            .collect()
+    }
+
+    fn node_count(&self) -> usize {
+        self.offsets.len() - 1
+    }
+
+    fn neighbors(&self, u: usize) -> impl Iterator<Item = (usize, u64)> + '_ {
+        let range = self.offsets[u]..self.offsets[u + 1];
+        self.targets[range.clone()].iter().copied().zip(self.weights[range].iter().copied())
+    }
+
+    fn out_degree(&self, u: usize) -> usize {
+        self.offsets[u + 1] - self.offsets[u]
+    }
+
+    fn transpose(&self) -> Self {
+        let mut edges = Vec::with_capacity(self.targets.len());
+        for u in 0..self.node_count() {
+            for (v, w) in self.neighbors(u) {
+                edges.push((v, u, w));
+            }
+        }
+        Self::from_edges(self.node_count(), &edges)
+    }
+
+    fn symmetrize(&self) -> Self {
+        let mut edges = Vec::with_capacity(self.targets.len() * 2);
+        for u in 0..self.node_count() {
+            for (v, w) in self.neighbors(u) {
+                edges.push((u, v, w));
+                edges.push((v, u, w));
+            }
+        }
+        Self::from_edges(self.node_count(), &edges)
+    }
+}
+
+// Sequential reference for whole-graph checks: BFS levels over the CSR layout.
+// `bfs` in rust-graph-algorithms.rs only prints the visit order, so levels are
+// spot-checked against its `dijkstra` over unit weights in main instead.
+fn bfs_levels(graph: &CsrGraph, start: usize) -> Vec<usize> {
+    let mut level = vec![UNVISITED; graph.node_count()];
+    let mut queue = VecDeque::new();
+    level[start] = 0;
+    queue.push_back(start);
+    while let Some(u) = queue.pop_front() {
+        for (v, _) in graph.neighbors(u) {
+            if level[v] == UNVISITED {
+                level[v] = level[u] + 1;
+                queue.push_back(v);
+            }
+        }
+    }
+    level
+}
+
+// Sequential reference for whole-graph checks: Dijkstra distances from one source
+// to every node. `dijkstra` in rust-graph-algorithms.rs answers one target per
+// call, so main checks it on a sample of targets.
+#[derive(Copy, Clone, Eq, PartialEq)]
+struct State {
+    cost: u64,
+    node: usize,
+}
+
+impl Ord for State {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.cost.cmp(&self.cost)
+    }
+}
+
+impl PartialOrd for State {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+fn dijkstra_distances(graph: &CsrGraph, start: usize) -> Vec<u64> {
+    let mut dist = vec![INFINITY; graph.node_count()];
+    let mut heap = BinaryHeap::new();
+    dist[start] = 0;
+    heap.push(State { cost: 0, node: start });
+    while let Some(State { cost, node }) = heap.pop() {
+        if cost > dist[node] {
+            continue;
+        }
+        for (next, w) in graph.neighbors(node) {
+            if cost + w < dist[next] {
+                dist[next] = cost + w;
+                heap.push(State { cost: cost + w, node: next });
+            }
+        }
+    }
+    dist
+}
+
+// 1. Level-synchronous parallel BFS.
+// Returns levels plus the smallest-id parent on the previous level, so the tree
+// is the same no matter which thread discovers a node first.
+fn parallel_bfs(pool: &WorkStealingPool, graph: &Arc<CsrGraph>, start: usize) -> (Vec<usize>, Vec<usize>) {
+    let n = graph.node_count();
+    let level: Arc<Vec<AtomicUsize>> = Arc::new((0..n).map(|_| AtomicUsize::new(UNVISITED)).collect());
+    let parent: Arc<Vec<AtomicUsize>> = Arc::new((0..n).map(|_| AtomicUsize::new(UNVISITED)).collect());
+    level[start].store(0, AtomicOrdering::Relaxed);
+
+    let mut frontier = Arc::new(vec![start]);
+    let mut depth = 0;
+    while !frontier.is_empty() {
+        let (graph, level, parent, current) = (Arc::clone(graph), Arc::clone(&level), Arc::clone(&parent), Arc::clone(&frontier));
+        let next_parts = pool.map_chunks(frontier.len(), 256, move |range| {
+            let mut discovered = Vec::new();
+            for &u in &current[range] {
+                for (v, _) in graph.neighbors(u) {
+                    let claimed = level[v]
+                        .compare_exchange(UNVISITED, depth + 1, AtomicOrdering::Relaxed, AtomicOrdering::Relaxed);
+                    match claimed {
+                        Ok(_) => {
+                            discovered.push(v);
+                            parent[v].fetch_min(u, AtomicOrdering::Relaxed);
+                        }
+                        Err(l) if l == depth + 1 => {
+                            parent[v].fetch_min(u, AtomicOrdering::Relaxed);
+                        }
+                        Err(_) => {}
+                    }
+                }
+            }
+            discovered
+        });
+        let mut next = next_parts.concat();
+        next.sort_unstable();
+        frontier = Arc::new(next);
+        depth += 1;
+    }
+
+    let load = |values: &[AtomicUsize]| values.iter().map(|x| x.load(AtomicOrdering::Relaxed)).collect();
+    (load(&level), load(&parent))
+}
+
+// 2. Connected components by label propagation over the symmetrized graph.
+// Every node ends up labeled with the smallest node id in its component.
+fn parallel_connected_components(pool: &WorkStealingPool, graph: &Arc<CsrGraph>) -> Vec<usize> {
+    let undirected = Arc::new(graph.symmetrize());
+    let n = undirected.node_count();
+    let labels: Arc<Vec<AtomicUsize>> = Arc::new((0..n).map(AtomicUsize::new).collect());
+
+    loop {
+        let changed = Arc::new(AtomicBool::new(false));
+        let (undirected, labels, flag) = (Arc::clone(&undirected), Arc::clone(&labels), Arc::clone(&changed));
+        pool.for_each_chunk(n, 1024, move |range| {
+            for u in range {
+                let mut best = labels[u].load(AtomicOrdering::Relaxed);
+                for (v, _) in undirected.neighbors(u) {
+                    best = best.min(labels[v].load(AtomicOrdering::Relaxed));
+                }
+                if labels[u].fetch_min(best, AtomicOrdering::Relaxed) > best {
+                    flag.store(true, AtomicOrdering::Relaxed);
+                }
+                for (v, _) in undirected.neighbors(u) {
+                    if labels[v].fetch_min(best, AtomicOrdering::Relaxed) > best {
+                        flag.store(true, AtomicOrdering::Relaxed);
+                    }
+                }
+            }
+        });
+        if !changed.load(AtomicOrdering::Relaxed) {
+            break;
+        }
+    }
+
+    labels.iter().map(|label| label.load(AtomicOrdering::Relaxed)).collect()
+}
+
+// 3. Pull-based PageRank.
+// Each node sums over its in-neighbors in a fixed order and partial sums are combined
+// in chunk order, so the result is bit-identical for any thread count.
+fn parallel_pagerank(pool: &WorkStealingPool, graph: &Arc<CsrGraph>, damping: f64, tolerance: f64, max_iterations: usize) -> Vec<f64> {
+    const GRAIN: usize = 1024;
+    let n = graph.node_count();
+    if n == 0 {
+        return Vec::new();
+    }
+    let incoming = Arc::new(graph.transpose());
+    let mut rank = Arc::new(vec![1.0 / n as f64; n]);
+
+    for _ in 0..max_iterations {
+        let (g, r) = (Arc::clone(graph), Arc::clone(&rank));
+        let dangling: f64 = pool
+            .map_chunks(n, GRAIN, move |range| range.filter(|&u| g.out_degree(u) == 0).map(|u| r[u]).sum::<f64>())
+            .into_iter()
+            .sum();
+        let base = (1.0 - damping) / n as f64 + damping * dangling / n as f64;
+
+        let (g, r, incoming) = (Arc::clone(graph), Arc::clone(&rank), Arc::clone(&incoming));
+        let parts = pool.map_chunks(n, GRAIN, move |range| {
+            range
+                .map(|v| {
+                    let pulled: f64 = incoming.neighbors(v).map(|(u, _)| r[u] / g.out_degree(u) as f64).sum();
+                    base + damping * pulled
+                })
+                .collect::<Vec<f64>>()
+        });
+        let next = parts.concat();
+        let delta: f64 = rank.iter().zip(&next).map(|(a, b)| (a - b).abs()).sum();
+        rank = Arc::new(next);
+        if delta < tolerance {
+            break;
+        }
+    }
+
+    rank.to_vec()
+}
+
+// 4. Delta-stepping single-source shortest paths.
+// Nodes are kept in buckets of width `delta`; light edges (w <= delta) of a bucket are
+// relaxed in parallel until it stops refilling, then its heavy edges are relaxed once.
+fn parallel_delta_stepping(pool: &WorkStealingPool, graph: &Arc<CsrGraph>, start: usize, delta: u64) -> Vec<u64> {
+    let delta = delta.max(1);
+    let n = graph.node_count();
+    let dist: Arc<Vec<AtomicU64>> = Arc::new((0..n).map(|_| AtomicU64::new(INFINITY)).collect());
+    dist[start].store(0, AtomicOrdering::Relaxed);
+    let mut buckets: Vec<Vec<usize>> = vec![vec![start]];
+
+    let relax = |frontier: Vec<usize>, light: bool| -> Vec<usize> {
+        let frontier = Arc::new(frontier);
+        let (graph, dist, len) = (Arc::clone(graph), Arc::clone(&dist), frontier.len());
+        pool.map_chunks(len, 256, move |range| {
+            let mut improved = Vec::new();
+            for &u in &frontier[range] {
+                let du = dist[u].load(AtomicOrdering::Relaxed);
+                for (v, w) in graph.neighbors(u) {
+                    if (w <= delta) == light {
+                        let candidate = du + w;
+                        if dist[v].fetch_min(candidate, AtomicOrdering::Relaxed) > candidate {
+                            improved.push(v);
+                        }
+                    }
+                }
+            }
+            improved
+        })
+        .concat()
+    };
+
+    let mut i = 0;
+    while i < buckets.len() {
+        let mut settled = Vec::new();
+        while !buckets[i].is_empty() {
+            let mut frontier = std::mem::take(&mut buckets[i]);
+            frontier.sort_unstable();
+            frontier.dedup();
+            // Drop stale entries whose distance has since moved to an earlier bucket
+            frontier.retain(|&u| dist[u].load(AtomicOrdering::Relaxed) / delta == i as u64);
+            settled.extend_from_slice(&frontier);
+            for v in relax(frontier, true) {
+                push_bucket(&mut buckets, dist[v].load(AtomicOrdering::Relaxed) / delta, v);
+            }
+        }
+        settled.sort_unstable();
+        settled.dedup();
+        for v in relax(settled, false) {
+            push_bucket(&mut buckets, dist[v].load(AtomicOrdering::Relaxed) / delta, v);
+        }
+        i += 1;
+    }
+
+    dist.iter().map(|d| d.load(AtomicOrdering::Relaxed)).collect()
+}
+
+fn push_bucket(buckets: &mut Vec<Vec<usize>>, index: u64, node: usize) {
+    let index = index as usize;
+    if buckets.len() <= index {
+        buckets.resize_with(index + 1, Vec::new);
+    }
+    buckets[index].push(node);
+}
+
+// Small xorshift generator so the benchmark graph is reproducible without external crates
+fn random_graph(n: usize, m: usize, max_weight: u64, seed: u64) -> CsrGraph {
+    let mut state = seed;
+    let mut next = move || {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state
+    };
+    let edges: Vec<(usize, usize, u64)> = (0..m)
+        .map(|_| ((next() % n as u64) as usize, (next() % n as u64) as usize, 1 + next() % max_weight))
+        .collect();
+    CsrGraph::from_edges(n, &edges)
+}
+
+fn main() {
+    // Example graph
+    let mut graph: Graph = HashMap::new();
+    graph.insert(0, vec![(1, 4), (2, 1)]);
+    graph.insert(1, vec![(3, 1)]);
+    graph.insert(2, vec![(1, 2), (3, 5)]);
+    graph.insert(3, vec![]);
+    graph.insert(4, vec![(5, 2)]);
+    graph.insert(5, vec![]);
+
+    let csr = Arc::new(CsrGraph::from_graph(&graph, 6).unwrap());
+    let pool = WorkStealingPool::new(4);
+
+    // 1. Parallel BFS
+    let (levels, parents) = parallel_bfs(&pool, &csr, 0);
+    let reached = |values: &[usize]| values.iter().map(|&x| (x != UNVISITED).then_some(x)).collect::<Vec<_>>();
+    println!("Parallel BFS levels: {:?}", reached(&levels));
+    println!("Parallel BFS parents: {:?}", reached(&parents));
+
+    // 2. Connected Components
+    println!("Components: {:?}", parallel_connected_components(&pool, &csr));
+
+    // 3. PageRank
+    let ranks = parallel_pagerank(&pool, &csr, 0.85, 1e-10, 100);
+    println!("PageRank: {:?}", ranks.iter().map(|r| format!("{:.4}", r)).collect::<Vec<_>>());
+
+    // 4. Delta-Stepping
+    let distances = parallel_delta_stepping(&pool, &csr, 0, 2);
+    println!("Delta-stepping distances: {:?}", distances.iter().map(|&d| (d != INFINITY).then_some(d)).collect::<Vec<_>>());
+
+    // Cross-check against `dijkstra` in rust-graph-algorithms.rs, over unit weights
+    // for BFS levels, then against the local references and across thread counts
+    println!("\nVerifying against sequential BFS and Dijkstra:");
+    let reference = |graph: &Graph, target: usize| match graph_algorithms::dijkstra(graph, 0, target) {
+        Ok((_, cost)) => cost as u64,
+        Err(GraphError::NoPath { .. }) => INFINITY,
+        Err(e) => panic!("dijkstra to {}: {}", target, e),
+    };
+    let hops = |graph: &Graph| -> Graph {
+        graph.iter().map(|(&u, edges)| (u, edges.iter().map(|&(v, _)| (v, 1)).collect())).collect()
+    };
+    let as_hops = |level: usize| if level == UNVISITED { INFINITY } else { level as u64 };
+    let unit = hops(&graph);
+    for target in 0..csr.node_count() {
+        assert_eq!(distances[target], reference(&graph, target), "distance to {}", target);
+        assert_eq!(as_hops(levels[target]), reference(&unit, target), "level of {}", target);
+    }
+
+    let big = Arc::new(random_graph(20_000, 120_000, 50, 0x9E3779B97F4A7C15));
+    let big_graph = big.to_graph();
+    let big_unit = hops(&big_graph);
+    let big_levels = parallel_bfs(&pool, &big, 0).0;
+    let big_distances = parallel_delta_stepping(&pool, &big, 0, 16);
+    for target in (0..big.node_count()).step_by(997) {
+        assert_eq!(big_distances[target], reference(&big_graph, target), "distance to {}", target);
+        assert_eq!(as_hops(big_levels[target]), reference(&big_unit, target), "level of {}", target);
+    }
+    let single = WorkStealingPool::new(1);
+    assert_eq!(parallel_bfs(&pool, &big, 0).0, bfs_levels(&big, 0));
+    assert_eq!(parallel_bfs(&pool, &big, 0), parallel_bfs(&single, &big, 0));
+    assert_eq!(parallel_delta_stepping(&pool, &big, 0, 16), dijkstra_distances(&big, 0));
+    assert_eq!(parallel_connected_components(&pool, &big), parallel_connected_components(&single, &big));
+    assert_eq!(parallel_pagerank(&pool, &big, 0.85, 1e-9, 50), parallel_pagerank(&single, &big, 0.85, 1e-9, 50));
+    println!("All parallel results match");
+
+    // A panicking task is reported to the caller and leaves the workers serving
+    panic::set_hook(Box::new(|_| {}));
+    let failed = panic::catch_unwind(AssertUnwindSafe(|| {
+        pool.for_each_chunk(64, 1, |range| assert!(range.start != 17, "task failed"));
+    }));
+    let _ = panic::take_hook();
+    assert!(failed.is_err());
+    assert_eq!(pool.map_chunks(1000, 10, |range| range.sum::<usize>()).into_iter().sum::<usize>(), 499_500);
+    println!("Pool recovered after a panicking task ({} chunks stolen so far)", pool.steals());
+
+    // Benchmark scaling with the number of worker threads
+    println!("\nScaling benchmark (200k nodes, 2M edges):");
+    let bench = Arc::new(random_graph(200_000, 2_000_000, 100, 42));
+    let cores = thread::available_parallelism().map_or(1, |c| c.get());
+    println!("{} cores available; the pool is reused across all four algorithms", cores);
+    let thread_counts = [1, 2, 4, 8];
+    println!("{:>8} {:>10} {:>10} {:>10} {:>10} {:>8}", "threads", "bfs", "cc", "pagerank", "sssp", "steals");
+    for threads in thread_counts {
+        let pool = WorkStealingPool::new(threads);
+        let time = |f: &dyn Fn()| {
+            let begin = Instant::now();
+            f();
+            format!("{:.1?}", begin.elapsed())
+        };
+        println!(
+            "{:>8} {:>10} {:>10} {:>10} {:>10} {:>8}",
+            threads,
+            time(&|| { parallel_bfs(&pool, &bench, 0); }),
+            time(&|| { parallel_connected_components(&pool, &bench); }),
+            time(&|| { parallel_pagerank(&pool, &bench, 0.85, 1e-6, 20); }),
+            time(&|| { parallel_delta_stepping(&pool, &bench, 0, 32); }),
+            pool.steals(),
+        );
+    }
+    let begin = Instant::now();
+    bfs_levels(&bench, 0);
+    let bfs_time = begin.elapsed();
+    let begin = Instant::now();
+    dijkstra_distances(&bench, 0);
+    println!("sequential bfs {:.1?}, dijkstra {:.1?}", bfs_time, begin.elapsed());
+}
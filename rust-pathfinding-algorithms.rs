@@ -1,162 +1,257 @@
 use std::collections::{HashMap, HashSet, BinaryHeap};
 use std::cmp::Ordering;
+use std::hash::Hash;
+use std::ops::Add;
+
+#[path = "rust-graph-algorithms.rs"]
+#[allow(dead_code)]
+mod graph_algorithms;
+
+use graph_algorithms::GraphError;
 
 // Define a simple graph structure
-type Graph = HashMap<char, Vec<(char, u32)>>;
+type Graph<N, C> = HashMap<N, Vec<(N, C)>>;
+
+// Edge costs must be totally ordered and summable, starting from zero
+trait Cost: Copy + Ord + Add<Output = Self> {
+    fn zero() -> Self;
+}
+
+macro_rules! impl_integer_cost {
+    ($($t:ty),*) => {
+        $(impl Cost for $t {
+            fn zero() -> Self {
+                0
+            }
+        })*
+    };
+}
+
+impl_integer_cost!(u8, u16, u32, u64, usize, i32, i64);
+
+// Total-order wrapper so f64 costs can be used as heap priorities
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct TotalF64(f64);
+
+impl Eq for TotalF64 {}
+
+impl Ord for TotalF64 {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.total_cmp(&other.0)
+    }
+}
+
+impl PartialOrd for TotalF64 {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Add for TotalF64 {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        TotalF64(self.0 + other.0)
+    }
+}
+
+impl Cost for TotalF64 {
+    fn zero() -> Self {
+        TotalF64(0.0)
+    }
+}
+
+// Source of outgoing edges, so implicit graphs need not be materialized
+trait Neighbors<N, C> {
+    fn neighbors(&self, node: &N) -> Vec<(N, C)>;
+
+    // Implicit graphs cannot enumerate their nodes, so every node is assumed valid
+    fn contains(&self, _node: &N) -> bool {
+        true
+    }
+
+    // Nor their edges, so only explicit graphs check for dangling ones
+    fn validate(&self) -> Result<(), GraphError<N>> {
+        Ok(())
+    }
+}
+
+// Every node is a key, sinks included, as in rust-graph-algorithms.rs
+impl<N: Hash + Eq + Clone, C: Clone> Neighbors<N, C> for Graph<N, C> {
+    fn neighbors(&self, node: &N) -> Vec<(N, C)> {
+        self.get(node).cloned().unwrap_or_default()
+    }
+
+    fn contains(&self, node: &N) -> bool {
+        self.contains_key(node)
+    }
+
+    fn validate(&self) -> Result<(), GraphError<N>> {
+        for (from, edges) in self {
+            if let Some((to, _)) = edges.iter().find(|(to, _)| !self.contains_key(to)) {
+                return Err(GraphError::DanglingEdge { from: from.clone(), to: to.clone() });
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<N, C, F: Fn(&N) -> Vec<(N, C)>> Neighbors<N, C> for F {
+    fn neighbors(&self, node: &N) -> Vec<(N, C)> {
+        self(node)
+    }
+}
+
+// The graph must be well formed and both endpoints known to it
+fn check_endpoints<N: Clone, C>(graph: &impl Neighbors<N, C>, start: &N, goal: &N) -> Result<(), GraphError<N>> {
+    graph.validate()?;
+    for node in [start, goal] {
+        if !graph.contains(node) {
+            return Err(GraphError::UnknownNode(node.clone()));
+        }
+    }
+    Ok(())
+}
 
 // Node for A* and Dijkstra's algorithms
-#[derive(Copy, Clone, Eq, PartialEq)]
-struct Node {
-    position: char,
-    cost: u32,
-    heuristic: u32,
+#[derive(Clone, Eq, PartialEq)]
+struct Node<N, C> {
+    position: N,
+    cost: C,
+    heuristic: C,
 }
 
-impl Ord for Node {
+impl<N: Eq, C: Cost> Ord for Node<N, C> {
     fn cmp(&self, other: &Self) -> Ordering {
         (other.cost + other.heuristic).cmp(&(self.cost + self.heuristic))
     }
 }
 
-impl PartialOrd for Node {
+impl<N: Eq, C: Cost> PartialOrd for Node<N, C> {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
         Some(self.cmp(other))
     }
 }
 
 // Breadth-First Search (BFS)
-fn bfs(graph: &Graph, start: char, goal: char) -> Option<Vec<char>> {
+fn bfs<N, C>(graph: &impl Neighbors<N, C>, start: N, goal: N) -> Result<Vec<N>, GraphError<N>>
+where
+    N: Hash + Eq + Clone,
+{
+    check_endpoints(graph, &start, &goal)?;
     let mut queue = std::collections::VecDeque::new();
     let mut visited = HashSet::new();
     let mut came_from = HashMap::new();
 
-    queue.push_back(start);
-    visited.insert(start);
+    queue.push_back(start.clone());
+    visited.insert(start.clone());
 
     while let Some(current) = queue.pop_front() {
         if current == goal {
-            return Some(reconstruct_path(&came_from, start, goal));
+            return reconstruct_path(&came_from, start, goal);
         }
 
-        if let Some(neighbors) = graph.get(&current) {
-            for &(next, _) in neighbors {
-                if visited.insert(next) {
-                    queue.push_back(next);
-                    came_from.insert(next, current);
-                }
+        for (next, _) in graph.neighbors(&current) {
+            if visited.insert(next.clone()) {
+                came_from.insert(next.clone(), current.clone());
+                queue.push_back(next);
             }
         }
     }
 
-    None
+    Err(GraphError::NoPath { from: start, to: goal })
 }
 
 // Depth-First Search (DFS)
-fn dfs(graph: &Graph, start: char, goal: char) -> Option<Vec<char>> {
-    let mut stack = vec![start];
+fn dfs<N, C>(graph: &impl Neighbors<N, C>, start: N, goal: N) -> Result<Vec<N>, GraphError<N>>
+where
+    N: Hash + Eq + Clone,
+{
+    check_endpoints(graph, &start, &goal)?;
+    let mut stack = vec![start.clone()];
     let mut visited = HashSet::new();
     let mut came_from = HashMap::new();
 
     while let Some(current) = stack.pop() {
         if current == goal {
-            return Some(reconstruct_path(&came_from, start, goal));
+            return reconstruct_path(&came_from, start, goal);
         }
 
-        if visited.insert(current) {
-            if let Some(neighbors) = graph.get(&current) {
-                for &(next, _) in neighbors {
-                    if !visited.contains(&next) {
-                        stack.push(next);
-                        came_from.insert(next, current);
-                    }
+        if visited.insert(current.clone()) {
+            for (next, _) in graph.neighbors(&current) {
+                if !visited.contains(&next) {
+                    came_from.insert(next.clone(), current.clone());
+                    stack.push(next);
                 }
             }
         }
     }
 
-    None
+    Err(GraphError::NoPath { from: start, to: goal })
 }
 
 // Dijkstra's Algorithm
-fn dijkstra(graph: &Graph, start: char, goal: char) -> Option<(Vec<char>, u32)> {
-    let mut heap = BinaryHeap::new();
-    let mut dist = HashMap::new();
-    let mut came_from = HashMap::new();
-
-    heap.push(Node { position: start, cost: 0, heuristic: 0 });
-    dist.insert(start, 0);
-
-    while let Some(Node { position, cost, .. }) = heap.pop() {
-        if position == goal {
-            return Some((reconstruct_path(&came_from, start, goal), cost));
-        }
-
-        if cost > *dist.get(&position).unwrap_or(&u32::MAX) {
-            continue;
-        }
-
-        if let Some(neighbors) = graph.get(&position) {
-            for &(next, edge_cost) in neighbors {
-                let new_cost = cost + edge_cost;
-                if new_cost < *dist.get(&next).unwrap_or(&u32::MAX) {
-                    heap.push(Node { position: next, cost: new_cost, heuristic: 0 });
-                    dist.insert(next, new_cost);
-                    came_from.insert(next, position);
-                }
-            }
-        }
-    }
-
-    None
+fn dijkstra<N, C>(graph: &impl Neighbors<N, C>, start: N, goal: N) -> Result<(Vec<N>, C), GraphError<N>>
+where
+    N: Hash + Eq + Clone,
+    C: Cost,
+{
+    a_star(graph, start, goal, |_| C::zero())
 }
 
 // A* Algorithm
-fn a_star(graph: &Graph, start: char, goal: char, h: impl Fn(char) -> u32) -> Option<(Vec<char>, u32)> {
+fn a_star<N, C>(graph: &impl Neighbors<N, C>, start: N, goal: N, h: impl Fn(&N) -> C) -> Result<(Vec<N>, C), GraphError<N>>
+where
+    N: Hash + Eq + Clone,
+    C: Cost,
+{
+    check_endpoints(graph, &start, &goal)?;
     let mut heap = BinaryHeap::new();
     let mut g_score = HashMap::new();
     let mut came_from = HashMap::new();
 
-    heap.push(Node { position: start, cost: 0, heuristic: h(start) });
-    g_score.insert(start, 0);
+    heap.push(Node { position: start.clone(), cost: C::zero(), heuristic: h(&start) });
+    g_score.insert(start.clone(), C::zero());
 
     while let Some(Node { position, cost, .. }) = heap.pop() {
         if position == goal {
-            return Some((reconstruct_path(&came_from, start, goal), cost));
+            return Ok((reconstruct_path(&came_from, start, goal)?, cost));
         }
 
-        if cost > *g_score.get(&position).unwrap_or(&u32::MAX) {
+        if g_score.get(&position).is_some_and(|&best| cost > best) {
             continue;
         }
 
-        if let Some(neighbors) = graph.get(&position) {
-            for &(next, edge_cost) in neighbors {
-                let tentative_g_score = cost + edge_cost;
-                if tentative_g_score < *g_score.get(&next).unwrap_or(&u32::MAX) {
-                    came_from.insert(next, position);
-                    g_score.insert(next, tentative_g_score);
-                    heap.push(Node {
-                        position: next,
-                        cost: tentative_g_score,
-                        heuristic: h(next),
-                    });
-                }
+        for (next, edge_cost) in graph.neighbors(&position) {
+            let tentative_g_score = cost + edge_cost;
+            if g_score.get(&next).is_none_or(|&best| tentative_g_score < best) {
+                came_from.insert(next.clone(), position.clone());
+                g_score.insert(next.clone(), tentative_g_score);
+                heap.push(Node {
+                    heuristic: h(&next),
+                    position: next,
+                    cost: tentative_g_score,
+                });
             }
         }
     }
 
-    None
+    Err(GraphError::NoPath { from: start, to: goal })
 }
 
 // Helper function to reconstruct the path
-fn reconstruct_path(came_from: &HashMap<char, char>, start: char, goal: char) -> Vec<char> {
-    let mut path = vec![goal];
-    let mut current = goal;
+fn reconstruct_path<N: Hash + Eq + Clone>(came_from: &HashMap<N, N>, start: N, goal: N) -> Result<Vec<N>, GraphError<N>> {
+    let mut path = vec![goal.clone()];
+    let mut current = goal.clone();
     while current != start {
-        current = *came_from.get(&current).unwrap();
-        path.push(current);
+        current = match came_from.get(&current) {
+            Some(prev) => prev.clone(),
+            None => return Err(GraphError::NoPath { from: start, to: goal }),
+        };
+        path.push(current.clone());
     }
     path.reverse();
-    path
+    Ok(path)
 }
 
 fn main() {
@@ -174,23 +269,90 @@ fn main() {
     let goal = 'G';
 
     // BFS
-    if let Some(path) = bfs(&graph, start, goal) {
-        println!("BFS Path: {:?}", path);
+    match bfs(&graph, start, goal) {
+        Ok(path) => println!("BFS Path: {:?}", path),
+        Err(e) => println!("BFS Error: {}", e),
     }
 
     // DFS
-    if let Some(path) = dfs(&graph, start, goal) {
-        println!("DFS Path: {:?}", path);
+    match dfs(&graph, start, goal) {
+        Ok(path) => println!("DFS Path: {:?}", path),
+        Err(e) => println!("DFS Error: {}", e),
     }
 
     // Dijkstra's Algorithm
-    if let Some((path, cost)) = dijkstra(&graph, start, goal) {
-        println!("Dijkstra Path: {:?}, Cost: {}", path, cost);
+    match dijkstra(&graph, start, goal) {
+        Ok((path, cost)) => println!("Dijkstra Path: {:?}, Cost: {}", path, cost),
+        Err(e) => println!("Dijkstra Error: {}", e),
     }
 
     // A* Algorithm
-    let h = |_| 0; // No heuristic for this example
-    if let Some((path, cost)) = a_star(&graph, start, goal, h) {
-        println!("A* Path: {:?}, Cost: {}", path, cost);
+    let h = |_: &char| 0; // No heuristic for this example
+    match a_star(&graph, start, goal, h) {
+        Ok((path, cost)) => println!("A* Path: {:?}, Cost: {}", path, cost),
+        Err(e) => println!("A* Error: {}", e),
+    }
+
+    assert_eq!(bfs(&graph, start, goal).map(|path| path.len()), Ok(4));
+    assert_eq!(dijkstra(&graph, start, goal).map(|(_, cost)| cost), Ok(8));
+    assert_eq!(a_star(&graph, start, goal, h).map(|(_, cost)| cost), Ok(8));
+
+    // Error cases
+    let unknown = bfs(&graph, start, 'Z');
+    println!("BFS Error: {}", unknown.clone().unwrap_err());
+    assert_eq!(unknown, Err(GraphError::UnknownNode('Z')));
+    assert_eq!(dfs(&graph, 'Z', goal), Err(GraphError::UnknownNode('Z')));
+    let no_path = dijkstra(&graph, goal, start);
+    println!("Dijkstra Error: {}", no_path.clone().unwrap_err());
+    assert_eq!(no_path, Err(GraphError::NoPath { from: goal, to: start }));
+    assert_eq!(dfs(&graph, 'C', 'B'), Err(GraphError::NoPath { from: 'C', to: 'B' }));
+    let mut dangling = graph.clone();
+    dangling.get_mut(&'F').unwrap().push(('X', 1));
+    let expected = Err(GraphError::DanglingEdge { from: 'F', to: 'X' });
+    println!("BFS Error: {}", expected.clone().unwrap_err());
+    assert_eq!(bfs(&dangling, start, goal), expected);
+    assert_eq!(dijkstra(&dangling, start, goal).map(|(path, _)| path), expected);
+
+    // String city names with floating-point distances
+    let mut roads: Graph<String, TotalF64> = Graph::new();
+    let mut road = |from: &str, to: &str, km: f64| {
+        roads.entry(from.to_string()).or_default().push((to.to_string(), TotalF64(km)));
+        roads.entry(to.to_string()).or_default().push((from.to_string(), TotalF64(km)));
+    };
+    road("Lisbon", "Madrid", 625.4);
+    road("Madrid", "Barcelona", 505.2);
+    road("Lisbon", "Porto", 313.1);
+    road("Porto", "Barcelona", 1010.7);
+    match dijkstra(&roads, "Lisbon".to_string(), "Barcelona".to_string()) {
+        Ok((path, km)) => println!("Road Path: {:?}, Distance: {:.1} km", path, km.0),
+        Err(e) => println!("Road Error: {}", e),
+    }
+    let (path, km) = dijkstra(&roads, "Lisbon".to_string(), "Barcelona".to_string()).unwrap();
+    assert_eq!(path, ["Lisbon", "Madrid", "Barcelona"]);
+    assert!((km.0 - 1130.6).abs() < 1e-9);
+
+    // Implicit grid graph of coordinate tuples, with neighbors generated on demand
+    let walls: HashSet<(i32, i32)> = [(1, 0), (1, 1), (1, 2), (3, 1), (3, 2), (3, 3)].into_iter().collect();
+    let grid = |&(x, y): &(i32, i32)| -> Vec<((i32, i32), u32)> {
+        [(x + 1, y), (x - 1, y), (x, y + 1), (x, y - 1)]
+            .into_iter()
+            .filter(|&(nx, ny)| (0..5).contains(&nx) && (0..5).contains(&ny) && !walls.contains(&(nx, ny)))
+            .map(|pos| (pos, 1))
+            .collect()
+    };
+    let target = (4, 0);
+    let manhattan = |&(x, y): &(i32, i32)| x.abs_diff(target.0) + y.abs_diff(target.1);
+    match a_star(&grid, (0, 0), target, manhattan) {
+        Ok((path, steps)) => println!("Grid Path: {:?}, Steps: {}", path, steps),
+        Err(e) => println!("Grid Error: {}", e),
+    }
+    match bfs(&grid, (0, 0), target) {
+        Ok(path) => println!("Grid BFS Path Length: {}", path.len() - 1),
+        Err(e) => println!("Grid Error: {}", e),
     }
+    assert_eq!(a_star(&grid, (0, 0), target, manhattan).map(|(_, steps)| steps), Ok(10));
+    assert_eq!(bfs(&grid, (0, 0), target).map(|path| path.len() - 1), Ok(10));
+    // The implicit grid accepts any coordinate as a node but cannot reach one off the board
+    assert_eq!(a_star(&grid, (0, 0), (9, 9), |_| 0), Err(GraphError::NoPath { from: (0, 0), to: (9, 9) }));
+    println!("All paths and error cases matched");
 }
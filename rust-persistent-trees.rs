@@ -0,0 +1,700 @@
+use std::cmp::Ordering;
+use std::collections::{BTreeSet, HashSet};
+use std::mem;
+use std::rc::Rc;
+use std::time::Instant;
+
+// Persistent (immutable, structurally shared) AVL and red-black trees.
+//
+// Every version is just a root pointer. An update copies only the nodes on the
+// path it touches and shares every other subtree with the version it came from,
+// so insert and delete cost O(log n) time and space and old versions stay valid.
+// Rc::make_mut does the copying: a node still shared with another version is
+// cloned before it is changed, while a node only this version owns is changed
+// in place. Swapping Rc for Arc makes versions shareable across threads.
+
+// 1. Persistent AVL tree
+type AvlLink<T> = Option<Rc<AvlNode<T>>>;
+
+#[derive(Clone)]
+struct AvlNode<T> {
+    value: T,
+    left: AvlLink<T>,
+    right: AvlLink<T>,
+    height: i32,
+    size: usize,
+}
+
+#[derive(Clone)]
+pub struct PersistentAVL<T> {
+    root: AvlLink<T>,
+}
+
+impl<T: Ord + Clone> Default for PersistentAVL<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Ord + Clone> PersistentAVL<T> {
+    pub fn new() -> Self {
+        PersistentAVL { root: None }
+    }
+
+    pub fn len(&self) -> usize {
+        Self::size(&self.root)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    pub fn height(&self) -> i32 {
+        Self::height_of(&self.root)
+    }
+
+    pub fn contains(&self, value: &T) -> bool {
+        let mut current = &self.root;
+        while let Some(node) = current {
+            match value.cmp(&node.value) {
+                Ordering::Less => current = &node.left,
+                Ordering::Greater => current = &node.right,
+                Ordering::Equal => return true,
+            }
+        }
+        false
+    }
+
+    // Return a new version containing the value; this version is unchanged
+    pub fn insert(&self, value: T) -> Self {
+        if self.contains(&value) {
+            return self.clone();
+        }
+        PersistentAVL { root: Some(Self::insert_rec(self.root.clone(), value)) }
+    }
+
+    // Return a new version without the value; this version is unchanged
+    pub fn delete(&self, value: &T) -> Self {
+        if !self.contains(value) {
+            return self.clone();
+        }
+        PersistentAVL { root: Self::delete_rec(self.root.clone(), value) }
+    }
+
+    pub fn inorder(&self) -> Vec<&T> {
+        let mut result = Vec::with_capacity(self.len());
+        Self::inorder_rec(&self.root, &mut result);
+        result
+    }
+
+    fn inorder_rec<'a>(link: &'a AvlLink<T>, result: &mut Vec<&'a T>) {
+        if let Some(node) = link {
+            Self::inorder_rec(&node.left, result);
+            result.push(&node.value);
+            Self::inorder_rec(&node.right, result);
+        }
+    }
+
+    fn height_of(link: &AvlLink<T>) -> i32 {
+        link.as_ref().map_or(0, |n| n.height)
+    }
+
+    fn size(link: &AvlLink<T>) -> usize {
+        link.as_ref().map_or(0, |n| n.size)
+    }
+
+    fn update(node: &mut AvlNode<T>) {
+        node.height = 1 + Self::height_of(&node.left).max(Self::height_of(&node.right));
+        node.size = 1 + Self::size(&node.left) + Self::size(&node.right);
+    }
+
+    fn rotate_right(mut node: Rc<AvlNode<T>>) -> Rc<AvlNode<T>> {
+        let n = Rc::make_mut(&mut node);
+        let mut left = n.left.take().unwrap();
+        let l = Rc::make_mut(&mut left);
+        n.left = l.right.take();
+        Self::update(n);
+        l.right = Some(node);
+        Self::update(l);
+        left
+    }
+
+    fn rotate_left(mut node: Rc<AvlNode<T>>) -> Rc<AvlNode<T>> {
+        let n = Rc::make_mut(&mut node);
+        let mut right = n.right.take().unwrap();
+        let r = Rc::make_mut(&mut right);
+        n.right = r.left.take();
+        Self::update(n);
+        r.left = Some(node);
+        Self::update(r);
+        right
+    }
+
+    // The node has just been copied onto the update path, so make_mut is free here
+    fn rebalance(mut node: Rc<AvlNode<T>>) -> Rc<AvlNode<T>> {
+        let n = Rc::make_mut(&mut node);
+        Self::update(n);
+        let balance = Self::height_of(&n.left) - Self::height_of(&n.right);
+        if balance > 1 {
+            let left = n.left.as_ref().unwrap();
+            if Self::height_of(&left.left) < Self::height_of(&left.right) {
+                n.left = Some(Self::rotate_left(n.left.take().unwrap()));
+            }
+            return Self::rotate_right(node);
+        }
+        if balance < -1 {
+            let right = n.right.as_ref().unwrap();
+            if Self::height_of(&right.right) < Self::height_of(&right.left) {
+                n.right = Some(Self::rotate_right(n.right.take().unwrap()));
+            }
+            return Self::rotate_left(node);
+        }
+        node
+    }
+
+    // The value is known to be absent
+    fn insert_rec(link: AvlLink<T>, value: T) -> Rc<AvlNode<T>> {
+        let mut node = match link {
+            None => return Rc::new(AvlNode { value, left: None, right: None, height: 1, size: 1 }),
+            Some(node) => node,
+        };
+        let n = Rc::make_mut(&mut node);
+        if value < n.value {
+            n.left = Some(Self::insert_rec(n.left.take(), value));
+        } else {
+            n.right = Some(Self::insert_rec(n.right.take(), value));
+        }
+        Self::rebalance(node)
+    }
+
+    // The value is known to be present
+    fn delete_rec(link: AvlLink<T>, value: &T) -> AvlLink<T> {
+        let mut node = link.unwrap();
+        let n = Rc::make_mut(&mut node);
+        match value.cmp(&n.value) {
+            Ordering::Less => n.left = Self::delete_rec(n.left.take(), value),
+            Ordering::Greater => n.right = Self::delete_rec(n.right.take(), value),
+            Ordering::Equal => match (n.left.take(), n.right.take()) {
+                (None, right) => return right,
+                (left, None) => return left,
+                (left, Some(right)) => {
+                    let (successor, rest) = Self::remove_min(right);
+                    n.value = successor;
+                    n.left = left;
+                    n.right = rest;
+                }
+            },
+        }
+        Some(Self::rebalance(node))
+    }
+
+    fn remove_min(mut node: Rc<AvlNode<T>>) -> (T, AvlLink<T>) {
+        if node.left.is_none() {
+            let node = Rc::unwrap_or_clone(node);
+            return (node.value, node.right);
+        }
+        let n = Rc::make_mut(&mut node);
+        let (min, rest) = Self::remove_min(n.left.take().unwrap());
+        n.left = rest;
+        (min, Some(Self::rebalance(node)))
+    }
+
+    // Check BST order, stored heights and sizes, and the AVL balance condition
+    pub fn validate(&self) -> Result<(), String> {
+        Self::validate_rec(&self.root, None, None).map(|_| ())
+    }
+
+    fn validate_rec(link: &AvlLink<T>, lo: Option<&T>, hi: Option<&T>) -> Result<i32, String> {
+        let n = match link {
+            None => return Ok(0),
+            Some(n) => n,
+        };
+        if lo.is_some_and(|lo| n.value <= *lo) || hi.is_some_and(|hi| n.value >= *hi) {
+            return Err("values out of BST order".to_string());
+        }
+        let left = Self::validate_rec(&n.left, lo, Some(&n.value))?;
+        let right = Self::validate_rec(&n.right, Some(&n.value), hi)?;
+        if (left - right).abs() > 1 {
+            return Err(format!("unbalanced node: heights {} and {}", left, right));
+        }
+        if n.height != 1 + left.max(right) || n.size != 1 + Self::size(&n.left) + Self::size(&n.right) {
+            return Err("stale height or size".to_string());
+        }
+        Ok(n.height)
+    }
+
+    // Full copy sharing nothing, which is what snapshotting a Box-based tree costs
+    pub fn deep_clone(&self) -> Self {
+        fn copy<T: Clone>(link: &AvlLink<T>) -> AvlLink<T> {
+            link.as_ref().map(|n| {
+                Rc::new(AvlNode { value: n.value.clone(), left: copy(&n.left), right: copy(&n.right), height: n.height, size: n.size })
+            })
+        }
+        PersistentAVL { root: copy(&self.root) }
+    }
+
+    fn collect_nodes(&self, seen: &mut HashSet<*const AvlNode<T>>) {
+        let mut stack: Vec<&Rc<AvlNode<T>>> = self.root.iter().collect();
+        while let Some(node) = stack.pop() {
+            if seen.insert(Rc::as_ptr(node)) {
+                stack.extend(node.left.iter().chain(node.right.iter()));
+            }
+        }
+    }
+}
+
+// 2. Persistent left-leaning red-black tree, the same algorithms as RedBlackTree
+// with Rc links instead of Box
+#[derive(Clone, Copy, PartialEq)]
+enum Color {
+    Red,
+    Black,
+}
+
+impl Color {
+    fn flip(self) -> Self {
+        match self {
+            Color::Red => Color::Black,
+            Color::Black => Color::Red,
+        }
+    }
+}
+
+type RbLink<T> = Option<Rc<RbNode<T>>>;
+
+#[derive(Clone)]
+struct RbNode<T> {
+    value: T,
+    color: Color,
+    left: RbLink<T>,
+    right: RbLink<T>,
+    size: usize,
+}
+
+#[derive(Clone)]
+pub struct PersistentRedBlackTree<T> {
+    root: RbLink<T>,
+}
+
+impl<T: Ord + Clone> Default for PersistentRedBlackTree<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Ord + Clone> PersistentRedBlackTree<T> {
+    pub fn new() -> Self {
+        PersistentRedBlackTree { root: None }
+    }
+
+    pub fn len(&self) -> usize {
+        Self::size(&self.root)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    pub fn contains(&self, value: &T) -> bool {
+        let mut current = &self.root;
+        while let Some(node) = current {
+            match value.cmp(&node.value) {
+                Ordering::Less => current = &node.left,
+                Ordering::Greater => current = &node.right,
+                Ordering::Equal => return true,
+            }
+        }
+        false
+    }
+
+    // Return a new version containing the value; this version is unchanged
+    pub fn insert(&self, value: T) -> Self {
+        if self.contains(&value) {
+            return self.clone();
+        }
+        let mut root = Self::insert_rec(self.root.clone(), value);
+        Rc::make_mut(&mut root).color = Color::Black;
+        PersistentRedBlackTree { root: Some(root) }
+    }
+
+    // Return a new version without the value; this version is unchanged
+    pub fn delete(&self, value: &T) -> Self {
+        if !self.contains(value) {
+            return self.clone();
+        }
+        let mut root = self.root.clone().unwrap();
+        if !Self::is_red(&root.left) && !Self::is_red(&root.right) {
+            Rc::make_mut(&mut root).color = Color::Red;
+        }
+        let mut root = Self::delete_rec(root, value);
+        if let Some(root) = root.as_mut() {
+            Rc::make_mut(root).color = Color::Black;
+        }
+        PersistentRedBlackTree { root }
+    }
+
+    pub fn inorder(&self) -> Vec<&T> {
+        let mut result = Vec::with_capacity(self.len());
+        Self::inorder_rec(&self.root, &mut result);
+        result
+    }
+
+    fn inorder_rec<'a>(link: &'a RbLink<T>, result: &mut Vec<&'a T>) {
+        if let Some(node) = link {
+            Self::inorder_rec(&node.left, result);
+            result.push(&node.value);
+            Self::inorder_rec(&node.right, result);
+        }
+    }
+
+    fn size(link: &RbLink<T>) -> usize {
+        link.as_ref().map_or(0, |n| n.size)
+    }
+
+    fn is_red(link: &RbLink<T>) -> bool {
+        link.as_ref().is_some_and(|n| n.color == Color::Red)
+    }
+
+    fn is_red_left_child(link: &RbLink<T>) -> bool {
+        link.as_ref().is_some_and(|n| Self::is_red(&n.left))
+    }
+
+    fn update_size(node: &mut RbNode<T>) {
+        node.size = 1 + Self::size(&node.left) + Self::size(&node.right);
+    }
+
+    fn rotate_left(mut node: Rc<RbNode<T>>) -> Rc<RbNode<T>> {
+        let n = Rc::make_mut(&mut node);
+        let mut right = n.right.take().unwrap();
+        let r = Rc::make_mut(&mut right);
+        n.right = r.left.take();
+        r.color = n.color;
+        n.color = Color::Red;
+        Self::update_size(n);
+        r.left = Some(node);
+        Self::update_size(r);
+        right
+    }
+
+    fn rotate_right(mut node: Rc<RbNode<T>>) -> Rc<RbNode<T>> {
+        let n = Rc::make_mut(&mut node);
+        let mut left = n.left.take().unwrap();
+        let l = Rc::make_mut(&mut left);
+        n.left = l.right.take();
+        l.color = n.color;
+        n.color = Color::Red;
+        Self::update_size(n);
+        l.right = Some(node);
+        Self::update_size(l);
+        left
+    }
+
+    fn flip_colors(node: &mut RbNode<T>) {
+        node.color = node.color.flip();
+        for child in [&mut node.left, &mut node.right].into_iter().flatten() {
+            let child = Rc::make_mut(child);
+            child.color = child.color.flip();
+        }
+    }
+
+    fn balance(mut node: Rc<RbNode<T>>) -> Rc<RbNode<T>> {
+        if Self::is_red(&node.right) && !Self::is_red(&node.left) {
+            node = Self::rotate_left(node);
+        }
+        if Self::is_red(&node.left) && Self::is_red_left_child(&node.left) {
+            node = Self::rotate_right(node);
+        }
+        let n = Rc::make_mut(&mut node);
+        if Self::is_red(&n.left) && Self::is_red(&n.right) {
+            Self::flip_colors(n);
+        }
+        Self::update_size(n);
+        node
+    }
+
+    fn move_red_left(mut node: Rc<RbNode<T>>) -> Rc<RbNode<T>> {
+        let n = Rc::make_mut(&mut node);
+        Self::flip_colors(n);
+        if Self::is_red_left_child(&n.right) {
+            n.right = Some(Self::rotate_right(n.right.take().unwrap()));
+            node = Self::rotate_left(node);
+            Self::flip_colors(Rc::make_mut(&mut node));
+        }
+        node
+    }
+
+    fn move_red_right(mut node: Rc<RbNode<T>>) -> Rc<RbNode<T>> {
+        let n = Rc::make_mut(&mut node);
+        Self::flip_colors(n);
+        if Self::is_red_left_child(&n.left) {
+            node = Self::rotate_right(node);
+            Self::flip_colors(Rc::make_mut(&mut node));
+        }
+        node
+    }
+
+    // The value is known to be absent
+    fn insert_rec(link: RbLink<T>, value: T) -> Rc<RbNode<T>> {
+        let mut node = match link {
+            None => return Rc::new(RbNode { value, color: Color::Red, left: None, right: None, size: 1 }),
+            Some(node) => node,
+        };
+        let n = Rc::make_mut(&mut node);
+        if value < n.value {
+            n.left = Some(Self::insert_rec(n.left.take(), value));
+        } else {
+            n.right = Some(Self::insert_rec(n.right.take(), value));
+        }
+        Self::balance(node)
+    }
+
+    fn delete_min_rec(mut node: Rc<RbNode<T>>) -> (RbLink<T>, T) {
+        if node.left.is_none() {
+            return (None, Rc::unwrap_or_clone(node).value);
+        }
+        if !Self::is_red(&node.left) && !Self::is_red_left_child(&node.left) {
+            node = Self::move_red_left(node);
+        }
+        let n = Rc::make_mut(&mut node);
+        let (left, min) = Self::delete_min_rec(n.left.take().unwrap());
+        n.left = left;
+        (Some(Self::balance(node)), min)
+    }
+
+    // The value is known to be in this subtree
+    fn delete_rec(mut node: Rc<RbNode<T>>, value: &T) -> RbLink<T> {
+        if *value < node.value {
+            if !Self::is_red(&node.left) && !Self::is_red_left_child(&node.left) {
+                node = Self::move_red_left(node);
+            }
+            let n = Rc::make_mut(&mut node);
+            n.left = Self::delete_rec(n.left.take().unwrap(), value);
+        } else {
+            if Self::is_red(&node.left) {
+                node = Self::rotate_right(node);
+            }
+            if *value == node.value && node.right.is_none() {
+                return None;
+            }
+            if !Self::is_red(&node.right) && !Self::is_red_left_child(&node.right) {
+                node = Self::move_red_right(node);
+            }
+            let n = Rc::make_mut(&mut node);
+            if *value == n.value {
+                let (right, successor) = Self::delete_min_rec(n.right.take().unwrap());
+                n.right = right;
+                n.value = successor;
+            } else {
+                n.right = Self::delete_rec(n.right.take().unwrap(), value);
+            }
+        }
+        Some(Self::balance(node))
+    }
+
+    // Check the black root, left-leaning red links, no two reds in a row, equal
+    // black height on every path, BST order and subtree sizes
+    pub fn validate(&self) -> Result<(), String> {
+        if Self::is_red(&self.root) {
+            return Err("root is red".to_string());
+        }
+        Self::validate_rec(&self.root, None, None).map(|_| ())
+    }
+
+    fn validate_rec(link: &RbLink<T>, lo: Option<&T>, hi: Option<&T>) -> Result<usize, String> {
+        let n = match link {
+            None => return Ok(1),
+            Some(n) => n,
+        };
+        if lo.is_some_and(|lo| n.value <= *lo) || hi.is_some_and(|hi| n.value >= *hi) {
+            return Err("values out of BST order".to_string());
+        }
+        if Self::is_red(&n.right) {
+            return Err("red right link".to_string());
+        }
+        if n.color == Color::Red && Self::is_red(&n.left) {
+            return Err("two red links in a row".to_string());
+        }
+        if n.size != 1 + Self::size(&n.left) + Self::size(&n.right) {
+            return Err("stale subtree size".to_string());
+        }
+        let left = Self::validate_rec(&n.left, lo, Some(&n.value))?;
+        let right = Self::validate_rec(&n.right, Some(&n.value), hi)?;
+        if left != right {
+            return Err(format!("black height mismatch: {} on the left, {} on the right", left, right));
+        }
+        Ok(left + usize::from(n.color == Color::Black))
+    }
+
+    // Full copy sharing nothing, which is what snapshotting a Box-based tree costs
+    pub fn deep_clone(&self) -> Self {
+        fn copy<T: Clone>(link: &RbLink<T>) -> RbLink<T> {
+            link.as_ref().map(|n| {
+                Rc::new(RbNode { value: n.value.clone(), color: n.color, left: copy(&n.left), right: copy(&n.right), size: n.size })
+            })
+        }
+        PersistentRedBlackTree { root: copy(&self.root) }
+    }
+
+    fn collect_nodes(&self, seen: &mut HashSet<*const RbNode<T>>) {
+        let mut stack: Vec<&Rc<RbNode<T>>> = self.root.iter().collect();
+        while let Some(node) = stack.pop() {
+            if seen.insert(Rc::as_ptr(node)) {
+                stack.extend(node.left.iter().chain(node.right.iter()));
+            }
+        }
+    }
+}
+
+// 3. Checks and benchmark
+
+// Small xorshift generator for the randomized checks below
+struct XorShift(u64);
+
+impl XorShift {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+}
+
+// Apply random inserts and deletes, keeping every version alongside a BTreeSet
+// snapshot, then check that no later update disturbed any earlier version
+fn history_check(operations: usize, seed: u64) {
+    let mut rng = XorShift(seed);
+    let mut avl_versions = vec![PersistentAVL::new()];
+    let mut rb_versions = vec![PersistentRedBlackTree::new()];
+    let mut models = vec![BTreeSet::new()];
+
+    for _ in 0..operations {
+        // Branch from a random earlier version now and then, like undo followed by an edit
+        let base = if rng.next().is_multiple_of(10) { (rng.next() % models.len() as u64) as usize } else { models.len() - 1 };
+        let value = rng.next() % 300;
+        let mut model = models[base].clone();
+        if rng.next().is_multiple_of(3) {
+            avl_versions.push(avl_versions[base].delete(&value));
+            rb_versions.push(rb_versions[base].delete(&value));
+            model.remove(&value);
+        } else {
+            avl_versions.push(avl_versions[base].insert(value));
+            rb_versions.push(rb_versions[base].insert(value));
+            model.insert(value);
+        }
+        models.push(model);
+    }
+
+    for ((avl, rb), model) in avl_versions.iter().zip(&rb_versions).zip(&models) {
+        if let Err(e) = avl.validate() {
+            panic!("AVL invariant broken: {}", e);
+        }
+        if let Err(e) = rb.validate() {
+            panic!("red-black invariant broken: {}", e);
+        }
+        assert!(avl.inorder().into_iter().eq(model.iter()));
+        assert!(rb.inorder().into_iter().eq(model.iter()));
+        assert_eq!(avl.len(), model.len());
+        assert_eq!(rb.len(), model.len());
+    }
+}
+
+// Each Rc allocation holds the node plus its strong and weak counts
+fn node_bytes<N>() -> usize {
+    mem::size_of::<N>() + 2 * mem::size_of::<usize>()
+}
+
+fn benchmark(initial: u64, edits: usize) {
+    let mut rng = XorShift(0x9E3779B97F4A7C15);
+    let mut avl = PersistentAVL::new();
+    let mut rb = PersistentRedBlackTree::new();
+    for _ in 0..initial {
+        let value = rng.next();
+        avl = avl.insert(value);
+        rb = rb.insert(value);
+    }
+    let edit_values: Vec<u64> = (0..edits).map(|_| rng.next()).collect();
+
+    println!("{} undo snapshots of a {}-element tree, one insert between snapshots:", edits, initial);
+    println!("{:<12} {:<14} {:>12} {:>14} {:>10}", "tree", "snapshots", "nodes", "bytes", "time");
+
+    // Persistent versions: each snapshot is the version itself
+    let start = Instant::now();
+    let mut versions = vec![avl.clone()];
+    for &value in &edit_values {
+        let next = versions.last().unwrap().insert(value);
+        versions.push(next);
+    }
+    let elapsed = start.elapsed();
+    let mut seen = HashSet::new();
+    versions.iter().for_each(|v| v.collect_nodes(&mut seen));
+    let bytes = seen.len() * node_bytes::<AvlNode<u64>>();
+    println!("{:<12} {:<14} {:>12} {:>14} {:>10.2?}", "AVL", "persistent", seen.len(), bytes, elapsed);
+
+    // Cloned snapshots: each snapshot is a full copy
+    let start = Instant::now();
+    let mut current = avl.deep_clone();
+    let mut snapshots = vec![current.deep_clone()];
+    for &value in &edit_values {
+        current = current.insert(value);
+        snapshots.push(current.deep_clone());
+    }
+    let elapsed = start.elapsed();
+    let nodes: usize = snapshots.iter().map(|s| s.len()).sum();
+    let cloned_bytes = nodes * node_bytes::<AvlNode<u64>>();
+    println!("{:<12} {:<14} {:>12} {:>14} {:>10.2?}", "AVL", "cloned", nodes, cloned_bytes, elapsed);
+    println!("  persistent versions use {:.1}x less memory", cloned_bytes as f64 / bytes as f64);
+
+    let start = Instant::now();
+    let mut versions = vec![rb.clone()];
+    for &value in &edit_values {
+        let next = versions.last().unwrap().insert(value);
+        versions.push(next);
+    }
+    let elapsed = start.elapsed();
+    let mut seen = HashSet::new();
+    versions.iter().for_each(|v| v.collect_nodes(&mut seen));
+    let bytes = seen.len() * node_bytes::<RbNode<u64>>();
+    println!("{:<12} {:<14} {:>12} {:>14} {:>10.2?}", "Red-Black", "persistent", seen.len(), bytes, elapsed);
+
+    let start = Instant::now();
+    let mut current = rb.deep_clone();
+    let mut snapshots = vec![current.deep_clone()];
+    for &value in &edit_values {
+        current = current.insert(value);
+        snapshots.push(current.deep_clone());
+    }
+    let elapsed = start.elapsed();
+    let nodes: usize = snapshots.iter().map(|s| s.len()).sum();
+    let cloned_bytes = nodes * node_bytes::<RbNode<u64>>();
+    println!("{:<12} {:<14} {:>12} {:>14} {:>10.2?}", "Red-Black", "cloned", nodes, cloned_bytes, elapsed);
+    println!("  persistent versions use {:.1}x less memory", cloned_bytes as f64 / bytes as f64);
+}
+
+fn main() {
+    // Old versions stay queryable after new ones are derived from them
+    let v1 = PersistentAVL::new().insert(10).insert(20).insert(30).insert(40);
+    let v2 = v1.insert(25);
+    let v3 = v2.delete(&10);
+    println!("AVL v1: {:?}", v1.inorder());
+    println!("AVL v2: {:?}", v2.inorder());
+    println!("AVL v3: {:?} (height {})", v3.inorder(), v3.height());
+    println!("v1 contains 25: {}, v2 contains 25: {}", v1.contains(&25), v2.contains(&25));
+
+    let r1: PersistentRedBlackTree<&str> = ["pear", "apple", "fig", "kiwi"].into_iter().fold(PersistentRedBlackTree::new(), |t, v| t.insert(v));
+    let r2 = r1.delete(&"fig").insert("lime");
+    println!("Red-black r1: {:?}, r2: {:?}", r1.inorder(), r2.inorder());
+
+    // An update copies only its path; everything else is shared
+    let big = (0..1000).fold(PersistentAVL::new(), |t, v| t.insert(v));
+    let next = big.insert(5000);
+    let mut seen = HashSet::new();
+    big.collect_nodes(&mut seen);
+    let before = seen.len();
+    next.collect_nodes(&mut seen);
+    println!("Inserting into a 1000-node AVL tree allocated {} new nodes (height {})", seen.len() - before, next.height());
+
+    history_check(5000, 0x2545F4914F6CDD1D);
+    println!("History check passed: all 5001 versions of both trees match their snapshots");
+
+    benchmark(10_000, 1000);
+}
@@ -1,5 +1,12 @@
 use std::cmp::Ordering;
-use std::mem;
+use std::collections::{BTreeSet, HashMap};
+use std::fmt::Debug;
+
+#[path = "rust-tree-printer.rs"]
+#[allow(dead_code)]
+mod tree_printer;
+
+use tree_printer::TreeShape;
 
 #[derive(Clone, Copy, PartialEq)]
 enum Color {
@@ -7,11 +14,21 @@ enum Color {
     Black,
 }
 
+impl Color {
+    fn flip(self) -> Self {
+        match self {
+            Color::Red => Color::Black,
+            Color::Black => Color::Red,
+        }
+    }
+}
+
 struct Node<T: Ord> {
     value: T,
     color: Color,
     left: Option<Box<Node<T>>>,
     right: Option<Box<Node<T>>>,
+    size: usize,
 }
 
 impl<T: Ord> Node<T> {
@@ -21,6 +38,28 @@ impl<T: Ord> Node<T> {
             color: Color::Red,
             left: None,
             right: None,
+            size: 1,
+        }
+    }
+}
+
+impl<T: Ord> TreeShape for Node<T> {
+    fn children(&self) -> Vec<Option<&Self>> {
+        vec![self.left.as_deref(), self.right.as_deref()]
+    }
+
+    // The color of a node is the color of the link from its parent
+    fn annotation(&self) -> String {
+        match self.color {
+            Color::Red => "red".to_string(),
+            Color::Black => "black".to_string(),
+        }
+    }
+
+    fn dot_attributes(&self) -> String {
+        match self.color {
+            Color::Red => "color=red, fontcolor=red".to_string(),
+            Color::Black => String::new(),
         }
     }
 }
@@ -29,13 +68,19 @@ pub struct RedBlackTree<T: Ord> {
     root: Option<Box<Node<T>>>,
 }
 
+impl<T: Ord> Default for RedBlackTree<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl<T: Ord> RedBlackTree<T> {
     pub fn new() -> Self {
         RedBlackTree { root: None }
     }
 
     pub fn insert(&mut self, value: T) {
-        let mut root = mem::replace(&mut self.root, None);
+        let mut root = self.root.take();
         root = Self::insert_rec(root, value);
         if let Some(ref mut node) = root {
             node.color = Color::Black;
@@ -43,18 +88,18 @@ impl<T: Ord> RedBlackTree<T> {
         self.root = root;
     }
 
-    fn insert_rec(mut node: Option<Box<Node<T>>>, value: T) -> Option<Box<Node<T>>> {
+    fn insert_rec(node: Option<Box<Node<T>>>, value: T) -> Option<Box<Node<T>>> {
         match node {
             None => Some(Box::new(Node::new(value))),
             Some(mut n) => {
                 match value.cmp(&n.value) {
                     Ordering::Less => {
                         n.left = Self::insert_rec(n.left.take(), value);
-                        Self::balance_after_insert(n)
+                        Self::balance(n)
                     }
                     Ordering::Greater => {
                         n.right = Self::insert_rec(n.right.take(), value);
-                        Self::balance_after_insert(n)
+                        Self::balance(n)
                     }
                     Ordering::Equal => Some(n),
                 }
@@ -62,7 +107,9 @@ impl<T: Ord> RedBlackTree<T> {
         }
     }
 
-    fn balance_after_insert(mut node: Box<Node<T>>) -> Option<Box<Node<T>>> {
+    // Restore the left-leaning invariants on the way back up after an insert or delete
+    fn balance(mut node: Box<Node<T>>) -> Option<Box<Node<T>>> {
+        Self::update_size(&mut node);
         if Self::is_red(&node.right) && !Self::is_red(&node.left) {
             node = Self::rotate_left(node);
         }
@@ -76,37 +123,232 @@ impl<T: Ord> RedBlackTree<T> {
     }
 
     fn is_red(node: &Option<Box<Node<T>>>) -> bool {
-        node.as_ref().map_or(false, |n| n.color == Color::Red)
+        node.as_ref().is_some_and(|n| n.color == Color::Red)
+    }
+
+    fn size(node: &Option<Box<Node<T>>>) -> usize {
+        node.as_ref().map_or(0, |n| n.size)
+    }
+
+    // Recompute a node's subtree size from its children
+    fn update_size(node: &mut Node<T>) {
+        node.size = 1 + Self::size(&node.left) + Self::size(&node.right);
     }
 
     fn rotate_left(mut node: Box<Node<T>>) -> Box<Node<T>> {
+        let before = tree_printer::before_rotation(&*node);
         let mut new_root = node.right.take().unwrap();
         node.right = new_root.left.take();
+        Self::update_size(&mut node);
         new_root.left = Some(node);
+        Self::update_size(&mut new_root);
         new_root.color = new_root.left.as_ref().unwrap().color;
         new_root.left.as_mut().unwrap().color = Color::Red;
+        tree_printer::after_rotation("rotate_left", before, &*new_root);
         new_root
     }
 
     fn rotate_right(mut node: Box<Node<T>>) -> Box<Node<T>> {
+        let before = tree_printer::before_rotation(&*node);
         let mut new_root = node.left.take().unwrap();
         node.left = new_root.right.take();
+        Self::update_size(&mut node);
         new_root.right = Some(node);
+        Self::update_size(&mut new_root);
         new_root.color = new_root.right.as_ref().unwrap().color;
         new_root.right.as_mut().unwrap().color = Color::Red;
+        tree_printer::after_rotation("rotate_right", before, &*new_root);
         new_root
     }
 
-    fn flip_colors(node: &mut Box<Node<T>>) {
-        node.color = Color::Red;
+    // Toggle the colors of a node and its children: splits a temporary 4-node on
+    // insert, and merges children into a 4-node on the way down a delete
+    fn flip_colors(node: &mut Node<T>) {
+        node.color = node.color.flip();
         if let Some(ref mut left) = node.left {
-            left.color = Color::Black;
+            left.color = left.color.flip();
         }
         if let Some(ref mut right) = node.right {
-            right.color = Color::Black;
+            right.color = right.color.flip();
+        }
+    }
+
+    fn is_red_left_child(node: &Option<Box<Node<T>>>) -> bool {
+        node.as_ref().is_some_and(|n| Self::is_red(&n.left))
+    }
+
+    // Make node.left or one of its children red, assuming node is red and both
+    // node.left and node.left.left are black
+    fn move_red_left(mut node: Box<Node<T>>) -> Box<Node<T>> {
+        Self::flip_colors(&mut node);
+        if Self::is_red_left_child(&node.right) {
+            node.right = Some(Self::rotate_right(node.right.take().unwrap()));
+            node = Self::rotate_left(node);
+            Self::flip_colors(&mut node);
+        }
+        node
+    }
+
+    // Make node.right or one of its children red, assuming node is red and both
+    // node.right and node.right.left are black
+    fn move_red_right(mut node: Box<Node<T>>) -> Box<Node<T>> {
+        Self::flip_colors(&mut node);
+        if Self::is_red_left_child(&node.left) {
+            node = Self::rotate_right(node);
+            Self::flip_colors(&mut node);
+        }
+        node
+    }
+
+    // Temporarily color the root red when both children are black, so the
+    // top-down delete always has a red link to borrow from
+    fn prepare_root_for_delete(&mut self) {
+        if let Some(root) = self.root.as_mut() {
+            if !Self::is_red(&root.left) && !Self::is_red(&root.right) {
+                root.color = Color::Red;
+            }
+        }
+    }
+
+    fn finish_delete(&mut self) {
+        if let Some(root) = self.root.as_mut() {
+            root.color = Color::Black;
         }
     }
 
+    // Remove and return the smallest value
+    pub fn delete_min(&mut self) -> Option<T> {
+        self.root.as_ref()?;
+        self.prepare_root_for_delete();
+        let (root, min) = Self::delete_min_rec(self.root.take().unwrap());
+        self.root = root;
+        self.finish_delete();
+        Some(min.value)
+    }
+
+    // Returns the rest of the subtree and the detached minimum node
+    fn delete_min_rec(mut node: Box<Node<T>>) -> (Option<Box<Node<T>>>, Box<Node<T>>) {
+        if node.left.is_none() {
+            return (None, node);
+        }
+        if !Self::is_red(&node.left) && !Self::is_red_left_child(&node.left) {
+            node = Self::move_red_left(node);
+        }
+        let (left, min) = Self::delete_min_rec(node.left.take().unwrap());
+        node.left = left;
+        (Self::balance(node), min)
+    }
+
+    // Remove and return the largest value
+    pub fn delete_max(&mut self) -> Option<T> {
+        self.root.as_ref()?;
+        self.prepare_root_for_delete();
+        let (root, max) = Self::delete_max_rec(self.root.take().unwrap());
+        self.root = root;
+        self.finish_delete();
+        Some(max)
+    }
+
+    fn delete_max_rec(mut node: Box<Node<T>>) -> (Option<Box<Node<T>>>, T) {
+        if Self::is_red(&node.left) {
+            node = Self::rotate_right(node);
+        }
+        if node.right.is_none() {
+            return (None, node.value);
+        }
+        if !Self::is_red(&node.right) && !Self::is_red_left_child(&node.right) {
+            node = Self::move_red_right(node);
+        }
+        let (right, max) = Self::delete_max_rec(node.right.take().unwrap());
+        node.right = right;
+        (Self::balance(node), max)
+    }
+
+    // Remove a value, returning whether it was present
+    pub fn delete(&mut self, value: &T) -> bool {
+        if !self.contains(value) {
+            return false;
+        }
+        self.prepare_root_for_delete();
+        self.root = Self::delete_rec(self.root.take().unwrap(), value);
+        self.finish_delete();
+        true
+    }
+
+    // The value is known to be in this subtree
+    fn delete_rec(mut node: Box<Node<T>>, value: &T) -> Option<Box<Node<T>>> {
+        if *value < node.value {
+            if !Self::is_red(&node.left) && !Self::is_red_left_child(&node.left) {
+                node = Self::move_red_left(node);
+            }
+            node.left = Self::delete_rec(node.left.take().unwrap(), value);
+        } else {
+            if Self::is_red(&node.left) {
+                node = Self::rotate_right(node);
+            }
+            if *value == node.value && node.right.is_none() {
+                return None;
+            }
+            if !Self::is_red(&node.right) && !Self::is_red_left_child(&node.right) {
+                node = Self::move_red_right(node);
+            }
+            if *value == node.value {
+                // The in-order successor node, removed from the right subtree, takes this node's place
+                let (right, mut successor) = Self::delete_min_rec(node.right.take().unwrap());
+                successor.left = node.left.take();
+                successor.right = right;
+                successor.color = node.color;
+                node = successor;
+            } else {
+                node.right = Self::delete_rec(node.right.take().unwrap(), value);
+            }
+        }
+        Self::balance(node)
+    }
+
+    // Check every red-black invariant: black root, no red right links, no two reds
+    // in a row, equal black height on every path, BST order and subtree sizes
+    pub fn validate(&self) -> Result<(), String> {
+        if Self::is_red(&self.root) {
+            return Err("root is red".to_string());
+        }
+        Self::validate_rec(&self.root, None, None).map(|_| ())
+    }
+
+    // Returns the black height of the subtree
+    fn validate_rec(node: &Option<Box<Node<T>>>, lo: Option<&T>, hi: Option<&T>) -> Result<usize, String> {
+        let n = match node {
+            None => return Ok(1),
+            Some(n) => n,
+        };
+        if lo.is_some_and(|lo| n.value <= *lo) || hi.is_some_and(|hi| n.value >= *hi) {
+            return Err("values out of BST order".to_string());
+        }
+        if Self::is_red(&n.right) {
+            return Err("red right link".to_string());
+        }
+        if n.color == Color::Red && Self::is_red(&n.left) {
+            return Err("two red links in a row".to_string());
+        }
+        if n.size != 1 + Self::size(&n.left) + Self::size(&n.right) {
+            return Err("stale subtree size".to_string());
+        }
+        let left = Self::validate_rec(&n.left, lo, Some(&n.value))?;
+        let right = Self::validate_rec(&n.right, Some(&n.value), hi)?;
+        if left != right {
+            return Err(format!("black height mismatch: {} on the left, {} on the right", left, right));
+        }
+        Ok(left + usize::from(n.color == Color::Black))
+    }
+
+    pub fn len(&self) -> usize {
+        Self::size(&self.root)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
     pub fn contains(&self, value: &T) -> bool {
         let mut current = &self.root;
         while let Some(node) = current {
@@ -119,22 +361,131 @@ impl<T: Ord> RedBlackTree<T> {
         false
     }
 
+    // Number of stored values smaller than `value`
+    pub fn rank(&self, value: &T) -> usize {
+        self.count_before(value, false)
+    }
+
+    // Number of stored values less than `value`, or less than or equal to it when `inclusive`
+    fn count_before(&self, value: &T, inclusive: bool) -> usize {
+        let mut count = 0;
+        let mut current = &self.root;
+        while let Some(node) = current {
+            let go_right = match value.cmp(&node.value) {
+                Ordering::Less => false,
+                Ordering::Equal => inclusive,
+                Ordering::Greater => true,
+            };
+            if go_right {
+                count += Self::size(&node.left) + 1;
+                current = &node.right;
+            } else {
+                current = &node.left;
+            }
+        }
+        count
+    }
+
+    // The k-th smallest value, counting from 0
+    pub fn select(&self, mut k: usize) -> Option<&T> {
+        let mut current = &self.root;
+        while let Some(node) = current {
+            let left_size = Self::size(&node.left);
+            match k.cmp(&left_size) {
+                Ordering::Less => current = &node.left,
+                Ordering::Equal => return Some(&node.value),
+                Ordering::Greater => {
+                    k -= left_size + 1;
+                    current = &node.right;
+                }
+            }
+        }
+        None
+    }
+
+    // Number of stored values in lo..=hi
+    pub fn count_range(&self, lo: &T, hi: &T) -> usize {
+        self.count_before(hi, true).saturating_sub(self.count_before(lo, false))
+    }
+
+    pub fn pretty(&self) -> String
+    where
+        T: Debug,
+    {
+        tree_printer::ascii(self.root.as_deref(), |n| format!("{:?}", n.value))
+    }
+
+    pub fn to_dot(&self) -> String
+    where
+        T: Debug,
+    {
+        tree_printer::dot(self.root.as_deref(), |n| format!("{:?}", n.value))
+    }
+
+    // Run `operation` and return its result with the subtree before and after each rotation it made
+    pub fn record_rotations<R>(&mut self, operation: impl FnOnce(&mut Self) -> R) -> (R, Vec<String>)
+    where
+        T: Debug,
+    {
+        let key = |n: &Node<T>| format!("{:?}", n.value);
+        let mut keys = HashMap::new();
+        tree_printer::collect_keys(self.root.as_deref(), &key, &mut keys);
+        let (result, rotations) = tree_printer::record(|| operation(self));
+        tree_printer::collect_keys(self.root.as_deref(), &key, &mut keys);
+        (result, tree_printer::describe(&rotations, &keys))
+    }
+
     pub fn inorder_traversal(&self) -> Vec<&T> {
         let mut result = Vec::new();
         Self::inorder_rec(&self.root, &mut result);
         result
     }
 
-    fn inorder_rec(node: &Option<Box<Node<T>>>, result: &mut Vec<&T>) {
+    fn inorder_rec<'a>(node: &'a Option<Box<Node<T>>>, result: &mut Vec<&'a T>) {
         if let Some(n) = node {
             Self::inorder_rec(&n.left, result);
             result.push(&n.value);
             Self::inorder_rec(&n.right, result);
         }
     }
+}
+
+// Small xorshift generator for the randomized checks below
+struct XorShift(u64);
+
+impl XorShift {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+}
+
+// Replays random inserts and deletes against a BTreeSet, validating after every operation
+fn randomized_check(operations: usize, seed: u64) {
+    let mut rng = XorShift(seed);
+    let mut tree = RedBlackTree::new();
+    let mut model = BTreeSet::new();
 
-    // Note: Deletion for Red-Black Trees is complex and omitted for brevity
-    // A full implementation would include deletion and rebalancing after deletion
+    for _ in 0..operations {
+        let value = rng.next() % 256;
+        match rng.next() % 6 {
+            0..=2 => {
+                tree.insert(value);
+                model.insert(value);
+            }
+            3 => assert_eq!(tree.delete(&value), model.remove(&value)),
+            4 => assert_eq!(tree.delete_min(), model.pop_first()),
+            _ => assert_eq!(tree.delete_max(), model.pop_last()),
+        }
+        if let Err(e) = tree.validate() {
+            panic!("invariant broken: {}", e);
+        }
+        assert_eq!(tree.contains(&value), model.contains(&value));
+        assert_eq!(tree.rank(&value), model.range(..value).count());
+    }
+    assert!(tree.inorder_traversal().into_iter().eq(model.iter()));
 }
 
 fn main() {
@@ -151,4 +502,28 @@ fn main() {
 
     // Print inorder traversal
     println!("Inorder traversal: {:?}", tree.inorder_traversal());
+
+    // Order statistics
+    println!("Rank of 11: {}", tree.rank(&11));
+    println!("4th smallest: {:?}", tree.select(3));
+    println!("Values in 5..=20: {}", tree.count_range(&5, &20));
+
+    // Deletion
+    tree.delete(&10);
+    println!("After deleting 10: {:?}", tree.inorder_traversal());
+    println!("Deleted min: {:?}, max: {:?}", tree.delete_min(), tree.delete_max());
+    println!("Inorder traversal: {:?}", tree.inorder_traversal());
+    println!("Valid: {:?}", tree.validate());
+
+    // Debugging output: the tree with link colors, and the rotations behind one delete
+    print!("\nTree:\n{}", tree.pretty());
+    let (deleted, steps) = tree.record_rotations(|tree| tree.delete(&7));
+    println!("\nDeleting 7 ({}) ran {} rotations:", deleted, steps.len());
+    for step in steps {
+        print!("{}", step);
+    }
+    print!("\nGraphviz:\n{}", tree.to_dot());
+
+    randomized_check(20_000, 0x853C49E6748FEA9B);
+    println!("Randomized check passed: invariants held after 20000 operations");
 }
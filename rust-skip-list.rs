@@ -0,0 +1,425 @@
+use std::collections::BTreeMap;
+use std::ops::{Bound, RangeBounds};
+
+// Skip List: a sorted linked list with extra "express lanes". Each node appears
+// on level 0 and, with probability 1/2 per level, on the levels above, so
+// search, insert and delete take O(log n) expected time.
+//
+// Nodes live in a Vec arena and link to each other by index, which sidesteps
+// the shared ownership a pointer-based skip list needs. Slot 0 is the head.
+//
+// Every link also records its width: how many level-0 steps it skips. Summing
+// widths along a search path gives a node's position, which makes indexed
+// access and rank queries O(log n) as well.
+
+const MAX_LEVEL: usize = 32;
+const NIL: usize = usize::MAX;
+const HEAD: usize = 0;
+
+#[derive(Clone, Copy)]
+struct Link {
+    next: usize,
+    width: usize, // position of `next` minus position of this node; NIL sits at len + 1
+}
+
+struct Node<K, V> {
+    entry: Option<(K, V)>, // None for the head and for free slots
+    forward: Vec<Link>,
+}
+
+// Small xorshift generator for node levels and the randomized check below
+struct XorShift(u64);
+
+impl XorShift {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+}
+
+pub struct SkipMap<K: Ord, V> {
+    nodes: Vec<Node<K, V>>,
+    free: Vec<usize>,
+    level: usize, // number of levels currently in use
+    len: usize,
+    rng: XorShift,
+}
+
+impl<K: Ord, V> Default for SkipMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Ord, V> SkipMap<K, V> {
+    pub fn new() -> Self {
+        SkipMap {
+            nodes: vec![Node { entry: None, forward: vec![Link { next: NIL, width: 1 }; MAX_LEVEL] }],
+            free: Vec::new(),
+            level: 1,
+            len: 0,
+            rng: XorShift(0x9E3779B97F4A7C15),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    // Each extra level is kept with probability 1/2: count the trailing ones of a random word
+    fn random_level(&mut self) -> usize {
+        (self.rng.next().trailing_ones() as usize + 1).min(MAX_LEVEL)
+    }
+
+    fn key(&self, node: usize) -> &K {
+        &self.nodes[node].entry.as_ref().unwrap().0
+    }
+
+    // For each level, the last node whose key is below `key` and its position
+    // (the head is position 0, the first entry position 1)
+    fn find_predecessors(&self, key: &K) -> ([usize; MAX_LEVEL], [usize; MAX_LEVEL]) {
+        let mut update = [HEAD; MAX_LEVEL];
+        let mut position = [0; MAX_LEVEL];
+        let mut current = HEAD;
+        let mut pos = 0;
+        for i in (0..self.level).rev() {
+            loop {
+                let link = self.nodes[current].forward[i];
+                if link.next != NIL && self.key(link.next) < key {
+                    pos += link.width;
+                    current = link.next;
+                } else {
+                    break;
+                }
+            }
+            update[i] = current;
+            position[i] = pos;
+        }
+        (update, position)
+    }
+
+    // The node holding `key`, if any
+    fn find(&self, key: &K) -> Option<usize> {
+        let (update, _) = self.find_predecessors(key);
+        let next = self.nodes[update[0]].forward[0].next;
+        (next != NIL && self.key(next) == key).then_some(next)
+    }
+
+    // Insert a key-value pair, returning the previous value for the key
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let (update, position) = self.find_predecessors(&key);
+        let next = self.nodes[update[0]].forward[0].next;
+        if next != NIL && *self.key(next) == key {
+            let entry = self.nodes[next].entry.as_mut().unwrap();
+            return Some(std::mem::replace(&mut entry.1, value));
+        }
+
+        // update[] and position[] already point at the head for levels above the
+        // current top; give those head links the width of an empty level first
+        let new_level = self.random_level();
+        for i in self.level..new_level {
+            self.nodes[HEAD].forward[i] = Link { next: NIL, width: self.len + 1 };
+        }
+        self.level = self.level.max(new_level);
+
+        let node = Node { entry: Some((key, value)), forward: vec![Link { next: NIL, width: 0 }; new_level] };
+        let index = match self.free.pop() {
+            Some(index) => {
+                self.nodes[index] = node;
+                index
+            }
+            None => {
+                self.nodes.push(node);
+                self.nodes.len() - 1
+            }
+        };
+
+        // The new node takes position p; links over it from higher up grow by one
+        let p = position[0] + 1;
+        for i in 0..self.level {
+            let prev = update[i];
+            let old = self.nodes[prev].forward[i];
+            if i < new_level {
+                self.nodes[index].forward[i] = Link { next: old.next, width: old.width + position[i] + 1 - p };
+                self.nodes[prev].forward[i] = Link { next: index, width: p - position[i] };
+            } else {
+                self.nodes[prev].forward[i].width += 1;
+            }
+        }
+        self.len += 1;
+        None
+    }
+
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        let (update, _) = self.find_predecessors(key);
+        let target = self.nodes[update[0]].forward[0].next;
+        if target == NIL || self.key(target) != key {
+            return None;
+        }
+        for (i, &prev) in update.iter().enumerate().take(self.level) {
+            let link = self.nodes[prev].forward[i];
+            if link.next == target {
+                let skipped = self.nodes[target].forward[i];
+                self.nodes[prev].forward[i] = Link { next: skipped.next, width: link.width + skipped.width - 1 };
+            } else {
+                self.nodes[prev].forward[i].width -= 1;
+            }
+        }
+        let node = std::mem::replace(&mut self.nodes[target], Node { entry: None, forward: Vec::new() });
+        self.free.push(target);
+        while self.level > 1 && self.nodes[HEAD].forward[self.level - 1].next == NIL {
+            self.level -= 1;
+        }
+        self.len -= 1;
+        node.entry.map(|(_, value)| value)
+    }
+
+    pub fn get(&self, key: &K) -> Option<&V> {
+        let node = self.find(key)?;
+        self.nodes[node].entry.as_ref().map(|(_, value)| value)
+    }
+
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        let node = self.find(key)?;
+        self.nodes[node].entry.as_mut().map(|(_, value)| value)
+    }
+
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.find(key).is_some()
+    }
+
+    // Number of keys smaller than `key`
+    pub fn rank(&self, key: &K) -> usize {
+        self.find_predecessors(key).1[0]
+    }
+
+    // The entry at position `index` in key order, counting from 0
+    pub fn get_index(&self, index: usize) -> Option<(&K, &V)> {
+        if index >= self.len {
+            return None;
+        }
+        let target = index + 1;
+        let mut current = HEAD;
+        let mut pos = 0;
+        for i in (0..self.level).rev() {
+            loop {
+                let link = self.nodes[current].forward[i];
+                if link.next != NIL && pos + link.width <= target {
+                    pos += link.width;
+                    current = link.next;
+                } else {
+                    break;
+                }
+            }
+            if pos == target {
+                break;
+            }
+        }
+        self.nodes[current].entry.as_ref().map(|(key, value)| (key, value))
+    }
+
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter { map: self, current: self.nodes[HEAD].forward[0].next, end: .. }
+    }
+
+    pub fn range<R: RangeBounds<K>>(&self, range: R) -> Iter<'_, K, V, R> {
+        let current = match range.start_bound() {
+            Bound::Unbounded => self.nodes[HEAD].forward[0].next,
+            Bound::Included(start) => self.nodes[self.find_predecessors(start).0[0]].forward[0].next,
+            Bound::Excluded(start) => match self.find(start) {
+                Some(node) => self.nodes[node].forward[0].next,
+                None => self.nodes[self.find_predecessors(start).0[0]].forward[0].next,
+            },
+        };
+        Iter { map: self, current, end: range }
+    }
+
+    // Checks key order at level 0 and that every link's width matches the
+    // positions of the nodes it connects
+    pub fn validate(&self) -> Result<(), String> {
+        let mut position = vec![0; self.nodes.len()];
+        let mut current = self.nodes[HEAD].forward[0].next;
+        let mut pos = 0;
+        let mut previous: Option<&K> = None;
+        while current != NIL {
+            pos += 1;
+            position[current] = pos;
+            let key = self.key(current);
+            if previous.is_some_and(|previous| previous >= key) {
+                return Err("keys out of order".to_string());
+            }
+            previous = Some(key);
+            current = self.nodes[current].forward[0].next;
+        }
+        if pos != self.len {
+            return Err(format!("len is {} but level 0 holds {} entries", self.len, pos));
+        }
+        for i in 0..self.level {
+            let mut current = HEAD;
+            loop {
+                let link = self.nodes[current].forward[i];
+                let next_position = if link.next == NIL { self.len + 1 } else { position[link.next] };
+                if link.width != next_position - position[current] {
+                    return Err(format!("wrong link width on level {}", i));
+                }
+                if link.next == NIL {
+                    break;
+                }
+                current = link.next;
+            }
+        }
+        Ok(())
+    }
+}
+
+// Borrowing in-order iterator along level 0, stopping at the end bound
+pub struct Iter<'a, K: Ord, V, R = std::ops::RangeFull> {
+    map: &'a SkipMap<K, V>,
+    current: usize,
+    end: R,
+}
+
+impl<'a, K: Ord, V, R: RangeBounds<K>> Iterator for Iter<'a, K, V, R> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.current == NIL {
+            return None;
+        }
+        let node = &self.map.nodes[self.current];
+        let (key, value) = node.entry.as_ref().unwrap();
+        let in_range = match self.end.end_bound() {
+            Bound::Included(end) => key <= end,
+            Bound::Excluded(end) => key < end,
+            Bound::Unbounded => true,
+        };
+        if !in_range {
+            self.current = NIL;
+            return None;
+        }
+        self.current = node.forward[0].next;
+        Some((key, value))
+    }
+}
+
+// Set of values on top of the map, keeping the original insert/search/delete API
+pub struct SkipList<T: Ord> {
+    map: SkipMap<T, ()>,
+}
+
+impl<T: Ord> Default for SkipList<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Ord> SkipList<T> {
+    pub fn new() -> Self {
+        SkipList { map: SkipMap::new() }
+    }
+
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    // Insert a value, returning false if it was already present
+    pub fn insert(&mut self, value: T) -> bool {
+        if self.map.contains_key(&value) {
+            return false;
+        }
+        self.map.insert(value, ());
+        true
+    }
+
+    // Delete a value, returning whether it was present
+    pub fn delete(&mut self, value: &T) -> bool {
+        self.map.remove(value).is_some()
+    }
+
+    pub fn search(&self, value: &T) -> bool {
+        self.map.contains_key(value)
+    }
+
+    // The value at position `index` in sorted order
+    pub fn get_index(&self, index: usize) -> Option<&T> {
+        self.map.get_index(index).map(|(value, _)| value)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &T> + '_ {
+        self.map.iter().map(|(value, _)| value)
+    }
+}
+
+// Random operations against BTreeMap, including indexed access, rank and ranges
+fn randomized_check(operations: usize, seed: u64) {
+    let mut rng = XorShift(seed);
+    let mut list = SkipMap::new();
+    let mut model = BTreeMap::new();
+    for step in 0..operations {
+        let key = rng.next() % 512;
+        match rng.next() % 6 {
+            0..=2 => assert_eq!(list.insert(key, step), model.insert(key, step)),
+            3 => assert_eq!(list.remove(&key), model.remove(&key)),
+            4 => {
+                let other = rng.next() % 512;
+                let (lo, hi) = (key.min(other), key.max(other));
+                assert!(list.range(lo..hi).eq(model.range(lo..hi)));
+                let excluded = (Bound::Excluded(lo), Bound::Included(hi));
+                assert!(list.range(excluded).eq(model.range(excluded)));
+            }
+            _ => {
+                if let Some(value) = list.get_mut(&key) {
+                    *value += 1;
+                    *model.get_mut(&key).unwrap() += 1;
+                }
+            }
+        }
+        assert_eq!(list.len(), model.len());
+        assert_eq!(list.get(&key), model.get(&key));
+        assert_eq!(list.rank(&key), model.range(..key).count());
+        let index = rng.next() as usize % (model.len() + 1);
+        assert_eq!(list.get_index(index), model.iter().nth(index));
+        if step % 500 == 0 {
+            if let Err(e) = list.validate() {
+                panic!("invariant broken at step {}: {}", step, e);
+            }
+        }
+    }
+    list.validate().unwrap();
+    assert!(list.iter().eq(model.iter()));
+}
+
+fn main() {
+    let mut skip_list = SkipList::new();
+    for value in [3, 6, 7, 9, 12] {
+        skip_list.insert(value);
+    }
+
+    println!("Search for 7: {}", skip_list.search(&7));
+    println!("Search for 10: {}", skip_list.search(&10));
+
+    skip_list.delete(&7);
+    println!("After deleting 7: {:?} ({} values)", skip_list.iter().collect::<Vec<_>>(), skip_list.len());
+    println!("Value at index 2: {:?}", skip_list.get_index(2));
+
+    // Indexed access on a key/value map
+    let mut map = SkipMap::new();
+    for i in 0..1000u32 {
+        map.insert(i * 3, i);
+    }
+    println!("Entry at index 500: {:?}, rank of 1500: {}", map.get_index(500), map.rank(&1500));
+    println!("Range 30..=42: {:?}", map.range(30..=42).collect::<Vec<_>>());
+
+    randomized_check(20_000, 0x853C49E6748FEA9B);
+    println!("Randomized check passed: lookups, ranks, indexes and ranges matched BTreeMap");
+}
@@ -0,0 +1,557 @@
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::fmt::Debug;
+use std::ops::{Bound, RangeBounds};
+
+#[path = "rust-tree-printer.rs"]
+#[allow(dead_code)]
+mod tree_printer;
+
+#[path = "rust-tree-iter.rs"]
+#[allow(dead_code)]
+mod tree_iter;
+
+use tree_iter::InOrderNode;
+use tree_printer::TreeShape;
+
+// Splay Tree: a self-adjusting binary search tree. Every access splays the key
+// (or its nearest neighbour) to the root, so recently used keys are cheap to
+// reach again; operations cost O(log n) amortized.
+//
+// Splaying is top-down and iterative. A splay tree can legitimately degenerate
+// into a long path (for example after inserting keys in ascending order), so no
+// operation here recurses over the height, including drop.
+//
+// Nodes cache their subtree size. Range operations splay both ends of the range
+// so that the keys inside it hang off the root as a single subtree, which can
+// then be counted, iterated or cut out whole in amortized O(log n) plus output.
+
+type Link<K, V> = Option<Box<Node<K, V>>>;
+
+// Public only because `Iter` names it; the fields stay private
+pub struct Node<K, V> {
+    key: K,
+    value: V,
+    size: usize,
+    left: Link<K, V>,
+    right: Link<K, V>,
+}
+
+impl<K, V> Node<K, V> {
+    fn new(key: K, value: V) -> Box<Self> {
+        Box::new(Node { key, value, size: 1, left: None, right: None })
+    }
+
+    fn update(&mut self) {
+        self.size = 1 + size(&self.left) + size(&self.right);
+    }
+}
+
+impl<K, V> TreeShape for Node<K, V> {
+    fn children(&self) -> Vec<Option<&Self>> {
+        vec![self.left.as_deref(), self.right.as_deref()]
+    }
+
+    fn annotation(&self) -> String {
+        format!("n={}", self.size)
+    }
+}
+
+fn size<K, V>(link: &Link<K, V>) -> usize {
+    link.as_ref().map_or(0, |n| n.size)
+}
+
+// Top-down splay: walk down from the root, rotating on zig-zig steps and
+// peeling nodes off into a left tree (keys below the target) and a right tree
+// (keys above it). The spines are kept in Vecs and reattached at the end under
+// the node where the search stopped, which becomes the new root.
+//
+// `target` compares the sought position against a node's key, so the same walk
+// serves exact keys, range bounds and the minimum or maximum.
+fn splay_by<K, V>(mut root: Box<Node<K, V>>, target: impl Fn(&K) -> Ordering) -> Box<Node<K, V>> {
+    let mut left_spine: Vec<Box<Node<K, V>>> = Vec::new();
+    let mut right_spine: Vec<Box<Node<K, V>>> = Vec::new();
+    loop {
+        match target(&root.key) {
+            Ordering::Less => {
+                let mut left = match root.left.take() {
+                    None => break,
+                    Some(left) => left,
+                };
+                if target(&left.key) == Ordering::Less {
+                    // Zig-zig: rotate right first
+                    root.left = left.right.take();
+                    root.update();
+                    left.right = Some(root);
+                    root = left;
+                    left = match root.left.take() {
+                        None => break,
+                        Some(left) => left,
+                    };
+                }
+                right_spine.push(root);
+                root = left;
+            }
+            Ordering::Greater => {
+                let mut right = match root.right.take() {
+                    None => break,
+                    Some(right) => right,
+                };
+                if target(&right.key) == Ordering::Greater {
+                    // Zig-zig: rotate left first
+                    root.right = right.left.take();
+                    root.update();
+                    right.left = Some(root);
+                    root = right;
+                    right = match root.right.take() {
+                        None => break,
+                        Some(right) => right,
+                    };
+                }
+                left_spine.push(root);
+                root = right;
+            }
+            Ordering::Equal => break,
+        }
+    }
+
+    // Reattach deepest first so every spine node's size is recomputed over its final children
+    let mut left = root.left.take();
+    while let Some(mut node) = left_spine.pop() {
+        node.right = left;
+        node.update();
+        left = Some(node);
+    }
+    let mut right = root.right.take();
+    while let Some(mut node) = right_spine.pop() {
+        node.left = right;
+        node.update();
+        right = Some(node);
+    }
+    root.left = left;
+    root.right = right;
+    root.update();
+    root
+}
+
+// Split where `goes_right` turns true; it must be false for a prefix of the keys and true for the rest
+fn split_by<K, V>(link: Link<K, V>, goes_right: impl Fn(&K) -> bool) -> (Link<K, V>, Link<K, V>) {
+    let root = match link {
+        None => return (None, None),
+        Some(root) => root,
+    };
+    let mut root = splay_by(root, |key| if goes_right(key) { Ordering::Less } else { Ordering::Greater });
+    if goes_right(&root.key) {
+        let left = root.left.take();
+        root.update();
+        (left, Some(root))
+    } else {
+        let right = root.right.take();
+        root.update();
+        (Some(root), right)
+    }
+}
+
+// Join two trees where every key in `left` is smaller than every key in `right`
+fn join<K, V>(left: Link<K, V>, right: Link<K, V>) -> Link<K, V> {
+    match left {
+        None => right,
+        Some(left) => {
+            // Splaying the maximum leaves the root without a right child
+            let mut root = splay_by(left, |_| Ordering::Greater);
+            root.right = right;
+            root.update();
+            Some(root)
+        }
+    }
+}
+
+pub struct SplayMap<K: Ord, V> {
+    root: Link<K, V>,
+}
+
+impl<K: Ord, V> Default for SplayMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Ord, V> SplayMap<K, V> {
+    pub fn new() -> Self {
+        SplayMap { root: None }
+    }
+
+    pub fn len(&self) -> usize {
+        size(&self.root)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    // Splays the key (or its neighbour) to the root and reports whether it is there
+    fn splay_key(&mut self, key: &K) -> bool {
+        match self.root.take() {
+            None => false,
+            Some(root) => {
+                let root = splay_by(root, |k| key.cmp(k));
+                let found = root.key == *key;
+                self.root = Some(root);
+                found
+            }
+        }
+    }
+
+    // Insert a key-value pair, returning the previous value for the key
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        if self.splay_key(&key) {
+            let root = self.root.as_mut().unwrap();
+            return Some(std::mem::replace(&mut root.value, value));
+        }
+        let mut new_root = Node::new(key, value);
+        if let Some(mut root) = self.root.take() {
+            if new_root.key < root.key {
+                new_root.left = root.left.take();
+                root.update();
+                new_root.right = Some(root);
+            } else {
+                new_root.right = root.right.take();
+                root.update();
+                new_root.left = Some(root);
+            }
+            new_root.update();
+        }
+        self.root = Some(new_root);
+        None
+    }
+
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        if !self.splay_key(key) {
+            return None;
+        }
+        let mut root = self.root.take().unwrap();
+        self.root = join(root.left.take(), root.right.take());
+        Some(root.value)
+    }
+
+    // Lookups splay too, hence `&mut self`
+    pub fn get(&mut self, key: &K) -> Option<&V> {
+        if self.splay_key(key) {
+            self.root.as_ref().map(|root| &root.value)
+        } else {
+            None
+        }
+    }
+
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        if self.splay_key(key) {
+            self.root.as_mut().map(|root| &mut root.value)
+        } else {
+            None
+        }
+    }
+
+    pub fn contains_key(&mut self, key: &K) -> bool {
+        self.splay_key(key)
+    }
+
+    pub fn first(&mut self) -> Option<(&K, &V)> {
+        let root = splay_by(self.root.take()?, |_| Ordering::Less);
+        let root = self.root.insert(root);
+        Some((&root.key, &root.value))
+    }
+
+    pub fn last(&mut self) -> Option<(&K, &V)> {
+        let root = splay_by(self.root.take()?, |_| Ordering::Greater);
+        let root = self.root.insert(root);
+        Some((&root.key, &root.value))
+    }
+
+    // Keep the keys below `key` and return the rest, like BTreeMap::split_off
+    pub fn split_off(&mut self, key: &K) -> Self {
+        let (left, right) = split_by(self.root.take(), |k| k >= key);
+        self.root = left;
+        SplayMap { root: right }
+    }
+
+    // Move every entry of `other` in; all of its keys must be larger than ours
+    pub fn append(&mut self, mut other: Self) {
+        if let (Some((last, _)), Some((first, _))) = (self.iter().next_back(), other.iter().next()) {
+            assert!(last < first, "append: key ranges overlap");
+        }
+        self.root = join(self.root.take(), other.root.take());
+    }
+
+    // Cut the tree into the keys before `range`, inside it and after it
+    fn split_range<R: RangeBounds<K>>(&mut self, range: &R) -> (Link<K, V>, Link<K, V>, Link<K, V>) {
+        let (before, rest) = split_by(self.root.take(), |k| match range.start_bound() {
+            Bound::Included(start) => k >= start,
+            Bound::Excluded(start) => k > start,
+            Bound::Unbounded => true,
+        });
+        let (inside, after) = split_by(rest, |k| match range.end_bound() {
+            Bound::Included(end) => k > end,
+            Bound::Excluded(end) => k >= end,
+            Bound::Unbounded => false,
+        });
+        (before, inside, after)
+    }
+
+    // Reassemble around the keys in `range` so that they form one subtree right
+    // under the root (the maximum before the range) or its right child (the
+    // minimum after it), and return that subtree
+    fn isolate<R: RangeBounds<K>>(&mut self, range: R) -> &Link<K, V> {
+        let (before, inside, after) = self.split_range(&range);
+        let before = before.map(|tree| splay_by(tree, |_| Ordering::Greater));
+        let after = after.map(|tree| splay_by(tree, |_| Ordering::Less));
+        match (before, after) {
+            (None, None) => {
+                self.root = inside;
+                &self.root
+            }
+            (None, Some(mut after)) => {
+                after.left = inside;
+                after.update();
+                &self.root.insert(after).left
+            }
+            (Some(mut before), None) => {
+                before.right = inside;
+                before.update();
+                &self.root.insert(before).right
+            }
+            (Some(mut before), Some(mut after)) => {
+                after.left = inside;
+                after.update();
+                before.right = Some(after);
+                before.update();
+                &self.root.insert(before).right.as_mut().unwrap().left
+            }
+        }
+    }
+
+    pub fn count_range<R: RangeBounds<K>>(&mut self, range: R) -> usize {
+        size(self.isolate(range))
+    }
+
+    pub fn range<R: RangeBounds<K>>(&mut self, range: R) -> Iter<'_, K, V> {
+        let inside = self.isolate(range);
+        Iter::new(inside.as_deref(), size(inside))
+    }
+
+    // Remove every key in `range` at once and return them as a map
+    pub fn remove_range<R: RangeBounds<K>>(&mut self, range: R) -> Self {
+        let (before, inside, after) = self.split_range(&range);
+        self.root = join(before, after);
+        SplayMap { root: inside }
+    }
+
+    // Does not splay, so it can borrow immutably
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter::new(self.root.as_deref(), size(&self.root))
+    }
+
+    // The printer recurses over the height, so these are meant for small trees
+    pub fn pretty(&self) -> String
+    where
+        K: Debug,
+    {
+        tree_printer::ascii(self.root.as_deref(), |n| format!("{:?}", n.key))
+    }
+
+    pub fn to_dot(&self) -> String
+    where
+        K: Debug,
+    {
+        tree_printer::dot(self.root.as_deref(), |n| format!("{:?}", n.key))
+    }
+
+    // Checks BST order and the cached subtree sizes without recursing
+    pub fn validate(&self) -> Result<(), String> {
+        let mut keys = self.iter().map(|(key, _)| key);
+        if let Some(mut previous) = keys.next() {
+            for key in keys {
+                if key <= previous {
+                    return Err("BST order violated".to_string());
+                }
+                previous = key;
+            }
+        }
+        let mut stack: Vec<&Node<K, V>> = self.root.as_deref().into_iter().collect();
+        while let Some(node) = stack.pop() {
+            if node.size != 1 + size(&node.left) + size(&node.right) {
+                return Err("stale subtree size".to_string());
+            }
+            stack.extend(node.left.as_deref());
+            stack.extend(node.right.as_deref());
+        }
+        Ok(())
+    }
+}
+
+// The default recursive drop could overflow the stack on a long path
+impl<K: Ord, V> Drop for SplayMap<K, V> {
+    fn drop(&mut self) {
+        let mut stack: Vec<Box<Node<K, V>>> = self.root.take().into_iter().collect();
+        while let Some(mut node) = stack.pop() {
+            stack.extend(node.left.take());
+            stack.extend(node.right.take());
+        }
+    }
+}
+
+// Borrowing in-order iterator over a subtree, from both ends
+pub type Iter<'a, K, V> = tree_iter::Iter<'a, Node<K, V>>;
+
+impl<K, V> InOrderNode for Node<K, V> {
+    type Key = K;
+    type Value = V;
+
+    fn left(&self) -> Option<&Self> {
+        self.left.as_deref()
+    }
+
+    fn right(&self) -> Option<&Self> {
+        self.right.as_deref()
+    }
+
+    fn entry(&self) -> (&K, &V) {
+        (&self.key, &self.value)
+    }
+}
+
+// Set of keys on top of the map, keeping the original insert/contains/delete API
+pub struct SplayTree<T: Ord> {
+    map: SplayMap<T, ()>,
+}
+
+impl<T: Ord> Default for SplayTree<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Ord> SplayTree<T> {
+    pub fn new() -> Self {
+        SplayTree { map: SplayMap::new() }
+    }
+
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    // Insert a key, returning false if it was already present
+    pub fn insert(&mut self, key: T) -> bool {
+        self.map.insert(key, ()).is_none()
+    }
+
+    // Splays the key (or its neighbour) to the root, hence `&mut self`
+    pub fn contains(&mut self, key: &T) -> bool {
+        self.map.contains_key(key)
+    }
+
+    // Delete a key, returning whether it was present
+    pub fn delete(&mut self, key: &T) -> bool {
+        self.map.remove(key).is_some()
+    }
+
+    pub fn inorder(&self) -> Vec<&T> {
+        self.map.iter().map(|(key, _)| key).collect()
+    }
+
+    pub fn pretty(&self) -> String
+    where
+        T: Debug,
+    {
+        self.map.pretty()
+    }
+}
+
+// Small xorshift generator for the randomized check below
+struct XorShift(u64);
+
+impl XorShift {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+}
+
+// Random point and range operations against BTreeMap, validating every step
+fn randomized_check(operations: usize, seed: u64) {
+    let mut rng = XorShift(seed);
+    let mut tree = SplayMap::new();
+    let mut model = BTreeMap::new();
+    for step in 0..operations {
+        let key = rng.next() % 512;
+        let other = rng.next() % 512;
+        let (lo, hi) = (key.min(other), key.max(other));
+        match rng.next() % 10 {
+            0..=3 => assert_eq!(tree.insert(key, step), model.insert(key, step)),
+            4 => assert_eq!(tree.remove(&key), model.remove(&key)),
+            5 => assert_eq!(tree.get(&key), model.get(&key)),
+            6 => assert_eq!(tree.count_range(lo..hi), model.range(lo..hi).count()),
+            7 => assert!(tree.range((Bound::Excluded(lo), Bound::Included(hi))).eq(model.range((Bound::Excluded(lo), Bound::Included(hi))))),
+            8 if step % 4 == 0 => {
+                let removed = tree.remove_range(lo..=hi);
+                let expected: Vec<_> = model.range(lo..=hi).map(|(&k, &v)| (k, v)).collect();
+                assert!(removed.iter().map(|(&k, &v)| (k, v)).eq(expected.iter().copied()));
+                for (k, _) in expected {
+                    model.remove(&k);
+                }
+            }
+            _ => {
+                // Cut at a key and glue the halves back together
+                let upper = tree.split_off(&key);
+                assert_eq!(upper.len(), model.range(key..).count());
+                tree.append(upper);
+            }
+        }
+        if let Err(e) = tree.validate() {
+            panic!("invariant broken at step {}: {}", step, e);
+        }
+        assert_eq!(tree.len(), model.len());
+        assert_eq!(tree.first().map(|(&k, &v)| (k, v)), model.first_key_value().map(|(&k, &v)| (k, v)));
+    }
+    assert!(tree.iter().eq(model.iter()));
+    assert!(tree.iter().rev().eq(model.iter().rev()));
+}
+
+fn main() {
+    let mut tree = SplayTree::new();
+    for key in [10, 20, 30, 40, 50, 25] {
+        tree.insert(key);
+    }
+
+    println!("Contains 30: {}", tree.contains(&30));
+    println!("Contains 35: {}", tree.contains(&35));
+    tree.delete(&20);
+    println!("After deleting 20: {:?}", tree.inorder());
+    tree.contains(&40);
+    print!("After looking up 40, it sits at the root:\n{}", tree.pretty());
+
+    // Range operations on a key/value map
+    let mut scores = SplayMap::new();
+    for (i, name) in ["ann", "ben", "cat", "dan", "eve", "fay", "gus"].into_iter().enumerate() {
+        scores.insert(name, i * 10);
+    }
+    println!("Names in \"b\"..\"e\": {}", scores.count_range("b".."e"));
+    println!("Entries from \"cat\" to \"eve\": {:?}", scores.range("cat"..="eve").collect::<Vec<_>>());
+    let removed = scores.remove_range("c".."f");
+    println!("Removed {} names, left {:?}", removed.len(), scores.iter().map(|(name, _)| name).collect::<Vec<_>>());
+
+    // Ascending inserts build a path; lookups then splay it back into shape
+    let mut path = SplayTree::new();
+    for key in 0..100_000 {
+        path.insert(key);
+    }
+    println!("Contains 0 after 100000 ascending inserts: {}", path.contains(&0));
+
+    randomized_check(20_000, 0x853C49E6748FEA9B);
+    println!("Randomized check passed: point and range operations matched BTreeMap");
+}
@@ -0,0 +1,457 @@
+use std::collections::{HashMap, HashSet, VecDeque, BinaryHeap};
+use std::cmp::Ordering;
+use std::hash::Hash;
+
+// A search problem is described by its start state, a goal test, the successor
+// function with step costs, and an optional admissible heuristic
+pub trait SearchProblem {
+    type State: Hash + Eq + Clone;
+
+    fn start(&self) -> Self::State;
+    fn is_goal(&self, state: &Self::State) -> bool;
+    fn successors(&self, state: &Self::State) -> Vec<(Self::State, u32)>;
+
+    fn heuristic(&self, _state: &Self::State) -> u32 {
+        0
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Solution<S> {
+    pub path: Vec<S>,
+    pub cost: u32,
+}
+
+// Counters collected by every solver
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct SearchStats {
+    pub expanded: usize,
+    pub generated: usize,
+    pub duplicates: usize,
+    pub max_frontier: usize,
+}
+
+#[derive(Debug)]
+pub struct SearchOutcome<S> {
+    pub solution: Option<Solution<S>>,
+    pub stats: SearchStats,
+}
+
+fn build_path<S: Hash + Eq + Clone>(parents: &HashMap<S, S>, goal: S) -> Vec<S> {
+    let mut path = vec![goal.clone()];
+    let mut current = goal;
+    while let Some(prev) = parents.get(&current) {
+        path.push(prev.clone());
+        current = prev.clone();
+    }
+    path.reverse();
+    path
+}
+
+// 1. Breadth-First Search with duplicate detection (optimal for unit step costs)
+pub fn bfs<P: SearchProblem>(problem: &P) -> SearchOutcome<P::State> {
+    let mut stats = SearchStats::default();
+    let start = problem.start();
+    let mut parents: HashMap<P::State, P::State> = HashMap::new();
+    let mut costs: HashMap<P::State, u32> = HashMap::new();
+    let mut seen = HashSet::new();
+    let mut queue = VecDeque::new();
+
+    seen.insert(start.clone());
+    costs.insert(start.clone(), 0);
+    queue.push_back(start);
+
+    while let Some(state) = queue.pop_front() {
+        if problem.is_goal(&state) {
+            let cost = costs[&state];
+            let path = build_path(&parents, state);
+            return SearchOutcome { solution: Some(Solution { path, cost }), stats };
+        }
+        stats.expanded += 1;
+        let cost = costs[&state];
+        for (next, step) in problem.successors(&state) {
+            stats.generated += 1;
+            if !seen.insert(next.clone()) {
+                stats.duplicates += 1;
+                continue;
+            }
+            parents.insert(next.clone(), state.clone());
+            costs.insert(next.clone(), cost + step);
+            queue.push_back(next);
+        }
+        stats.max_frontier = stats.max_frontier.max(queue.len());
+    }
+
+    SearchOutcome { solution: None, stats }
+}
+
+// Frontier entry ordered by f = g + h, breaking ties towards deeper nodes
+struct Entry<S> {
+    f: u32,
+    g: u32,
+    state: S,
+}
+
+impl<S> PartialEq for Entry<S> {
+    fn eq(&self, other: &Self) -> bool {
+        self.f == other.f && self.g == other.g
+    }
+}
+
+impl<S> Eq for Entry<S> {}
+
+impl<S> Ord for Entry<S> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.f.cmp(&self.f).then_with(|| self.g.cmp(&other.g))
+    }
+}
+
+impl<S> PartialOrd for Entry<S> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+fn best_first<P: SearchProblem>(problem: &P, h: impl Fn(&P::State) -> u32) -> SearchOutcome<P::State> {
+    let mut stats = SearchStats::default();
+    let start = problem.start();
+    let mut parents: HashMap<P::State, P::State> = HashMap::new();
+    let mut best: HashMap<P::State, u32> = HashMap::new();
+    let mut closed = HashSet::new();
+    let mut heap = BinaryHeap::new();
+
+    best.insert(start.clone(), 0);
+    heap.push(Entry { f: h(&start), g: 0, state: start });
+
+    while let Some(Entry { g, state, .. }) = heap.pop() {
+        if !closed.insert(state.clone()) {
+            continue;
+        }
+        if problem.is_goal(&state) {
+            let path = build_path(&parents, state);
+            return SearchOutcome { solution: Some(Solution { path, cost: g }), stats };
+        }
+        stats.expanded += 1;
+        for (next, step) in problem.successors(&state) {
+            stats.generated += 1;
+            let next_g = g + step;
+            if closed.contains(&next) || best.get(&next).is_some_and(|&b| b <= next_g) {
+                stats.duplicates += 1;
+                continue;
+            }
+            best.insert(next.clone(), next_g);
+            parents.insert(next.clone(), state.clone());
+            heap.push(Entry { f: next_g + h(&next), g: next_g, state: next });
+        }
+        stats.max_frontier = stats.max_frontier.max(heap.len());
+    }
+
+    SearchOutcome { solution: None, stats }
+}
+
+// 2. Uniform-Cost Search (Dijkstra over the state space)
+pub fn uniform_cost<P: SearchProblem>(problem: &P) -> SearchOutcome<P::State> {
+    best_first(problem, |_| 0)
+}
+
+// 3. A* Search using the problem's heuristic
+pub fn a_star<P: SearchProblem>(problem: &P) -> SearchOutcome<P::State> {
+    best_first(problem, |state| problem.heuristic(state))
+}
+
+// 4. IDA*: depth-first probes bounded by f, raising the bound each round.
+// Memory stays linear in the solution depth since only the current path is kept.
+pub fn ida_star<P: SearchProblem>(problem: &P) -> SearchOutcome<P::State> {
+    fn probe<P: SearchProblem>(
+        problem: &P,
+        path: &mut Vec<P::State>,
+        on_path: &mut HashSet<P::State>,
+        g: u32,
+        bound: u32,
+        stats: &mut SearchStats,
+    ) -> Result<u32, u32> {
+        let state = path.last().unwrap().clone();
+        let f = g + problem.heuristic(&state);
+        if f > bound {
+            return Err(f);
+        }
+        if problem.is_goal(&state) {
+            return Ok(g);
+        }
+        stats.expanded += 1;
+        let mut next_bound = u32::MAX;
+        for (next, step) in problem.successors(&state) {
+            stats.generated += 1;
+            if !on_path.insert(next.clone()) {
+                stats.duplicates += 1;
+                continue;
+            }
+            path.push(next.clone());
+            stats.max_frontier = stats.max_frontier.max(path.len());
+            match probe(problem, path, on_path, g + step, bound, stats) {
+                Ok(cost) => return Ok(cost),
+                Err(t) => next_bound = next_bound.min(t),
+            }
+            path.pop();
+            on_path.remove(&next);
+        }
+        Err(next_bound)
+    }
+
+    let mut stats = SearchStats::default();
+    let start = problem.start();
+    let mut bound = problem.heuristic(&start);
+    let mut path = vec![start.clone()];
+    let mut on_path: HashSet<P::State> = [start].into_iter().collect();
+
+    loop {
+        match probe(problem, &mut path, &mut on_path, 0, bound, &mut stats) {
+            Ok(cost) => return SearchOutcome { solution: Some(Solution { path, cost }), stats },
+            Err(u32::MAX) => return SearchOutcome { solution: None, stats },
+            Err(next_bound) => bound = next_bound,
+        }
+    }
+}
+
+// 5. Beam Search: keeps only the `width` most promising states of each layer.
+// Fast and memory-bounded, but neither complete nor optimal.
+pub fn beam_search<P: SearchProblem>(problem: &P, width: usize) -> SearchOutcome<P::State> {
+    let mut stats = SearchStats::default();
+    let start = problem.start();
+    let mut parents: HashMap<P::State, P::State> = HashMap::new();
+    let mut costs: HashMap<P::State, u32> = HashMap::new();
+    let mut seen = HashSet::new();
+    let mut layer = vec![start.clone()];
+
+    seen.insert(start.clone());
+    costs.insert(start, 0);
+
+    while !layer.is_empty() {
+        let mut candidates = Vec::new();
+        for state in &layer {
+            if problem.is_goal(state) {
+                let cost = costs[state];
+                let path = build_path(&parents, state.clone());
+                return SearchOutcome { solution: Some(Solution { path, cost }), stats };
+            }
+            stats.expanded += 1;
+            for (next, step) in problem.successors(state) {
+                stats.generated += 1;
+                if !seen.insert(next.clone()) {
+                    stats.duplicates += 1;
+                    continue;
+                }
+                let g = costs[state] + step;
+                parents.insert(next.clone(), state.clone());
+                costs.insert(next.clone(), g);
+                candidates.push((g + problem.heuristic(&next), next));
+            }
+        }
+        candidates.sort_by_key(|c| c.0);
+        candidates.truncate(width);
+        stats.max_frontier = stats.max_frontier.max(candidates.len());
+        layer = candidates.into_iter().map(|(_, state)| state).collect();
+    }
+
+    SearchOutcome { solution: None, stats }
+}
+
+// Example 1: Sliding-tile puzzle, blank encoded as 0
+struct SlidingPuzzle {
+    size: usize,
+    tiles: Vec<u8>,
+}
+
+impl SearchProblem for SlidingPuzzle {
+    type State = Vec<u8>;
+
+    fn start(&self) -> Vec<u8> {
+        self.tiles.clone()
+    }
+
+    fn is_goal(&self, state: &Vec<u8>) -> bool {
+        let n = state.len();
+        state.iter().enumerate().all(|(i, &t)| t as usize == (i + 1) % n)
+    }
+
+    fn successors(&self, state: &Vec<u8>) -> Vec<(Vec<u8>, u32)> {
+        let blank = state.iter().position(|&t| t == 0).unwrap();
+        let (row, col) = (blank / self.size, blank % self.size);
+        let mut moves = Vec::new();
+        if row > 0 { moves.push(blank - self.size); }
+        if row + 1 < self.size { moves.push(blank + self.size); }
+        if col > 0 { moves.push(blank - 1); }
+        if col + 1 < self.size { moves.push(blank + 1); }
+        moves.into_iter().map(|pos| {
+            let mut next = state.clone();
+            next.swap(blank, pos);
+            (next, 1)
+        }).collect()
+    }
+
+    // Sum of Manhattan distances of each tile from its home square
+    fn heuristic(&self, state: &Vec<u8>) -> u32 {
+        state.iter().enumerate().filter(|&(_, &t)| t != 0).map(|(i, &t)| {
+            let home = t as usize - 1;
+            ((i / self.size).abs_diff(home / self.size) + (i % self.size).abs_diff(home % self.size)) as u32
+        }).sum()
+    }
+}
+
+// Example 2: Word ladder, each step changes exactly one letter. Replacement
+// letters come from the word list, so words outside a-z work too.
+pub struct WordLadder {
+    begin: String,
+    end: String,
+    words: HashSet<String>,
+    alphabet: Vec<char>,
+}
+
+impl WordLadder {
+    pub fn new(begin: &str, end: &str, words: impl IntoIterator<Item = String>) -> Self {
+        let words: HashSet<String> = words.into_iter().collect();
+        let mut alphabet: Vec<char> = words.iter().flat_map(|word| word.chars()).collect();
+        alphabet.sort_unstable();
+        alphabet.dedup();
+        WordLadder { begin: begin.to_string(), end: end.to_string(), words, alphabet }
+    }
+
+    // Number of words on the shortest ladder, counting both ends, or 0 if none
+    pub fn length(&self) -> i32 {
+        if !self.words.contains(&self.end) {
+            return 0;
+        }
+        bfs(self).solution.map_or(0, |s| s.path.len() as i32)
+    }
+}
+
+impl SearchProblem for WordLadder {
+    type State = String;
+
+    fn start(&self) -> String {
+        self.begin.clone()
+    }
+
+    fn is_goal(&self, state: &String) -> bool {
+        *state == self.end
+    }
+
+    fn successors(&self, state: &String) -> Vec<(String, u32)> {
+        let mut next = Vec::new();
+        for (i, old) in state.char_indices() {
+            for &c in &self.alphabet {
+                if c == old {
+                    continue;
+                }
+                let mut candidate = state.clone();
+                candidate.replace_range(i..i + old.len_utf8(), c.encode_utf8(&mut [0; 4]));
+                if self.words.contains(&candidate) {
+                    next.push((candidate, 1));
+                }
+            }
+        }
+        next
+    }
+
+    // Every differing letter needs at least one step
+    fn heuristic(&self, state: &String) -> u32 {
+        state.chars().zip(self.end.chars()).filter(|(a, b)| a != b).count() as u32
+    }
+}
+
+// Example 3: N-Queens, placing one queen per row; a state lists the chosen columns
+struct NQueens {
+    n: usize,
+}
+
+impl SearchProblem for NQueens {
+    type State = Vec<usize>;
+
+    fn start(&self) -> Vec<usize> {
+        Vec::new()
+    }
+
+    fn is_goal(&self, state: &Vec<usize>) -> bool {
+        state.len() == self.n
+    }
+
+    fn successors(&self, state: &Vec<usize>) -> Vec<(Vec<usize>, u32)> {
+        let row = state.len();
+        (0..self.n)
+            .filter(|&col| state.iter().enumerate().all(|(r, &c)| c != col && row - r != c.abs_diff(col)))
+            .map(|col| {
+                let mut next = state.clone();
+                next.push(col);
+                (next, 1)
+            })
+            .collect()
+    }
+
+    // Exactly one step per remaining row
+    fn heuristic(&self, state: &Vec<usize>) -> u32 {
+        (self.n - state.len()) as u32
+    }
+}
+
+fn report<S: std::fmt::Debug>(name: &str, outcome: &SearchOutcome<S>) {
+    match &outcome.solution {
+        Some(solution) => println!("{}: cost {}, {} states on path", name, solution.cost, solution.path.len()),
+        None => println!("{}: no solution", name),
+    }
+    println!("  {:?}", outcome.stats);
+}
+
+fn main() {
+    // 1. Sliding puzzle (8-puzzle)
+    println!("Sliding Puzzle:");
+    let puzzle = SlidingPuzzle { size: 3, tiles: vec![8, 6, 7, 2, 5, 4, 3, 0, 1] };
+    report("BFS", &bfs(&puzzle));
+    report("Uniform-Cost", &uniform_cost(&puzzle));
+    report("A*", &a_star(&puzzle));
+    report("IDA*", &ida_star(&puzzle));
+    report("Beam (width 100)", &beam_search(&puzzle, 100));
+
+    // 2. Word ladder
+    println!("\nWord Ladder:");
+    let words: Vec<String> = ["hot", "dot", "dog", "lot", "log", "cog"].iter().map(|w| w.to_string()).collect();
+    let ladder = WordLadder::new("hit", "cog", words);
+    println!("Word Ladder Length: {}", ladder.length());
+    if let Some(solution) = a_star(&ladder).solution {
+        println!("A* Ladder: {:?}", solution.path);
+    }
+    // Multibyte letters are replaced whole
+    let accented = WordLadder::new("café", "rosé", ["cafe", "rafe", "rase", "rose", "rosé", "casé", "rasé"].map(String::from));
+    println!("Accented ladder: {:?}", bfs(&accented).solution.map(|s| s.path));
+
+    // 3. N-Queens
+    println!("\nN-Queens (8x8):");
+    let queens = NQueens { n: 8 };
+    let outcome = ida_star(&queens);
+    report("IDA*", &outcome);
+    if let Some(solution) = outcome.solution {
+        for &col in solution.path.last().unwrap() {
+            println!("{}", (0..queens.n).map(|c| if c == col { 'Q' } else { '.' }).collect::<String>());
+        }
+    }
+    report("BFS", &bfs(&queens));
+
+    // The optimal solvers must agree on the cost of every example
+    let solved: Vec<u8> = (1..9).chain([0]).collect();
+    for tiles in [vec![8, 6, 7, 2, 5, 4, 3, 0, 1], vec![1, 2, 3, 4, 5, 6, 0, 7, 8], vec![4, 1, 3, 7, 2, 6, 5, 8, 0]] {
+        let puzzle = SlidingPuzzle { size: 3, tiles };
+        let solution = bfs(&puzzle).solution;
+        let cost = solution.as_ref().map(|s| s.cost);
+        assert_eq!(a_star(&puzzle).solution.map(|s| s.cost), cost, "A* on {:?}", puzzle.tiles);
+        assert_eq!(ida_star(&puzzle).solution.map(|s| s.cost), cost, "IDA* on {:?}", puzzle.tiles);
+        let path = solution.unwrap().path;
+        assert_eq!(path.last(), Some(&solved));
+        assert!(path.windows(2).all(|step| puzzle.successors(&step[0]).iter().any(|(next, _)| *next == step[1])));
+    }
+    for ladder in [&ladder, &accented] {
+        let cost = bfs(ladder).solution.map(|s| s.cost);
+        assert_eq!(a_star(ladder).solution.map(|s| s.cost), cost);
+    }
+    assert_eq!(ladder.length(), 5);
+    assert_eq!(accented.length(), 4);
+    println!("\nBFS, A* and IDA* agreed on every optimal cost");
+}
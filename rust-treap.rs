@@ -0,0 +1,593 @@
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::fmt::Debug;
+
+#[path = "rust-tree-printer.rs"]
+#[allow(dead_code)]
+mod tree_printer;
+
+#[path = "rust-tree-iter.rs"]
+#[allow(dead_code)]
+mod tree_iter;
+
+use tree_iter::InOrderNode;
+use tree_printer::TreeShape;
+
+// Treap (Randomized Binary Search Tree): keys follow the BST order and randomly
+// assigned priorities follow the max-heap order, which keeps the expected height
+// at O(log n) whatever the insertion order. Priorities come from a small seeded
+// xorshift generator, so runs are reproducible.
+//
+// Everything is built from two primitives, split and merge, which both run in
+// expected O(log n). Nodes carry their subtree size, so the same primitives also
+// drive `ImplicitTreap`, a sequence whose "key" is simply a position.
+
+type Link<K, V> = Option<Box<Node<K, V>>>;
+
+// Public only because `Iter` names it; the fields stay private
+pub struct Node<K, V> {
+    key: K,
+    value: V,
+    priority: u64,
+    size: usize,
+    left: Link<K, V>,
+    right: Link<K, V>,
+}
+
+impl<K, V> Node<K, V> {
+    fn new(key: K, value: V, priority: u64) -> Box<Self> {
+        Box::new(Node {
+            key,
+            value,
+            priority,
+            size: 1,
+            left: None,
+            right: None,
+        })
+    }
+
+    fn update(&mut self) {
+        self.size = 1 + size(&self.left) + size(&self.right);
+    }
+}
+
+// Shows the top 16 bits of the priority, which decide the shape in practice
+impl<K, V> TreeShape for Node<K, V> {
+    fn children(&self) -> Vec<Option<&Self>> {
+        vec![self.left.as_deref(), self.right.as_deref()]
+    }
+
+    fn annotation(&self) -> String {
+        format!("p={:04x} n={}", self.priority >> 48, self.size)
+    }
+}
+
+fn size<K, V>(link: &Link<K, V>) -> usize {
+    link.as_ref().map_or(0, |n| n.size)
+}
+
+// Merge two treaps where every node of `left` comes before every node of `right`
+fn merge<K, V>(left: Link<K, V>, right: Link<K, V>) -> Link<K, V> {
+    match (left, right) {
+        (None, right) => right,
+        (left, None) => left,
+        (Some(mut l), Some(mut r)) => {
+            if l.priority > r.priority {
+                l.right = merge(l.right.take(), Some(r));
+                l.update();
+                Some(l)
+            } else {
+                r.left = merge(Some(l), r.left.take());
+                r.update();
+                Some(r)
+            }
+        }
+    }
+}
+
+// Split into the first `k` nodes in order and the rest
+fn split_at<K, V>(link: Link<K, V>, k: usize) -> (Link<K, V>, Link<K, V>) {
+    let mut n = match link {
+        None => return (None, None),
+        Some(n) => n,
+    };
+    let left_size = size(&n.left);
+    if k <= left_size {
+        let (l, r) = split_at(n.left.take(), k);
+        n.left = r;
+        n.update();
+        (l, Some(n))
+    } else {
+        let (l, r) = split_at(n.right.take(), k - left_size - 1);
+        n.right = l;
+        n.update();
+        (Some(n), r)
+    }
+}
+
+// Split into the keys below `key`, the node holding `key` (if any) and the keys above it
+fn split_key<K: Ord, V>(link: Link<K, V>, key: &K) -> (Link<K, V>, Link<K, V>, Link<K, V>) {
+    let mut n = match link {
+        None => return (None, None, None),
+        Some(n) => n,
+    };
+    match key.cmp(&n.key) {
+        Ordering::Less => {
+            let (l, m, r) = split_key(n.left.take(), key);
+            n.left = r;
+            n.update();
+            (l, m, Some(n))
+        }
+        Ordering::Greater => {
+            let (l, m, r) = split_key(n.right.take(), key);
+            n.right = l;
+            n.update();
+            (Some(n), m, r)
+        }
+        Ordering::Equal => {
+            let (l, r) = (n.left.take(), n.right.take());
+            n.update();
+            (l, Some(n), r)
+        }
+    }
+}
+
+// Small xorshift generator for node priorities and the randomized checks below
+struct XorShift(u64);
+
+impl XorShift {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+}
+
+const DEFAULT_SEED: u64 = 0x2545F4914F6CDD1D;
+
+pub struct TreapMap<K: Ord, V> {
+    root: Link<K, V>,
+    rng: XorShift,
+}
+
+impl<K: Ord, V> Default for TreapMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Ord, V> TreapMap<K, V> {
+    pub fn new() -> Self {
+        Self::with_seed(DEFAULT_SEED)
+    }
+
+    pub fn with_seed(seed: u64) -> Self {
+        TreapMap { root: None, rng: XorShift(seed | 1) }
+    }
+
+    pub fn len(&self) -> usize {
+        size(&self.root)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    // Insert a key-value pair, returning the previous value for the key
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        if let Some(old) = self.get_mut(&key) {
+            return Some(std::mem::replace(old, value));
+        }
+        let node = Node::new(key, value, self.rng.next());
+        let (l, _, r) = split_key(self.root.take(), &node.key);
+        self.root = merge(merge(l, Some(node)), r);
+        None
+    }
+
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        let (l, m, r) = split_key(self.root.take(), key);
+        self.root = merge(l, r);
+        m.map(|n| n.value)
+    }
+
+    pub fn get(&self, key: &K) -> Option<&V> {
+        let mut current = &self.root;
+        while let Some(n) = current {
+            match key.cmp(&n.key) {
+                Ordering::Less => current = &n.left,
+                Ordering::Greater => current = &n.right,
+                Ordering::Equal => return Some(&n.value),
+            }
+        }
+        None
+    }
+
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        let mut current = &mut self.root;
+        while let Some(n) = current {
+            match key.cmp(&n.key) {
+                Ordering::Less => current = &mut n.left,
+                Ordering::Greater => current = &mut n.right,
+                Ordering::Equal => return Some(&mut n.value),
+            }
+        }
+        None
+    }
+
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.get(key).is_some()
+    }
+
+    // Keys below `key`, the value stored under `key`, and keys above it
+    pub fn split(mut self, key: &K) -> (Self, Option<V>, Self) {
+        let (l, m, r) = split_key(self.root.take(), key);
+        let right_seed = self.rng.next();
+        let left = TreapMap { root: l, rng: self.rng };
+        (left, m.map(|n| n.value), TreapMap { root: r, rng: XorShift(right_seed | 1) })
+    }
+
+    // Every key in `left` must be smaller than every key in `right`
+    pub fn merge(mut left: Self, right: Self) -> Self {
+        if let (Some((last, _)), Some((first, _))) = (left.iter().next_back(), right.iter().next()) {
+            assert!(last < first, "merge: key ranges overlap");
+        }
+        left.root = merge(left.root.take(), right.root);
+        left
+    }
+
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter::new(self.root.as_deref(), size(&self.root))
+    }
+
+    pub fn pretty(&self) -> String
+    where
+        K: Debug,
+    {
+        tree_printer::ascii(self.root.as_deref(), |n| format!("{:?}", n.key))
+    }
+
+    pub fn to_dot(&self) -> String
+    where
+        K: Debug,
+    {
+        tree_printer::dot(self.root.as_deref(), |n| format!("{:?}", n.key))
+    }
+
+    // Checks BST order, heap order on priorities and the cached subtree sizes
+    pub fn validate(&self) -> Result<(), String> {
+        Self::validate_rec(&self.root, None, None, u64::MAX).map(|_| ())
+    }
+
+    fn validate_rec(link: &Link<K, V>, lo: Option<&K>, hi: Option<&K>, max_priority: u64) -> Result<usize, String> {
+        let n = match link {
+            None => return Ok(0),
+            Some(n) => n,
+        };
+        if lo.is_some_and(|lo| n.key <= *lo) || hi.is_some_and(|hi| n.key >= *hi) {
+            return Err("BST order violated".to_string());
+        }
+        if n.priority > max_priority {
+            return Err("heap order violated".to_string());
+        }
+        let left = Self::validate_rec(&n.left, lo, Some(&n.key), n.priority)?;
+        let right = Self::validate_rec(&n.right, Some(&n.key), hi, n.priority)?;
+        if n.size != left + right + 1 {
+            return Err("stale subtree size".to_string());
+        }
+        Ok(n.size)
+    }
+}
+
+// Borrowing in-order iterator over a subtree, from both ends
+pub type Iter<'a, K, V> = tree_iter::Iter<'a, Node<K, V>>;
+
+impl<K, V> InOrderNode for Node<K, V> {
+    type Key = K;
+    type Value = V;
+
+    fn left(&self) -> Option<&Self> {
+        self.left.as_deref()
+    }
+
+    fn right(&self) -> Option<&Self> {
+        self.right.as_deref()
+    }
+
+    fn entry(&self) -> (&K, &V) {
+        (&self.key, &self.value)
+    }
+}
+
+// Implicit-key treap: a sequence ordered by position rather than by key. The
+// position of a node is the size of everything to its left, so indexing,
+// inserting or removing anywhere, cutting and concatenating are all O(log n).
+pub struct ImplicitTreap<T> {
+    root: Link<(), T>,
+    rng: XorShift,
+}
+
+impl<T> Default for ImplicitTreap<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> ImplicitTreap<T> {
+    pub fn new() -> Self {
+        Self::with_seed(DEFAULT_SEED)
+    }
+
+    pub fn with_seed(seed: u64) -> Self {
+        ImplicitTreap { root: None, rng: XorShift(seed | 1) }
+    }
+
+    pub fn len(&self) -> usize {
+        size(&self.root)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    pub fn get(&self, mut index: usize) -> Option<&T> {
+        let mut current = &self.root;
+        while let Some(n) = current {
+            let left_size = size(&n.left);
+            match index.cmp(&left_size) {
+                Ordering::Less => current = &n.left,
+                Ordering::Equal => return Some(&n.value),
+                Ordering::Greater => {
+                    index -= left_size + 1;
+                    current = &n.right;
+                }
+            }
+        }
+        None
+    }
+
+    pub fn get_mut(&mut self, mut index: usize) -> Option<&mut T> {
+        let mut current = &mut self.root;
+        while let Some(n) = current {
+            let left_size = size(&n.left);
+            match index.cmp(&left_size) {
+                Ordering::Less => current = &mut n.left,
+                Ordering::Equal => return Some(&mut n.value),
+                Ordering::Greater => {
+                    index -= left_size + 1;
+                    current = &mut n.right;
+                }
+            }
+        }
+        None
+    }
+
+    pub fn push(&mut self, value: T) {
+        let node = Node::new((), value, self.rng.next());
+        self.root = merge(self.root.take(), Some(node));
+    }
+
+    // Insert before position `index`; panics if index > len, like Vec::insert
+    pub fn insert(&mut self, index: usize, value: T) {
+        assert!(index <= self.len(), "insert index {} out of bounds (len {})", index, self.len());
+        let node = Node::new((), value, self.rng.next());
+        let (l, r) = split_at(self.root.take(), index);
+        self.root = merge(merge(l, Some(node)), r);
+    }
+
+    // Remove and return the element at `index`; panics if out of bounds, like Vec::remove
+    pub fn remove(&mut self, index: usize) -> T {
+        assert!(index < self.len(), "remove index {} out of bounds (len {})", index, self.len());
+        let (l, r) = split_at(self.root.take(), index);
+        let (m, r) = split_at(r, 1);
+        self.root = merge(l, r);
+        m.unwrap().value
+    }
+
+    // Keep [0, at) and return [at, len), like Vec::split_off
+    pub fn split_off(&mut self, at: usize) -> Self {
+        assert!(at <= self.len(), "split index {} out of bounds (len {})", at, self.len());
+        let (l, r) = split_at(self.root.take(), at);
+        self.root = l;
+        ImplicitTreap { root: r, rng: XorShift(self.rng.next() | 1) }
+    }
+
+    // Concatenate `other` onto the end
+    pub fn append(&mut self, other: Self) {
+        self.root = merge(self.root.take(), other.root);
+    }
+
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &T> + ExactSizeIterator + '_ {
+        Iter::new(self.root.as_deref(), size(&self.root)).map(|(_, value)| value)
+    }
+}
+
+impl<T> FromIterator<T> for ImplicitTreap<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut sequence = ImplicitTreap::new();
+        for value in iter {
+            sequence.push(value);
+        }
+        sequence
+    }
+}
+
+// Set of keys on top of the map, keeping the original insert/search/delete API
+pub struct Treap<T: Ord> {
+    map: TreapMap<T, ()>,
+}
+
+impl<T: Ord> Default for Treap<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Ord> Treap<T> {
+    pub fn new() -> Self {
+        Treap { map: TreapMap::new() }
+    }
+
+    pub fn with_seed(seed: u64) -> Self {
+        Treap { map: TreapMap::with_seed(seed) }
+    }
+
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    // Insert a key, returning false if it was already present
+    pub fn insert(&mut self, key: T) -> bool {
+        if self.map.contains_key(&key) {
+            return false;
+        }
+        self.map.insert(key, ());
+        true
+    }
+
+    // Delete a key, returning whether it was present
+    pub fn delete(&mut self, key: &T) -> bool {
+        self.map.remove(key).is_some()
+    }
+
+    pub fn search(&self, key: &T) -> bool {
+        self.map.contains_key(key)
+    }
+
+    pub fn inorder(&self) -> Vec<&T> {
+        self.map.iter().map(|(key, _)| key).collect()
+    }
+
+    pub fn pretty(&self) -> String
+    where
+        T: Debug,
+    {
+        self.map.pretty()
+    }
+}
+
+// Random inserts, removals, splits and merges against BTreeMap, validating every step
+fn randomized_map_check(operations: usize, seed: u64) {
+    let mut rng = XorShift(seed);
+    let mut treap = TreapMap::with_seed(seed);
+    let mut model = BTreeMap::new();
+    for step in 0..operations {
+        let key = rng.next() % 512;
+        match rng.next() % 8 {
+            0..=3 => assert_eq!(treap.insert(key, step), model.insert(key, step)),
+            4 | 5 => assert_eq!(treap.remove(&key), model.remove(&key)),
+            6 => assert_eq!(treap.get(&key), model.get(&key)),
+            _ => {
+                // Split around a key and merge the halves back
+                let (left, value, right) = treap.split(&key);
+                assert_eq!(left.len(), model.range(..key).count());
+                assert_eq!(right.len(), model.range(key + 1..).count());
+                assert_eq!(value.as_ref(), model.get(&key));
+                treap = TreapMap::merge(left, right);
+                if let Some(value) = value {
+                    treap.insert(key, value);
+                }
+            }
+        }
+        if let Err(e) = treap.validate() {
+            panic!("invariant broken at step {}: {}", step, e);
+        }
+        assert_eq!(treap.len(), model.len());
+    }
+    assert!(treap.iter().eq(model.iter()));
+    assert!(treap.iter().rev().eq(model.iter().rev()));
+}
+
+// Random positional edits, cuts and concatenations against Vec
+fn randomized_sequence_check(operations: usize, seed: u64) {
+    let mut rng = XorShift(seed);
+    let mut sequence = ImplicitTreap::with_seed(seed);
+    let mut model: Vec<usize> = Vec::new();
+    for step in 0..operations {
+        let len = model.len();
+        match rng.next() % 6 {
+            0 | 1 => {
+                let index = rng.next() as usize % (len + 1);
+                sequence.insert(index, step);
+                model.insert(index, step);
+            }
+            2 if len > 0 => {
+                let index = rng.next() as usize % len;
+                assert_eq!(sequence.remove(index), model.remove(index));
+            }
+            3 if len > 0 => {
+                let index = rng.next() as usize % len;
+                *sequence.get_mut(index).unwrap() += 1;
+                model[index] += 1;
+            }
+            4 => {
+                // Move a random block to the front: cut twice, concatenate in a new order
+                let a = rng.next() as usize % (len + 1);
+                let b = a + rng.next() as usize % (len - a + 1);
+                let mut middle = sequence.split_off(a);
+                let tail = middle.split_off(b - a);
+                middle.append(std::mem::take(&mut sequence));
+                middle.append(tail);
+                sequence = middle;
+                let tail = model.split_off(b);
+                let mut rotated = model.split_off(a);
+                rotated.append(&mut model);
+                rotated.extend(tail);
+                model = rotated;
+            }
+            _ => {
+                sequence.push(step);
+                model.push(step);
+            }
+        }
+        assert_eq!(sequence.len(), model.len());
+        if !model.is_empty() {
+            let index = rng.next() as usize % model.len();
+            assert_eq!(sequence.get(index), model.get(index));
+        }
+        assert_eq!(sequence.get(model.len()), None);
+    }
+    assert!(sequence.iter().eq(model.iter()));
+    assert!(sequence.iter().rev().eq(model.iter().rev()));
+}
+
+fn main() {
+    let mut treap = Treap::new();
+    for key in [5, 2, 7, 1, 9] {
+        treap.insert(key);
+    }
+
+    println!("Search for 7: {}", treap.search(&7));
+    println!("Search for 3: {}", treap.search(&3));
+
+    treap.delete(&7);
+    println!("After deleting 7: {:?} ({} keys)", treap.inorder(), treap.len());
+    print!("Shape, with priorities heap-ordered from the root down:\n{}", treap.pretty());
+
+    // Key/value map with split and merge
+    let mut ages = TreapMap::new();
+    for (name, age) in [("carol", 41), ("alice", 30), ("erin", 25), ("bob", 35), ("dave", 52)] {
+        ages.insert(name, age);
+    }
+    let (before, found, after) = ages.split(&"carol");
+    println!("Split at carol: {:?} / {:?} / {:?}", before.iter().collect::<Vec<_>>(), found, after.iter().collect::<Vec<_>>());
+    let ages = TreapMap::merge(before, after);
+    println!("Merged back without carol: {:?}", ages.iter().map(|(name, _)| name).collect::<Vec<_>>());
+
+    // Implicit keys: a text buffer edited by position
+    let mut text: ImplicitTreap<char> = "hello world".chars().collect();
+    let world = text.split_off(6);
+    text.insert(0, ' ');
+    let mut edited = world;
+    edited.append(text);
+    edited.remove(edited.len() - 1);
+    println!("Rearranged: {:?}", edited.iter().collect::<String>());
+
+    randomized_map_check(20_000, 0x853C49E6748FEA9B);
+    randomized_sequence_check(20_000, 0xDA942042E4DD58B5);
+    println!("Randomized checks passed: map and sequence matched BTreeMap and Vec");
+}
@@ -0,0 +1,298 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::panic;
+
+// Tree printer: ASCII drawings and Graphviz DOT export for the trees in this
+// repository, plus a recorder that captures a subtree before and after every
+// rotation. The tree files pull it in as a module:
+//
+//     #[path = "rust-tree-printer.rs"]
+//     mod tree_printer;
+//
+// Rotations run deep inside generic code where keys are not known to be Debug,
+// so the recorder snapshots only the shape and the key-independent details of
+// each node (height, balance factor, color), identified by node address. The
+// tree fills in the key text afterwards from the trees before and after the
+// whole operation; boxed nodes keep their address while they are rotated.
+
+pub trait TreeShape {
+    // Child slots in order; None marks an empty slot of a binary node
+    fn children(&self) -> Vec<Option<&Self>>;
+
+    // Details shown after the key, such as height and balance factor or color
+    fn annotation(&self) -> String;
+
+    // Extra Graphviz attributes for the node, such as `color=red`
+    fn dot_attributes(&self) -> String {
+        String::new()
+    }
+}
+
+fn label<N: TreeShape>(node: &N, key: &impl Fn(&N) -> String) -> String {
+    let annotation = node.annotation();
+    if annotation.is_empty() {
+        key(node)
+    } else {
+        format!("{} [{}]", key(node), annotation)
+    }
+}
+
+// Draw the tree top to bottom, one node per line:
+//
+//     8 [h=3 bf=0]
+//     |-- L: 3 [h=2 bf=0]
+//     |   |-- L: 1 [h=1 bf=0]
+//     |   `-- R: 6 [h=1 bf=0]
+//     `-- R: 10 [h=1 bf=0]
+//
+// Binary nodes tag their children L and R, wider nodes number them.
+pub fn ascii<N: TreeShape>(root: Option<&N>, key: impl Fn(&N) -> String) -> String {
+    let mut out = String::new();
+    match root {
+        None => out.push_str("(empty)\n"),
+        Some(root) => {
+            writeln!(out, "{}", label(root, &key)).unwrap();
+            ascii_children(root, &key, "", &mut out);
+        }
+    }
+    out
+}
+
+fn ascii_children<N: TreeShape>(node: &N, key: &impl Fn(&N) -> String, prefix: &str, out: &mut String) {
+    let children = node.children();
+    if children.iter().all(Option::is_none) {
+        return;
+    }
+    for (i, child) in children.iter().enumerate() {
+        let tag = match (children.len(), i) {
+            (2, 0) => "L".to_string(),
+            (2, _) => "R".to_string(),
+            _ => i.to_string(),
+        };
+        let (branch, indent) = if i + 1 == children.len() { ("`-- ", "    ") } else { ("|-- ", "|   ") };
+        match child {
+            None => writeln!(out, "{}{}{}: .", prefix, branch, tag).unwrap(),
+            Some(child) => {
+                writeln!(out, "{}{}{}: {}", prefix, branch, tag, label(*child, key)).unwrap();
+                ascii_children(*child, key, &format!("{}{}", prefix, indent), out);
+            }
+        }
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+// Graphviz DOT source; render with `dot -Tsvg tree.dot -o tree.svg`. Empty
+// slots of binary nodes become small points so left and right stay apart.
+pub fn dot<N: TreeShape>(root: Option<&N>, key: impl Fn(&N) -> String) -> String {
+    let mut out = String::from("digraph tree {\n    node [shape=box, fontname=\"monospace\"];\n");
+    if let Some(root) = root {
+        let mut next_id = 0;
+        dot_node(root, &key, &mut next_id, &mut out);
+    }
+    out.push_str("}\n");
+    out
+}
+
+// Emits the subtree and returns the id of its root
+fn dot_node<N: TreeShape>(node: &N, key: &impl Fn(&N) -> String, next_id: &mut usize, out: &mut String) -> usize {
+    let id = *next_id;
+    *next_id += 1;
+    let attributes = node.dot_attributes();
+    let separator = if attributes.is_empty() { "" } else { ", " };
+    writeln!(out, "    n{} [label=\"{}\"{}{}];", id, escape(&label(node, key)), separator, attributes).unwrap();
+    let children = node.children();
+    if children.iter().all(Option::is_none) {
+        return id;
+    }
+    for child in children {
+        match child {
+            None => {
+                let nil = *next_id;
+                *next_id += 1;
+                writeln!(out, "    n{} [shape=point];", nil).unwrap();
+                writeln!(out, "    n{} -> n{};", id, nil).unwrap();
+            }
+            Some(child) => {
+                let child_id = dot_node(child, key, next_id, out);
+                writeln!(out, "    n{} -> n{};", id, child_id).unwrap();
+            }
+        }
+    }
+    id
+}
+
+pub fn node_id<N>(node: &N) -> usize {
+    node as *const N as usize
+}
+
+// Shape of a subtree with key-independent details, as captured mid-rotation
+pub struct Snapshot {
+    id: usize,
+    annotation: String,
+    children: Vec<Option<Snapshot>>,
+}
+
+impl TreeShape for Snapshot {
+    fn children(&self) -> Vec<Option<&Self>> {
+        self.children.iter().map(Option::as_ref).collect()
+    }
+
+    fn annotation(&self) -> String {
+        self.annotation.clone()
+    }
+}
+
+fn snapshot<N: TreeShape>(node: &N) -> Snapshot {
+    Snapshot {
+        id: node_id(node),
+        annotation: node.annotation(),
+        children: node.children().into_iter().map(|child| child.map(snapshot)).collect(),
+    }
+}
+
+pub struct Rotation {
+    pub name: &'static str,
+    pub before: Snapshot,
+    pub after: Snapshot,
+}
+
+thread_local! {
+    static RECORDING: RefCell<Option<Vec<Rotation>>> = const { RefCell::new(None) };
+}
+
+// Puts back the recording that was active before `record` when dropped, so a
+// panicking operation caught further up does not leave recording switched on
+struct RestoreRecording(Option<Vec<Rotation>>);
+
+impl Drop for RestoreRecording {
+    fn drop(&mut self) {
+        let outer = self.0.take();
+        RECORDING.with(|recording| recording.replace(outer));
+    }
+}
+
+// Run `operation` with recording switched on; returns its result and the rotations it made
+pub fn record<R>(operation: impl FnOnce() -> R) -> (R, Vec<Rotation>) {
+    let _restore = RestoreRecording(RECORDING.with(|recording| recording.replace(Some(Vec::new()))));
+    let result = operation();
+    let rotations = RECORDING.with(|recording| recording.take()).unwrap_or_default();
+    (result, rotations)
+}
+
+// Called by a rotation before it starts; costs one thread-local check when not recording
+pub fn before_rotation<N: TreeShape>(node: &N) -> Option<Snapshot> {
+    RECORDING.with(|recording| recording.borrow().is_some()).then(|| snapshot(node))
+}
+
+// Called by a rotation with the new subtree root once it is done
+pub fn after_rotation<N: TreeShape>(name: &'static str, before: Option<Snapshot>, node: &N) {
+    if let Some(before) = before {
+        let after = snapshot(node);
+        RECORDING.with(|recording| {
+            if let Some(rotations) = recording.borrow_mut().as_mut() {
+                rotations.push(Rotation { name, before, after });
+            }
+        });
+    }
+}
+
+// Map node addresses to key text, to be combined with recorded snapshots
+pub fn collect_keys<N: TreeShape>(root: Option<&N>, key: &impl Fn(&N) -> String, keys: &mut HashMap<usize, String>) {
+    if let Some(node) = root {
+        keys.insert(node_id(node), key(node));
+        for child in node.children() {
+            collect_keys(child, key, keys);
+        }
+    }
+}
+
+// One block of text per rotation: its name, then the subtree before and after
+pub fn describe(rotations: &[Rotation], keys: &HashMap<usize, String>) -> Vec<String> {
+    let key = |snapshot: &Snapshot| keys.get(&snapshot.id).cloned().unwrap_or_else(|| "?".to_string());
+    rotations
+        .iter()
+        .map(|rotation| {
+            format!(
+                "{}\nbefore:\n{}after:\n{}",
+                rotation.name,
+                ascii(Some(&rotation.before), key),
+                ascii(Some(&rotation.after), key)
+            )
+        })
+        .collect()
+}
+
+// A bare binary search tree to show the printer on its own
+struct DemoNode {
+    key: i32,
+    left: Option<Box<DemoNode>>,
+    right: Option<Box<DemoNode>>,
+}
+
+impl TreeShape for DemoNode {
+    fn children(&self) -> Vec<Option<&Self>> {
+        vec![self.left.as_deref(), self.right.as_deref()]
+    }
+
+    fn annotation(&self) -> String {
+        String::new()
+    }
+}
+
+fn demo_insert(node: Option<Box<DemoNode>>, key: i32) -> Option<Box<DemoNode>> {
+    match node {
+        None => Some(Box::new(DemoNode { key, left: None, right: None })),
+        Some(mut n) => {
+            if key < n.key {
+                n.left = demo_insert(n.left.take(), key);
+            } else {
+                n.right = demo_insert(n.right.take(), key);
+            }
+            Some(n)
+        }
+    }
+}
+
+fn demo_rotate_left(mut node: Box<DemoNode>) -> Box<DemoNode> {
+    let before = before_rotation(&*node);
+    let mut new_root = node.right.take().unwrap();
+    node.right = new_root.left.take();
+    new_root.left = Some(node);
+    after_rotation("rotate_left", before, &*new_root);
+    new_root
+}
+
+fn main() {
+    let mut root = None;
+    for key in [4, 2, 6, 1, 3, 5, 7] {
+        root = demo_insert(root, key);
+    }
+    let key = |node: &DemoNode| node.key.to_string();
+    print!("{}", ascii(root.as_deref(), key));
+    print!("{}", dot(root.as_deref(), key));
+
+    let mut keys = HashMap::new();
+    collect_keys(root.as_deref(), &key, &mut keys);
+    let (root, rotations) = record(|| demo_rotate_left(root.unwrap()));
+    collect_keys(Some(&*root), &key, &mut keys);
+    for step in describe(&rotations, &keys) {
+        print!("{}", step);
+    }
+
+    // A panic inside `record`, caught by the caller, must switch recording back
+    // off, and a nested one must leave the enclosing recording running
+    let hook = panic::take_hook();
+    panic::set_hook(Box::new(|_| {}));
+    assert!(panic::catch_unwind(|| record(|| panic!("operation failed"))).is_err());
+    assert!(before_rotation(&*root).is_none(), "recording stayed on after a panic");
+    let (_, rotations) = record(|| {
+        assert!(panic::catch_unwind(|| record(|| panic!("inner operation failed"))).is_err());
+        demo_rotate_left(root)
+    });
+    panic::set_hook(hook);
+    assert_eq!(rotations.len(), 1, "the outer recording was lost");
+}
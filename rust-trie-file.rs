@@ -0,0 +1,416 @@
+use std::collections::{HashMap, VecDeque};
+use std::io;
+
+// Trie file: a compact binary image of a `Trie` that is written once and then
+// queried in place. Loading checks the header and checksum and walks the node
+// table once to validate it; after that every lookup reads straight from the
+// byte slice, so the bytes can come from a memory map (e.g. the memmap2 crate)
+// or a single `std::fs::read` without allocating anything per node.
+//
+//     rustc -O --edition 2021 rust-trie-file.rs
+//     ./rust-trie-file build words.txt words.trie
+//     ./rust-trie-file lookup words.trie pre
+//
+// Without arguments it runs the demo and the round-trip checks.
+
+#[path = "rust-trie.rs"]
+#[allow(dead_code)]
+mod trie;
+
+use trie::Trie;
+
+const MAGIC: &[u8; 8] = b"RUSTTRIE";
+const FORMAT_VERSION: u32 = 1;
+
+// File layout (little endian):
+//   header, 32 bytes:
+//     0: magic, 8: format version u32, 12: node count u32, 16: edge count u32,
+//     20: word count u32, 24: CRC-32 of every other byte of the file,
+//     28: reserved u32 (zero)
+//   nodes, 16 bytes each, root first, breadth first:
+//     0: first edge u32, 4: edge count u32 (top bit set if a word ends here), 8: weight u64
+//   edges, 8 bytes each, every node's edges contiguous and sorted by character:
+//     0: character u32, 4: child node u32
+// Breadth-first numbering puts every child after its parent, which the loader
+// checks so that no walk over a validated file can loop.
+const HEADER_SIZE: usize = 32;
+const NODE_SIZE: usize = 16;
+const EDGE_SIZE: usize = 8;
+const END_OF_WORD: u32 = 1 << 31;
+
+// 1. CRC-32 (IEEE) for the file checksum
+const CRC_TABLE: [u32; 256] = crc_table();
+
+const fn crc_table() -> [u32; 256] {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 == 1 { (crc >> 1) ^ 0xEDB8_8320 } else { crc >> 1 };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+}
+
+fn crc32(parts: &[&[u8]]) -> u32 {
+    let mut crc = !0u32;
+    for part in parts {
+        for &byte in *part {
+            crc = CRC_TABLE[((crc ^ byte as u32) & 0xFF) as usize] ^ (crc >> 8);
+        }
+    }
+    !crc
+}
+
+fn get_u32(buf: &[u8], at: usize) -> u32 {
+    u32::from_le_bytes(buf[at..at + 4].try_into().unwrap())
+}
+
+fn get_u64(buf: &[u8], at: usize) -> u64 {
+    u64::from_le_bytes(buf[at..at + 8].try_into().unwrap())
+}
+
+fn put_u32(buf: &mut [u8], at: usize, value: u32) {
+    buf[at..at + 4].copy_from_slice(&value.to_le_bytes());
+}
+
+fn corrupt(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+// 2. Writing: lay the words out breadth first from the sorted word list. The
+// words below a node are a contiguous run of that list, and so are the words
+// below each of its children.
+pub fn encode<V>(trie: &Trie<V>) -> Vec<u8> {
+    let entries: Vec<(Vec<char>, u64)> = trie
+        .find_words_with_prefix("")
+        .into_iter()
+        .map(|word| {
+            let weight = trie.weight(&word).unwrap();
+            (word.chars().collect(), weight)
+        })
+        .collect();
+
+    let mut nodes = Vec::new();
+    let mut edges = Vec::new();
+    let mut node_count: usize = 1;
+    let mut pending = VecDeque::from([(0, entries.len(), 0)]);
+    while let Some((start, end, depth)) = pending.pop_front() {
+        let (mut first, mut flags, mut weight) = (start, 0, 0);
+        if first < end && entries[first].0.len() == depth {
+            flags = END_OF_WORD;
+            weight = entries[first].1;
+            first += 1;
+        }
+        let first_edge = edges.len() / EDGE_SIZE;
+        while first < end {
+            let ch = entries[first].0[depth];
+            let last = first + entries[first..end].partition_point(|(word, _)| word[depth] == ch);
+            edges.extend_from_slice(&(ch as u32).to_le_bytes());
+            edges.extend_from_slice(&(node_count as u32).to_le_bytes());
+            pending.push_back((first, last, depth + 1));
+            node_count += 1;
+            first = last;
+        }
+        let edge_count = edges.len() / EDGE_SIZE - first_edge;
+        nodes.extend_from_slice(&(first_edge as u32).to_le_bytes());
+        nodes.extend_from_slice(&(edge_count as u32 | flags).to_le_bytes());
+        nodes.extend_from_slice(&weight.to_le_bytes());
+    }
+    assert!(node_count < END_OF_WORD as usize, "too many nodes for the trie file format");
+
+    let mut bytes = vec![0; HEADER_SIZE];
+    bytes[..8].copy_from_slice(MAGIC);
+    put_u32(&mut bytes, 8, FORMAT_VERSION);
+    put_u32(&mut bytes, 12, node_count as u32);
+    put_u32(&mut bytes, 16, (edges.len() / EDGE_SIZE) as u32);
+    put_u32(&mut bytes, 20, entries.len() as u32);
+    bytes.extend_from_slice(&nodes);
+    bytes.extend_from_slice(&edges);
+    let crc = crc32(&[&bytes[..24], &bytes[28..]]);
+    put_u32(&mut bytes, 24, crc);
+    bytes
+}
+
+// 3. Reading: a validated view over the file's bytes
+#[derive(Clone, Copy)]
+pub struct TrieView<'a> {
+    bytes: &'a [u8],
+    node_count: usize,
+    edge_count: usize,
+    word_count: usize,
+}
+
+impl<'a> TrieView<'a> {
+    pub fn new(bytes: &'a [u8]) -> io::Result<Self> {
+        if bytes.len() < HEADER_SIZE || &bytes[..8] != MAGIC {
+            return Err(corrupt("not a trie file".to_string()));
+        }
+        let version = get_u32(bytes, 8);
+        if version != FORMAT_VERSION {
+            return Err(corrupt(format!("unsupported trie file format version {}", version)));
+        }
+        let view = TrieView {
+            bytes,
+            node_count: get_u32(bytes, 12) as usize,
+            edge_count: get_u32(bytes, 16) as usize,
+            word_count: get_u32(bytes, 20) as usize,
+        };
+        let expected = HEADER_SIZE as u64 + view.node_count as u64 * NODE_SIZE as u64 + view.edge_count as u64 * EDGE_SIZE as u64;
+        if bytes.len() as u64 != expected {
+            return Err(corrupt(format!("trie file is {} bytes, its header says {}", bytes.len(), expected)));
+        }
+        if crc32(&[&bytes[..24], &bytes[28..]]) != get_u32(bytes, 24) {
+            return Err(corrupt("trie file checksum mismatch".to_string()));
+        }
+        view.validate()?;
+        Ok(view)
+    }
+
+    // The checksum catches damage, not a writer with a bug, so check the structure
+    // the lookups rely on as well
+    fn validate(&self) -> io::Result<()> {
+        if self.node_count == 0 {
+            return Err(corrupt("trie file has no root node".to_string()));
+        }
+        let mut words = 0;
+        for node in 0..self.node_count {
+            let (first, count, is_end, _) = self.node(node);
+            words += usize::from(is_end);
+            if first + count > self.edge_count {
+                return Err(corrupt(format!("node {} has edges past the end of the edge table", node)));
+            }
+            let mut previous = None;
+            for edge in first..first + count {
+                let (ch, child) = self.edge(edge);
+                let ch = char::from_u32(ch).ok_or_else(|| corrupt(format!("edge {} has invalid character {:#x}", edge, ch)))?;
+                if previous.is_some_and(|previous| previous >= ch) {
+                    return Err(corrupt(format!("edges of node {} are not sorted", node)));
+                }
+                if child <= node || child >= self.node_count {
+                    return Err(corrupt(format!("edge {} points to node {}", edge, child)));
+                }
+                previous = Some(ch);
+            }
+        }
+        if words != self.word_count {
+            return Err(corrupt(format!("trie file holds {} words, its header says {}", words, self.word_count)));
+        }
+        Ok(())
+    }
+
+    // (first edge, edge count, is end of word, weight)
+    fn node(&self, node: usize) -> (usize, usize, bool, u64) {
+        let at = HEADER_SIZE + node * NODE_SIZE;
+        let count = get_u32(self.bytes, at + 4);
+        (get_u32(self.bytes, at) as usize, (count & !END_OF_WORD) as usize, count & END_OF_WORD != 0, get_u64(self.bytes, at + 8))
+    }
+
+    // (character, child node)
+    fn edge(&self, edge: usize) -> (u32, usize) {
+        let at = HEADER_SIZE + self.node_count * NODE_SIZE + edge * EDGE_SIZE;
+        (get_u32(self.bytes, at), get_u32(self.bytes, at + 4) as usize)
+    }
+
+    // Binary search over the node's sorted edges
+    fn child(&self, node: usize, ch: char) -> Option<usize> {
+        let (first, count, _, _) = self.node(node);
+        let (mut low, mut high) = (first, first + count);
+        while low < high {
+            let mid = low + (high - low) / 2;
+            let (edge_char, child) = self.edge(mid);
+            match edge_char.cmp(&(ch as u32)) {
+                std::cmp::Ordering::Less => low = mid + 1,
+                std::cmp::Ordering::Greater => high = mid,
+                std::cmp::Ordering::Equal => return Some(child),
+            }
+        }
+        None
+    }
+
+    fn find_node(&self, prefix: &str) -> Option<usize> {
+        prefix.chars().try_fold(0, |node, ch| self.child(node, ch))
+    }
+
+    pub fn len(&self) -> usize {
+        self.word_count
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.word_count == 0
+    }
+
+    pub fn search(&self, word: &str) -> bool {
+        self.weight(word).is_some()
+    }
+
+    pub fn starts_with(&self, prefix: &str) -> bool {
+        self.find_node(prefix).is_some()
+    }
+
+    pub fn weight(&self, word: &str) -> Option<u64> {
+        let (_, _, is_end, weight) = self.node(self.find_node(word)?);
+        is_end.then_some(weight)
+    }
+
+    // Every word starting with `prefix`, in alphabetical order
+    pub fn find_words_with_prefix(&self, prefix: &str) -> Vec<String> {
+        let mut result = Vec::new();
+        if let Some(node) = self.find_node(prefix) {
+            self.dfs(node, &mut String::from(prefix), &mut result);
+        }
+        result
+    }
+
+    fn dfs(&self, node: usize, current_word: &mut String, result: &mut Vec<String>) {
+        let (first, count, is_end, _) = self.node(node);
+        if is_end {
+            result.push(current_word.clone());
+        }
+        for edge in first..first + count {
+            let (ch, child) = self.edge(edge);
+            // Validated when the view was created
+            current_word.push(char::from_u32(ch).unwrap());
+            self.dfs(child, current_word, result);
+            current_word.pop();
+        }
+    }
+}
+
+// 4. Command line: repeated words in the list add up to their weight
+fn build_file(words_path: &str, trie_path: &str) -> io::Result<(usize, usize)> {
+    let text = std::fs::read_to_string(words_path)?;
+    let mut trie = Trie::new();
+    for line in text.lines() {
+        let word = line.trim();
+        if !word.is_empty() {
+            trie.increment(word, 1);
+        }
+    }
+    let bytes = encode(&trie);
+    std::fs::write(trie_path, &bytes)?;
+    Ok((trie.len(), bytes.len()))
+}
+
+fn lookup_file(trie_path: &str, prefix: &str) -> io::Result<Vec<(String, u64)>> {
+    let bytes = std::fs::read(trie_path)?;
+    let view = TrieView::new(&bytes)?;
+    Ok(view.find_words_with_prefix(prefix).into_iter().map(|word| (word.clone(), view.weight(&word).unwrap())).collect())
+}
+
+// Small xorshift generator for the checks below
+struct XorShift(u64);
+
+impl XorShift {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+}
+
+// Encode random tries, read them back through a view and compare every lookup;
+// then damage the bytes and check that loading refuses them
+fn round_trip_check(rounds: usize, seed: u64) {
+    let mut rng = XorShift(seed);
+    let alphabet = ['a', 'b', 'c', 'é', 'ж', '中', '😀'];
+    let random_word = |rng: &mut XorShift| -> String {
+        let len = rng.next() % 6;
+        (0..len).map(|_| alphabet[(rng.next() % alphabet.len() as u64) as usize]).collect()
+    };
+    for _ in 0..rounds {
+        let mut trie = Trie::new();
+        let mut model: HashMap<String, u64> = HashMap::new();
+        for _ in 0..rng.next() % 200 {
+            let word = random_word(&mut rng);
+            let weight = rng.next() % 1_000;
+            trie.insert_with(&word, weight, ());
+            model.insert(word, weight);
+        }
+        let bytes = encode(&trie);
+        let view = TrieView::new(&bytes).unwrap();
+        assert_eq!(view.len(), model.len());
+        for (word, weight) in &model {
+            assert_eq!(view.weight(word), Some(*weight));
+        }
+        for _ in 0..50 {
+            let probe = random_word(&mut rng);
+            assert_eq!(view.weight(&probe), model.get(&probe).copied());
+            assert_eq!(view.starts_with(&probe), trie.starts_with(&probe));
+            assert_eq!(view.find_words_with_prefix(&probe), trie.find_words_with_prefix(&probe));
+        }
+
+        // Rebuilding from the view gives the same bytes
+        let mut rebuilt = Trie::new();
+        for word in view.find_words_with_prefix("") {
+            rebuilt.insert_with(&word, view.weight(&word).unwrap(), ());
+        }
+        assert_eq!(encode(&rebuilt), bytes);
+
+        // CRC-32 catches every single-byte change
+        let mut damaged = bytes.clone();
+        let at = (rng.next() % damaged.len() as u64) as usize;
+        damaged[at] ^= 1 + (rng.next() % 255) as u8;
+        assert!(TrieView::new(&damaged).is_err(), "flipped byte {} went unnoticed", at);
+        let cut = (rng.next() % bytes.len() as u64) as usize;
+        assert!(TrieView::new(&bytes[..cut]).is_err(), "truncated file accepted");
+    }
+
+    let mut newer = encode(&Trie::new());
+    put_u32(&mut newer, 8, FORMAT_VERSION + 1);
+    let error = TrieView::new(&newer).err().unwrap();
+    assert!(error.to_string().contains("version"), "{}", error);
+}
+
+fn usage() -> ! {
+    eprintln!("usage: rust-trie-file build <words.txt> <out.trie>");
+    eprintln!("       rust-trie-file lookup <file.trie> <prefix>");
+    std::process::exit(2);
+}
+
+fn main() -> io::Result<()> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match args.iter().map(String::as_str).collect::<Vec<_>>().as_slice() {
+        ["build", words, out] => {
+            let (words, bytes) = build_file(words, out)?;
+            println!("Wrote {} words in {} bytes to {}", words, bytes, out);
+            return Ok(());
+        }
+        ["lookup", file, prefix] => {
+            for (word, weight) in lookup_file(file, prefix)? {
+                println!("{}\t{}", word, weight);
+            }
+            return Ok(());
+        }
+        [] => {}
+        _ => usage(),
+    }
+
+    // Build a file from a word list in the temp directory and query it
+    let dir = std::env::temp_dir();
+    let words_path = dir.join(format!("rust-trie-file-{}.txt", std::process::id()));
+    let trie_path = dir.join(format!("rust-trie-file-{}.trie", std::process::id()));
+    std::fs::write(&words_path, "apple\napp\napply\nbanana\nbath\nbat\napp\nbâtir\napp\n")?;
+    let (words, bytes) = build_file(words_path.to_str().unwrap(), trie_path.to_str().unwrap())?;
+    println!("Wrote {} words in {} bytes", words, bytes);
+    println!("Words with prefix 'app': {:?}", lookup_file(trie_path.to_str().unwrap(), "app")?);
+
+    let file = std::fs::read(&trie_path)?;
+    let view = TrieView::new(&file)?;
+    println!("Search 'bat': {}", view.search("bat")); // true
+    println!("Search 'ba': {}", view.search("ba")); // false
+    println!("Starts with 'ba': {}", view.starts_with("ba")); // true
+    println!("Words with prefix 'b': {:?}", view.find_words_with_prefix("b"));
+    std::fs::remove_file(&words_path)?;
+    std::fs::remove_file(&trie_path)?;
+
+    round_trip_check(300, 0x2545F4914F6CDD1D);
+    println!("Round-trip check passed: views matched their tries and rejected damaged files");
+    Ok(())
+}
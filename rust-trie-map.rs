@@ -0,0 +1,582 @@
+use std::borrow::Borrow;
+use std::collections::{btree_map, BTreeMap};
+
+// TrieMap: a trie keyed by any sequence of ordered symbols rather than the
+// characters of a string. Bytes give a byte-string map, u32 token ids an n-gram
+// table, and path segments a router:
+//
+//     map.insert(b"\x89PNG".iter().copied(), "png");
+//     map.get(b"\x89PNG\r\n");
+//     routes.insert(["api", "users"].map(String::from), handler);
+//     routes.get(["api", "users"]);
+//
+// Keys go in as owned symbols, and lookups take any iterator of borrowed
+// symbols (`&u8`, `&u32`, `&str` for String), like the Borrow lookups of
+// BTreeMap. Children are kept in a BTreeMap so iteration comes out in key order.
+
+struct Node<S, V> {
+    children: BTreeMap<S, Node<S, V>>,
+    value: Option<V>,
+}
+
+impl<S, V> Node<S, V> {
+    fn new() -> Self {
+        Node {
+            children: BTreeMap::new(),
+            value: None,
+        }
+    }
+}
+
+pub struct TrieMap<S, V> {
+    root: Node<S, V>,
+    len: usize,
+}
+
+impl<S: Ord, V> Default for TrieMap<S, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S: Ord, V> TrieMap<S, V> {
+    pub fn new() -> Self {
+        TrieMap {
+            root: Node::new(),
+            len: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    // Returns the previous value of the key, if any
+    pub fn insert(&mut self, key: impl IntoIterator<Item = S>, value: V) -> Option<V> {
+        let mut node = &mut self.root;
+        for symbol in key {
+            node = node.children.entry(symbol).or_insert_with(Node::new);
+        }
+        let previous = node.value.replace(value);
+        if previous.is_none() {
+            self.len += 1;
+        }
+        previous
+    }
+
+    fn find_node<'k, Q>(&self, key: impl IntoIterator<Item = &'k Q>) -> Option<&Node<S, V>>
+    where
+        S: Borrow<Q>,
+        Q: Ord + ?Sized + 'k,
+    {
+        key.into_iter().try_fold(&self.root, |node, symbol| node.children.get(symbol))
+    }
+
+    pub fn get<'k, Q>(&self, key: impl IntoIterator<Item = &'k Q>) -> Option<&V>
+    where
+        S: Borrow<Q>,
+        Q: Ord + ?Sized + 'k,
+    {
+        self.find_node(key)?.value.as_ref()
+    }
+
+    pub fn get_mut<'k, Q>(&mut self, key: impl IntoIterator<Item = &'k Q>) -> Option<&mut V>
+    where
+        S: Borrow<Q>,
+        Q: Ord + ?Sized + 'k,
+    {
+        key.into_iter().try_fold(&mut self.root, |node, symbol| node.children.get_mut(symbol))?.value.as_mut()
+    }
+
+    pub fn contains_key<'k, Q>(&self, key: impl IntoIterator<Item = &'k Q>) -> bool
+    where
+        S: Borrow<Q>,
+        Q: Ord + ?Sized + 'k,
+    {
+        self.get(key).is_some()
+    }
+
+    // Whether any key starts with `prefix`
+    pub fn contains_prefix<'k, Q>(&self, prefix: impl IntoIterator<Item = &'k Q>) -> bool
+    where
+        S: Borrow<Q>,
+        Q: Ord + ?Sized + 'k,
+    {
+        // Only the root can be a node without values below it
+        self.find_node(prefix).is_some_and(|node| node.value.is_some() || !node.children.is_empty())
+    }
+
+    // Removes the key, pruning the nodes that no longer lead to any value
+    pub fn remove<'k, Q>(&mut self, key: impl IntoIterator<Item = &'k Q>) -> Option<V>
+    where
+        S: Borrow<Q>,
+        Q: Ord + ?Sized + 'k,
+    {
+        let removed = Self::remove_recursive(&mut self.root, key.into_iter());
+        if removed.is_some() {
+            self.len -= 1;
+        }
+        removed
+    }
+
+    fn remove_recursive<'k, Q>(node: &mut Node<S, V>, mut key: impl Iterator<Item = &'k Q>) -> Option<V>
+    where
+        S: Borrow<Q>,
+        Q: Ord + ?Sized + 'k,
+    {
+        match key.next() {
+            None => node.value.take(),
+            Some(symbol) => {
+                let child = node.children.get_mut(symbol)?;
+                let removed = Self::remove_recursive(child, key)?;
+                if child.value.is_none() && child.children.is_empty() {
+                    node.children.remove(symbol);
+                }
+                Some(removed)
+            }
+        }
+    }
+
+    // The longest key that is a prefix of `key` (possibly all of it), as its
+    // length in symbols and its value
+    pub fn longest_prefix<'k, Q>(&self, key: impl IntoIterator<Item = &'k Q>) -> Option<(usize, &V)>
+    where
+        S: Borrow<Q>,
+        Q: Ord + ?Sized + 'k,
+    {
+        let mut node = &self.root;
+        let mut longest = node.value.as_ref().map(|value| (0, value));
+        for (i, symbol) in key.into_iter().enumerate() {
+            node = match node.children.get(symbol) {
+                None => break,
+                Some(child) => child,
+            };
+            if let Some(value) = &node.value {
+                longest = Some((i + 1, value));
+            }
+        }
+        longest
+    }
+
+    pub fn entry(&mut self, key: impl IntoIterator<Item = S>) -> Entry<'_, S, V> {
+        let key: Vec<S> = key.into_iter().collect();
+        if self.contains_key(&key) {
+            Entry::Occupied(OccupiedEntry { map: self, key })
+        } else {
+            Entry::Vacant(VacantEntry { map: self, key })
+        }
+    }
+
+    // The part of the trie below `prefix`, for walks the other methods don't cover
+    pub fn subtrie<'k, Q>(&self, prefix: impl IntoIterator<Item = &'k Q>) -> Option<SubTrie<'_, S, V>>
+    where
+        S: Borrow<Q>,
+        Q: Ord + ?Sized + 'k,
+    {
+        self.find_node(prefix).map(|node| SubTrie { node })
+    }
+
+    // Every key and value in key order
+    pub fn iter(&self) -> Iter<'_, S, V>
+    where
+        S: Clone,
+    {
+        Iter::new(&self.root, Vec::new())
+    }
+
+    // Every key starting with `prefix` and its value, in key order
+    pub fn iter_prefix(&self, prefix: impl IntoIterator<Item = S>) -> Iter<'_, S, V>
+    where
+        S: Clone,
+    {
+        let prefix: Vec<S> = prefix.into_iter().collect();
+        match self.find_node(&prefix) {
+            None => Iter { key: prefix, stack: Vec::new(), pending: None },
+            Some(node) => Iter::new(node, prefix),
+        }
+    }
+}
+
+impl<S: Ord, V> FromIterator<(Vec<S>, V)> for TrieMap<S, V> {
+    fn from_iter<I: IntoIterator<Item = (Vec<S>, V)>>(iter: I) -> Self {
+        let mut map = TrieMap::new();
+        for (key, value) in iter {
+            map.insert(key, value);
+        }
+        map
+    }
+}
+
+// Depth-first walk that keeps the current key and, per level, the children still
+// to visit; a node's own value comes before its children's
+pub struct Iter<'a, S, V> {
+    key: Vec<S>,
+    stack: Vec<btree_map::Iter<'a, S, Node<S, V>>>,
+    pending: Option<&'a V>,
+}
+
+impl<'a, S, V> Iter<'a, S, V> {
+    fn new(node: &'a Node<S, V>, key: Vec<S>) -> Self {
+        Iter {
+            key,
+            stack: vec![node.children.iter()],
+            pending: node.value.as_ref(),
+        }
+    }
+}
+
+impl<'a, S: Clone, V> Iterator for Iter<'a, S, V> {
+    type Item = (Vec<S>, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(value) = self.pending.take() {
+                return Some((self.key.clone(), value));
+            }
+            match self.stack.last_mut()?.next() {
+                Some((symbol, child)) => {
+                    self.key.push(symbol.clone());
+                    self.pending = child.value.as_ref();
+                    self.stack.push(child.children.iter());
+                }
+                None => {
+                    self.stack.pop();
+                    self.key.pop();
+                }
+            }
+        }
+    }
+}
+
+// Read-only cursor on a node of the trie
+pub struct SubTrie<'a, S, V> {
+    node: &'a Node<S, V>,
+}
+
+impl<S, V> Clone for SubTrie<'_, S, V> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<S, V> Copy for SubTrie<'_, S, V> {}
+
+impl<'a, S: Ord, V> SubTrie<'a, S, V> {
+    // Value of the key that ends at this node
+    pub fn value(&self) -> Option<&'a V> {
+        self.node.value.as_ref()
+    }
+
+    pub fn child<Q>(&self, symbol: &Q) -> Option<SubTrie<'a, S, V>>
+    where
+        S: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.node.children.get(symbol).map(|node| SubTrie { node })
+    }
+
+    // Children in symbol order
+    pub fn children(&self) -> impl Iterator<Item = (&'a S, SubTrie<'a, S, V>)> + 'a {
+        self.node.children.iter().map(|(symbol, node)| (symbol, SubTrie { node }))
+    }
+}
+
+pub enum Entry<'a, S: Ord, V> {
+    Occupied(OccupiedEntry<'a, S, V>),
+    Vacant(VacantEntry<'a, S, V>),
+}
+
+// The entries hold on to the map and the key and walk down again when used;
+// a vacant entry must not create nodes until a value is actually inserted
+pub struct OccupiedEntry<'a, S: Ord, V> {
+    map: &'a mut TrieMap<S, V>,
+    key: Vec<S>,
+}
+
+pub struct VacantEntry<'a, S: Ord, V> {
+    map: &'a mut TrieMap<S, V>,
+    key: Vec<S>,
+}
+
+impl<'a, S: Ord, V> Entry<'a, S, V> {
+    pub fn key(&self) -> &[S] {
+        match self {
+            Entry::Occupied(entry) => &entry.key,
+            Entry::Vacant(entry) => &entry.key,
+        }
+    }
+
+    pub fn or_insert(self, default: V) -> &'a mut V {
+        self.or_insert_with(|| default)
+    }
+
+    pub fn or_insert_with(self, default: impl FnOnce() -> V) -> &'a mut V {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(default()),
+        }
+    }
+
+    pub fn or_default(self) -> &'a mut V
+    where
+        V: Default,
+    {
+        self.or_insert_with(V::default)
+    }
+
+    pub fn and_modify(mut self, f: impl FnOnce(&mut V)) -> Self {
+        if let Entry::Occupied(entry) = &mut self {
+            f(entry.get_mut());
+        }
+        self
+    }
+}
+
+impl<'a, S: Ord, V> OccupiedEntry<'a, S, V> {
+    pub fn key(&self) -> &[S] {
+        &self.key
+    }
+
+    pub fn get(&self) -> &V {
+        self.map.get(&self.key).unwrap()
+    }
+
+    pub fn get_mut(&mut self) -> &mut V {
+        self.map.get_mut(&self.key).unwrap()
+    }
+
+    pub fn into_mut(self) -> &'a mut V {
+        self.map.get_mut(&self.key).unwrap()
+    }
+
+    pub fn insert(&mut self, value: V) -> V {
+        std::mem::replace(self.get_mut(), value)
+    }
+
+    pub fn remove(self) -> V {
+        self.map.remove(&self.key).unwrap()
+    }
+}
+
+impl<'a, S: Ord, V> VacantEntry<'a, S, V> {
+    pub fn key(&self) -> &[S] {
+        &self.key
+    }
+
+    pub fn insert(self, value: V) -> &'a mut V {
+        let map = self.map;
+        map.len += 1;
+        let mut node = &mut map.root;
+        for symbol in self.key {
+            node = node.children.entry(symbol).or_insert_with(Node::new);
+        }
+        node.value.insert(value)
+    }
+}
+
+// Worked example: an HTTP router on path segments. A pattern segment is a
+// literal, `:name` (matches any one segment) or `*name` (matches the rest of
+// the path, at least one segment, and must come last). The derived order puts
+// literals before parameters before wildcards, and matching tries them in that
+// order, backtracking when a branch leads nowhere:
+//
+//     /users/me          beats /users/:id for "/users/me"
+//     /files/*path       catches "/files/a/b/c" with path = "a/b/c"
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Segment {
+    Literal(String),
+    Param(String),
+    Wildcard(String),
+}
+
+struct Router<H> {
+    routes: TrieMap<Segment, H>,
+}
+
+impl<H> Router<H> {
+    fn new() -> Self {
+        Router { routes: TrieMap::new() }
+    }
+
+    fn segments(path: &str) -> impl Iterator<Item = &str> {
+        path.split('/').filter(|segment| !segment.is_empty())
+    }
+
+    // Returns the handler the pattern had before, if any
+    fn add(&mut self, pattern: &str, handler: H) -> Option<H> {
+        let segments: Vec<Segment> = Self::segments(pattern)
+            .map(|segment| {
+                if let Some(name) = segment.strip_prefix(':') {
+                    Segment::Param(name.to_string())
+                } else if let Some(name) = segment.strip_prefix('*') {
+                    Segment::Wildcard(name.to_string())
+                } else {
+                    Segment::Literal(segment.to_string())
+                }
+            })
+            .collect();
+        let wildcard = segments.iter().position(|segment| matches!(segment, Segment::Wildcard(_)));
+        assert!(wildcard.is_none_or(|at| at + 1 == segments.len()), "a wildcard must be the last segment of {:?}", pattern);
+        self.routes.insert(segments, handler)
+    }
+
+    // The handler for a request path and the captured parameters, in pattern order
+    fn route(&self, path: &str) -> Option<(&H, Vec<(String, String)>)> {
+        let segments: Vec<&str> = Self::segments(path).collect();
+        let mut params = Vec::new();
+        let handler = Self::match_segments(self.routes.subtrie::<Segment>([])?, &segments, &mut params)?;
+        Some((handler, params))
+    }
+
+    fn match_segments<'a>(node: SubTrie<'a, Segment, H>, path: &[&str], params: &mut Vec<(String, String)>) -> Option<&'a H> {
+        let (first, rest) = match path.split_first() {
+            None => return node.value(),
+            Some(split) => split,
+        };
+        if let Some(child) = node.child(&Segment::Literal(first.to_string())) {
+            if let Some(handler) = Self::match_segments(child, rest, params) {
+                return Some(handler);
+            }
+        }
+        for (segment, child) in node.children() {
+            match segment {
+                Segment::Literal(_) => {}
+                Segment::Param(name) => {
+                    params.push((name.clone(), first.to_string()));
+                    if let Some(handler) = Self::match_segments(child, rest, params) {
+                        return Some(handler);
+                    }
+                    params.pop();
+                }
+                Segment::Wildcard(name) => {
+                    if let Some(handler) = child.value() {
+                        params.push((name.clone(), path.join("/")));
+                        return Some(handler);
+                    }
+                }
+            }
+        }
+        None
+    }
+}
+
+// Small xorshift generator for the randomized check below
+struct XorShift(u64);
+
+impl XorShift {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+}
+
+// Byte-string keys against a BTreeMap: inserts, removes, entries, longest
+// prefix and prefix iteration, with a check that removal prunes every empty node
+fn randomized_check(operations: usize, seed: u64) {
+    let mut rng = XorShift(seed);
+    let mut map: TrieMap<u8, u64> = TrieMap::new();
+    let mut model: BTreeMap<Vec<u8>, u64> = BTreeMap::new();
+    let random_key = |rng: &mut XorShift, max_len: u64| -> Vec<u8> {
+        let len = rng.next() % (max_len + 1);
+        (0..len).map(|_| b"abc"[(rng.next() % 3) as usize]).collect()
+    };
+    for _ in 0..operations {
+        let key = random_key(&mut rng, 5);
+        let value = rng.next() % 100;
+        match rng.next() % 5 {
+            0 | 1 => assert_eq!(map.insert(key.iter().copied(), value), model.insert(key.clone(), value)),
+            2 => assert_eq!(map.remove(&key), model.remove(&key)),
+            3 => {
+                let counter = map.entry(key.iter().copied()).and_modify(|v| *v += 1).or_insert(value);
+                let expected = model.entry(key.clone()).and_modify(|v| *v += 1).or_insert(value);
+                assert_eq!(counter, expected);
+            }
+            _ => {
+                if let Entry::Occupied(entry) = map.entry(key.iter().copied()) {
+                    assert_eq!(entry.key(), &key[..]);
+                    assert_eq!(entry.remove(), model.remove(&key).unwrap());
+                }
+            }
+        }
+        assert_eq!(map.len(), model.len());
+        assert_eq!(map.get(&key), model.get(&key));
+
+        let longest = (0..=key.len()).rev().find_map(|n| model.get(&key[..n]).map(|value| (n, value)));
+        assert_eq!(map.longest_prefix(&key), longest);
+
+        let prefix = random_key(&mut rng, 2);
+        let expected: Vec<(Vec<u8>, &u64)> = model.iter().filter(|(k, _)| k.starts_with(&prefix)).map(|(k, v)| (k.clone(), v)).collect();
+        assert_eq!(map.contains_prefix(&prefix), !expected.is_empty());
+        assert_eq!(map.iter_prefix(prefix.iter().copied()).collect::<Vec<_>>(), expected);
+    }
+    assert_eq!(map.iter().collect::<Vec<_>>(), model.iter().map(|(k, v)| (k.clone(), v)).collect::<Vec<_>>());
+
+    let keys: Vec<Vec<u8>> = model.keys().cloned().collect();
+    for key in keys {
+        map.remove(&key);
+    }
+    assert!(map.is_empty());
+    assert!(map.root.children.is_empty(), "removing every key should prune every node");
+}
+
+fn main() {
+    // Byte strings: identify a file type from its first bytes
+    let mut magic: TrieMap<u8, &str> = TrieMap::new();
+    magic.insert(b"\x89PNG\r\n\x1a\n".iter().copied(), "png");
+    magic.insert(b"GIF87a".iter().copied(), "gif");
+    magic.insert(b"GIF89a".iter().copied(), "gif");
+    magic.insert(b"\xff\xd8\xff".iter().copied(), "jpeg");
+    magic.insert(b"PK\x03\x04".iter().copied(), "zip");
+    magic.insert(b"%PDF-".iter().copied(), "pdf");
+    let header: &[u8] = b"%PDF-1.7\n%\xe2\xe3\xcf\xd3";
+    println!("File type of {:?}...: {:?}", String::from_utf8_lossy(&header[..8]), magic.longest_prefix(header));
+    println!("File type of \"hello\": {:?}", magic.longest_prefix(b"hello"));
+
+    // Token ids: count every n-gram up to length 3, then list what follows a token
+    let tokens: Vec<u32> = vec![7, 3, 9, 7, 3, 4, 7, 3, 9];
+    let mut ngrams: TrieMap<u32, usize> = TrieMap::new();
+    for start in 0..tokens.len() {
+        for end in start + 1..=(start + 3).min(tokens.len()) {
+            *ngrams.entry(tokens[start..end].iter().copied()).or_default() += 1;
+        }
+    }
+    println!("Count of [7, 3]: {:?}", ngrams.get(&[7, 3]));
+    println!("N-grams starting with 7: {:?}", ngrams.iter_prefix([7]).collect::<Vec<_>>());
+
+    // Path segments: a small HTTP router
+    let mut router = Router::new();
+    router.add("/", "home");
+    router.add("/users", "list users");
+    router.add("/users/me", "current user");
+    router.add("/users/:id", "show user");
+    router.add("/users/:id/posts/:post", "show post");
+    router.add("/files/*path", "serve file");
+    router.add("/:lang/docs", "localized docs");
+    for path in ["/", "/users/me", "/users/42", "/users/42/posts/7", "/files/css/site.css", "/en/docs", "/files", "/users/42/posts"] {
+        match router.route(path) {
+            Some((handler, params)) => println!("{:<22} -> {} {:?}", path, handler, params),
+            None => println!("{:<22} -> 404", path),
+        }
+    }
+    assert_eq!(router.route("/users/me").map(|(handler, _)| *handler), Some("current user"));
+    assert_eq!(router.route("/users/42/posts/7").unwrap().1, vec![("id".to_string(), "42".to_string()), ("post".to_string(), "7".to_string())]);
+    assert_eq!(router.route("/files/css/site.css").unwrap().1, vec![("path".to_string(), "css/site.css".to_string())]);
+    // "users" is a literal, so "/users/docs" goes to the user route, not the docs route
+    assert_eq!(router.route("/users/docs").map(|(handler, _)| *handler), Some("show user"));
+    assert!(router.route("/files").is_none());
+    assert!(router.route("/users/42/posts").is_none());
+
+    // String path segments can be looked up with &str
+    let mut tree: TrieMap<String, u32> = TrieMap::new();
+    tree.insert(["usr", "lib"].map(String::from), 1);
+    println!("Has /usr/lib: {}", tree.contains_key(["usr", "lib"]));
+
+    randomized_check(20_000, 0x2545F4914F6CDD1D);
+    println!("Randomized check passed: TrieMap matched a BTreeMap of byte strings");
+}
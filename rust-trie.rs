@@ -1,110 +1,683 @@
-use std::collections::HashMap;
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::ops::Index;
+use std::str::Chars;
 
-struct TrieNode {
-    children: HashMap<char, TrieNode>,
+// Edit distance rows for the fuzzy searches
+#[path = "rust-dynamic-programming.rs"]
+#[allow(dead_code)]
+mod dynamic_programming;
+
+use dynamic_programming::{edit_distance_first_row, edit_distance_next_row};
+
+// Default number of best completions cached at every node
+const DEFAULT_CACHE_SIZE: usize = 8;
+
+// Highest weight first, ties broken alphabetically
+fn by_rank<S: AsRef<str>>(a: &(u64, S), b: &(u64, S)) -> Ordering {
+    b.0.cmp(&a.0).then_with(|| a.1.as_ref().cmp(b.1.as_ref()))
+}
+
+// Text of every stored word, so nodes and caches can refer to a word by its
+// index. The slot of a deleted word is reused by the next insert. Indices are
+// u32 so a node's index packs next to its flag instead of growing the node.
+#[derive(Default)]
+struct Words {
+    text: Vec<String>,
+    free: Vec<u32>,
+}
+
+impl Words {
+    fn add(&mut self, word: &str) -> u32 {
+        match self.free.pop() {
+            Some(id) => {
+                self.text[id as usize] = word.to_string();
+                id
+            }
+            None => {
+                self.text.push(word.to_string());
+                u32::try_from(self.text.len() - 1).expect("more than u32::MAX words")
+            }
+        }
+    }
+
+    fn remove(&mut self, id: u32) {
+        self.text[id as usize] = String::new();
+        self.free.push(id);
+    }
+}
+
+impl Index<u32> for Words {
+    type Output = str;
+
+    fn index(&self, id: u32) -> &str {
+        &self.text[id as usize]
+    }
+}
+
+struct TrieNode<V> {
+    children: HashMap<char, TrieNode<V>>,
     is_end_of_word: bool,
+    // Index of this node's word in `Words`, while it ends a word
+    id: u32,
+    weight: u64,
+    payload: Option<V>,
+    // Best (weight, word index) pairs in this subtree, in rank order
+    best: Vec<(u64, u32)>,
 }
 
-impl TrieNode {
+impl<V> TrieNode<V> {
     fn new() -> Self {
         TrieNode {
             children: HashMap::new(),
             is_end_of_word: false,
+            id: 0,
+            weight: 0,
+            payload: None,
+            best: Vec::new(),
         }
     }
+
+    // Rebuild the completion cache from this node's own word and its children's caches.
+    // Every word in the subtree is either this node's or in some child's subtree, and a
+    // child's top entries are the only ones that can make this node's top list.
+    // Returns whether the cache changed.
+    fn refresh(&mut self, words: &Words, cache_size: usize) -> bool {
+        let mut best: Vec<(u64, u32)> = Vec::new();
+        if self.is_end_of_word {
+            best.push((self.weight, self.id));
+        }
+        for child in self.children.values() {
+            best.extend_from_slice(&child.best);
+        }
+        best.sort_by(|a, b| by_rank(&(a.0, &words[a.1]), &(b.0, &words[b.1])));
+        best.truncate(cache_size);
+        if best == self.best {
+            return false;
+        }
+        // Drop the slack left from gathering the children's entries
+        best.shrink_to_fit();
+        self.best = best;
+        true
+    }
+}
+
+// How the fuzzy searches count edits
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EditModel {
+    // Insertions, deletions and substitutions
+    Levenshtein,
+    // Also swaps of two adjacent characters, so "form" is one edit from "from"
+    Damerau,
+}
+
+// A fuzzy search in progress: the query, the edit budget and what has matched so far
+struct FuzzyWalk<'q> {
+    target: &'q [char],
+    max_edits: usize,
+    model: EditModel,
+    // Match words that start with something close to the query instead of whole words
+    prefix: bool,
+    // Distance rows for every character of the current path, root first
+    rows: Vec<Vec<usize>>,
+    path: String,
+    results: Vec<(String, usize, u64)>,
 }
 
-pub struct Trie {
-    root: TrieNode,
+// Entry of the best-first search in `top_k`: either a finished word or a whole
+// subtree, ranked by the best word it can still produce
+struct Candidate<'a, V> {
+    weight: u64,
+    word: &'a str,
+    subtree: Option<&'a TrieNode<V>>,
+}
+
+impl<V> Candidate<'_, V> {
+    fn rank(&self) -> (u64, &str) {
+        (self.weight, self.word)
+    }
+}
+
+impl<V> PartialEq for Candidate<'_, V> {
+    fn eq(&self, other: &Self) -> bool {
+        self.rank() == other.rank()
+    }
+}
+
+impl<V> Eq for Candidate<'_, V> {}
+
+impl<V> PartialOrd for Candidate<'_, V> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+// BinaryHeap pops the maximum: higher weight first, then the alphabetically smaller word
+impl<V> Ord for Candidate<'_, V> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.weight.cmp(&other.weight).then_with(|| other.word.cmp(self.word))
+    }
+}
+
+// A trie of words, each carrying a weight (such as a search frequency) and a payload
+pub struct Trie<V = ()> {
+    root: TrieNode<V>,
+    words: Words,
+    cache_size: usize,
+    len: usize,
+}
+
+impl<V> Default for Trie<V> {
+    fn default() -> Self {
+        Self::with_cache_size(DEFAULT_CACHE_SIZE)
+    }
 }
 
 impl Trie {
     pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<V> Trie<V> {
+    // `top_k` answers straight from the cache for k up to `cache_size`
+    pub fn with_cache_size(cache_size: usize) -> Self {
+        assert!(cache_size > 0, "the completion cache must hold at least one word");
         Trie {
             root: TrieNode::new(),
+            words: Words::default(),
+            cache_size,
+            len: 0,
         }
     }
 
-    pub fn insert(&mut self, word: &str) {
-        let mut current_node = &mut self.root;
-        for ch in word.chars() {
-            current_node = current_node.children.entry(ch).or_insert(TrieNode::new());
+    // Number of words stored
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    // Insert a word with weight 0, keeping the weight and payload of a word already present
+    pub fn insert(&mut self, word: &str)
+    where
+        V: Default,
+    {
+        let inserted = Self::update(&mut self.root, &mut self.words, word, 0, self.cache_size, |node| {
+            let inserted = !node.is_end_of_word;
+            if inserted {
+                node.is_end_of_word = true;
+                node.payload = Some(V::default());
+            }
+            inserted
+        })
+        .0;
+        if inserted {
+            self.len += 1;
         }
-        current_node.is_end_of_word = true;
     }
 
-    pub fn search(&self, word: &str) -> bool {
-        let mut current_node = &self.root;
-        for ch in word.chars() {
-            match current_node.children.get(&ch) {
-                Some(node) => current_node = node,
-                None => return false,
+    // Insert or overwrite a word with its weight and payload, returning the previous payload
+    pub fn insert_with(&mut self, word: &str, weight: u64, payload: V) -> Option<V> {
+        let previous = Self::update(&mut self.root, &mut self.words, word, 0, self.cache_size, |node| {
+            node.is_end_of_word = true;
+            node.weight = weight;
+            node.payload.replace(payload)
+        })
+        .0;
+        // Every word has a payload, so none before means the word is new
+        if previous.is_none() {
+            self.len += 1;
+        }
+        previous
+    }
+
+    // Add to a word's weight, inserting it first if needed; returns the new weight
+    pub fn increment(&mut self, word: &str, by: u64) -> u64
+    where
+        V: Default,
+    {
+        let (weight, inserted) = Self::update(&mut self.root, &mut self.words, word, 0, self.cache_size, |node| {
+            let inserted = !node.is_end_of_word;
+            if inserted {
+                node.is_end_of_word = true;
+                node.payload = Some(V::default());
             }
+            node.weight += by;
+            (node.weight, inserted)
+        })
+        .0;
+        if inserted {
+            self.len += 1;
         }
-        current_node.is_end_of_word
+        weight
     }
 
-    pub fn starts_with(&self, prefix: &str) -> bool {
+    // Walk down to the node for `word` (creating it if needed), apply `f` there,
+    // then refresh the completion caches on the way back up. Caches are built from
+    // the children's caches, so the refresh stops at the first one left unchanged.
+    // Returns the result of `f` and whether this node's cache changed.
+    fn update<R>(
+        node: &mut TrieNode<V>,
+        words: &mut Words,
+        word: &str,
+        offset: usize,
+        cache_size: usize,
+        f: impl FnOnce(&mut TrieNode<V>) -> R,
+    ) -> (R, bool) {
+        let (result, child_changed) = match word[offset..].chars().next() {
+            None => {
+                let was_word = node.is_end_of_word;
+                let result = f(node);
+                if node.is_end_of_word && !was_word {
+                    node.id = words.add(word);
+                }
+                (result, true)
+            }
+            Some(ch) => {
+                let child = node.children.entry(ch).or_insert_with(TrieNode::new);
+                Self::update(child, words, word, offset + ch.len_utf8(), cache_size, f)
+            }
+        };
+        let changed = child_changed && node.refresh(words, cache_size);
+        (result, changed)
+    }
+
+    fn find_node(&self, prefix: &str) -> Option<&TrieNode<V>> {
         let mut current_node = &self.root;
         for ch in prefix.chars() {
-            match current_node.children.get(&ch) {
-                Some(node) => current_node = node,
-                None => return false,
-            }
+            current_node = current_node.children.get(&ch)?;
         }
-        true
+        Some(current_node)
+    }
+
+    pub fn search(&self, word: &str) -> bool {
+        self.find_node(word).is_some_and(|node| node.is_end_of_word)
+    }
+
+    pub fn starts_with(&self, prefix: &str) -> bool {
+        self.find_node(prefix).is_some()
+    }
+
+    pub fn weight(&self, word: &str) -> Option<u64> {
+        self.find_node(word).filter(|node| node.is_end_of_word).map(|node| node.weight)
     }
 
+    pub fn get(&self, word: &str) -> Option<&V> {
+        self.find_node(word).and_then(|node| node.payload.as_ref())
+    }
+
+    // Estimated heap bytes held by the nodes, child maps, completion caches and
+    // word texts, not counting whatever the payloads themselves point to
+    pub fn heap_bytes(&self) -> usize {
+        let words = self.words.text.capacity() * std::mem::size_of::<String>()
+            + self.words.text.iter().map(String::capacity).sum::<usize>()
+            + self.words.free.capacity() * std::mem::size_of::<u32>();
+        words + Self::node_heap_bytes(&self.root)
+    }
+
+    fn node_heap_bytes(node: &TrieNode<V>) -> usize {
+        // hashbrown keeps buckets at 8/7 of the capacity (capacity + 1 below 8),
+        // each with one control byte, plus one 16-byte group of trailing control bytes
+        let capacity = node.children.capacity();
+        let buckets = if capacity == 0 { 0 } else if capacity < 8 { capacity + 1 } else { capacity / 7 * 8 };
+        let map = if buckets == 0 { 0 } else { buckets * (std::mem::size_of::<(char, TrieNode<V>)>() + 1) + 16 };
+        let cache = node.best.capacity() * std::mem::size_of::<(u64, u32)>();
+        map + cache + node.children.values().map(Self::node_heap_bytes).sum::<usize>()
+    }
+
+    // Every word starting with `prefix`, in alphabetical order
     pub fn find_words_with_prefix(&self, prefix: &str) -> Vec<String> {
         let mut result = Vec::new();
-        let mut current_node = &self.root;
-        
-        // Navigate to the node representing the prefix
-        for ch in prefix.chars() {
-            match current_node.children.get(&ch) {
-                Some(node) => current_node = node,
-                None => return result, // Prefix not found
-            }
+        if let Some(node) = self.find_node(prefix) {
+            // Perform DFS from the prefix node
+            Self::dfs(node, &mut String::from(prefix), &mut result);
         }
-
-        // Perform DFS from the prefix node
-        self.dfs(current_node, &mut String::from(prefix), &mut result);
         result
     }
 
-    fn dfs(&self, node: &TrieNode, current_word: &mut String, result: &mut Vec<String>) {
+    fn dfs(node: &TrieNode<V>, current_word: &mut String, result: &mut Vec<String>) {
         if node.is_end_of_word {
             result.push(current_word.clone());
         }
 
-        for (&ch, child) in &node.children {
+        let mut children: Vec<_> = node.children.iter().collect();
+        children.sort_by_key(|&(&ch, _)| ch);
+        for (&ch, child) in children {
             current_word.push(ch);
-            self.dfs(child, current_word, result);
+            Self::dfs(child, current_word, result);
             current_word.pop();
         }
     }
 
-    pub fn delete(&mut self, word: &str) -> bool {
-        self.delete_recursive(&mut self.root, word, 0)
-    }
+    // The k heaviest words starting with `prefix`, heaviest first and ties in
+    // alphabetical order. Up to `cache_size` this is a copy of the prefix node's
+    // cache; beyond it, a best-first search opens only the subtrees whose best
+    // cached word still ranks above the k-th result.
+    pub fn top_k(&self, prefix: &str, k: usize) -> Vec<(String, u64)> {
+        let node = match self.find_node(prefix) {
+            None => return Vec::new(),
+            Some(node) => node,
+        };
+        if k <= self.cache_size {
+            return node.best.iter().take(k).map(|&(weight, id)| (self.words[id].to_string(), weight)).collect();
+        }
 
-    fn delete_recursive(&mut self, node: &mut TrieNode, word: &str, depth: usize) -> bool {
-        if depth == word.len() {
-            if !node.is_end_of_word {
-                return false;
+        let mut result = Vec::with_capacity(k);
+        let mut heap = BinaryHeap::new();
+        self.push_subtree(&mut heap, node);
+        while result.len() < k {
+            let candidate = match heap.pop() {
+                None => break,
+                Some(candidate) => candidate,
+            };
+            let node = match candidate.subtree {
+                None => {
+                    result.push((candidate.word.to_string(), candidate.weight));
+                    continue;
+                }
+                Some(node) => node,
+            };
+            if node.is_end_of_word {
+                heap.push(Candidate { weight: node.weight, word: &self.words[node.id], subtree: None });
+            }
+            for child in node.children.values() {
+                self.push_subtree(&mut heap, child);
             }
-            node.is_end_of_word = false;
-            return node.children.is_empty();
         }
+        result
+    }
+
+    fn push_subtree<'a>(&'a self, heap: &mut BinaryHeap<Candidate<'a, V>>, node: &'a TrieNode<V>) {
+        // A subtree with no words has an empty cache and nothing to offer
+        if let Some(&(weight, id)) = node.best.first() {
+            heap.push(Candidate { weight, word: &self.words[id], subtree: Some(node) });
+        }
+    }
+
+    // Every word within `max_edits` Levenshtein edits of `word`, with its distance,
+    // closest first and ties in alphabetical order
+    pub fn search_within(&self, word: &str, max_edits: usize) -> Vec<(String, usize)> {
+        self.search_within_by(word, max_edits, EditModel::Levenshtein)
+    }
+
+    // Like `search_within`, counting edits by `model`. Each trie edge extends the
+    // distance row of its parent by one character, so words sharing a prefix share
+    // the work, and a subtree is skipped once no cell of the row is within budget.
+    pub fn search_within_by(&self, word: &str, max_edits: usize, model: EditModel) -> Vec<(String, usize)> {
+        let mut results = self.fuzzy(word, max_edits, model, false);
+        results.sort_by(|a, b| a.1.cmp(&b.1).then_with(|| a.0.cmp(&b.0)));
+        results.into_iter().map(|(word, distance, _)| (word, distance)).collect()
+    }
+
+    // Autocomplete that tolerates typos: the k best words starting with something
+    // within `max_edits` of `prefix`, closest first, then heaviest, then alphabetical.
+    // A word's distance is the smallest over its prefixes, so "rsut" reaches "rustup"
+    // through "rust" at distance 1 (Damerau) or 2 (Levenshtein).
+    pub fn fuzzy_top_k(&self, prefix: &str, max_edits: usize, model: EditModel, k: usize) -> Vec<(String, usize)> {
+        let mut results = self.fuzzy(prefix, max_edits, model, true);
+        results.sort_by(|a, b| a.1.cmp(&b.1).then_with(|| b.2.cmp(&a.2)).then_with(|| a.0.cmp(&b.0)));
+        results.into_iter().take(k).map(|(word, distance, _)| (word, distance)).collect()
+    }
+
+    fn fuzzy(&self, query: &str, max_edits: usize, model: EditModel, prefix: bool) -> Vec<(String, usize, u64)> {
+        let target: Vec<char> = query.chars().collect();
+        let mut walk = FuzzyWalk {
+            target: &target,
+            max_edits,
+            model,
+            prefix,
+            rows: vec![edit_distance_first_row(target.len())],
+            path: String::new(),
+            results: Vec::new(),
+        };
+        Self::fuzzy_walk(&self.root, &mut walk, usize::MAX);
+        walk.results
+    }
+
+    // `closest_prefix` is the smallest distance from the query to a proper prefix of the path
+    fn fuzzy_walk(node: &TrieNode<V>, walk: &mut FuzzyWalk, closest_prefix: usize) {
+        let row = walk.rows.last().unwrap();
+        let distance = if walk.prefix { closest_prefix.min(row[walk.target.len()]) } else { row[walk.target.len()] };
+        if node.is_end_of_word && distance <= walk.max_edits {
+            walk.results.push((walk.path.clone(), distance, node.weight));
+        }
+        // Row cells never drop by more than one per extra character of the path, and
+        // a transposition costs one more than the cell diagonal to it, so once every
+        // cell is over budget no descendant can come back within it. In prefix mode
+        // a matched prefix keeps the whole subtree.
+        let matched = walk.prefix && distance <= walk.max_edits;
+        if !matched && row.iter().min().is_some_and(|&least| least > walk.max_edits) {
+            return;
+        }
+
+        for (&ch, child) in &node.children {
+            let depth = walk.rows.len();
+            let transposition = match (walk.model, walk.path.chars().next_back()) {
+                (EditModel::Damerau, Some(previous)) => Some((walk.rows[depth - 2].as_slice(), previous)),
+                _ => None,
+            };
+            let next = edit_distance_next_row(&walk.rows[depth - 1], ch, walk.target, transposition);
+            walk.rows.push(next);
+            walk.path.push(ch);
+            Self::fuzzy_walk(child, walk, distance);
+            walk.path.pop();
+            walk.rows.pop();
+        }
+    }
+
+    // Remove a word, returning its payload, or None if it was not there. Nodes
+    // left with neither a word nor children are pruned on the way back up.
+    pub fn delete(&mut self, word: &str) -> Option<V> {
+        let removed = Self::delete_recursive(&mut self.root, &mut self.words, word.chars(), self.cache_size);
+        if removed.is_some() {
+            self.len -= 1;
+        }
+        removed
+    }
 
-        let current_char = word.chars().nth(depth).unwrap();
-        if let Some(child) = node.children.get_mut(&current_char) {
-            if self.delete_recursive(child, word, depth + 1) {
-                node.children.remove(&current_char);
-                return node.children.is_empty() && !node.is_end_of_word;
+    // `rest` holds the characters of the word below this node
+    fn delete_recursive(node: &mut TrieNode<V>, words: &mut Words, mut rest: Chars, cache_size: usize) -> Option<V> {
+        let removed = match rest.next() {
+            None => {
+                if !node.is_end_of_word {
+                    return None;
+                }
+                node.is_end_of_word = false;
+                node.weight = 0;
+                words.remove(node.id);
+                node.payload.take()
+            }
+            Some(ch) => {
+                let child = node.children.get_mut(&ch)?;
+                let removed = Self::delete_recursive(child, words, rest, cache_size)?;
+                if !child.is_end_of_word && child.children.is_empty() {
+                    node.children.remove(&ch);
+                }
+                Some(removed)
+            }
+        };
+        node.refresh(words, cache_size);
+        removed
+    }
+}
+
+// Small xorshift generator for the randomized check below
+struct XorShift(u64);
+
+impl XorShift {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+}
+
+// Compare top_k against sorting every matching word, for k below and above the cache size
+fn randomized_check(operations: usize, seed: u64) {
+    let mut rng = XorShift(seed);
+    let mut trie: Trie<u64> = Trie::with_cache_size(4);
+    let mut model: HashMap<String, (u64, u64)> = HashMap::new();
+    let random_word = |rng: &mut XorShift, max_len: u64| -> String {
+        let len = 1 + rng.next() % max_len;
+        (0..len).map(|_| (b'a' + (rng.next() % 3) as u8) as char).collect()
+    };
+    for _ in 0..operations {
+        let word = random_word(&mut rng, 6);
+        match rng.next() % 4 {
+            0 | 1 => {
+                let weight = rng.next() % 50;
+                let payload = rng.next();
+                let previous = trie.insert_with(&word, weight, payload);
+                assert_eq!(previous, model.insert(word, (weight, payload)).map(|(_, payload)| payload));
+            }
+            2 => {
+                let by = rng.next() % 10;
+                let new_weight = trie.increment(&word, by);
+                let expected = model.entry(word).or_insert((0, 0));
+                expected.0 += by;
+                assert_eq!(new_weight, expected.0);
+            }
+            _ => {
+                assert_eq!(trie.delete(&word), model.remove(&word).map(|(_, payload)| payload));
             }
         }
-        false
+        assert_eq!(trie.len(), model.len());
+
+        let prefix = random_word(&mut rng, 3);
+        let k = (rng.next() % 12) as usize;
+        let mut expected: Vec<(u64, String)> =
+            model.iter().filter(|(word, _)| word.starts_with(&prefix)).map(|(word, &(weight, _))| (weight, word.clone())).collect();
+        expected.sort_by(by_rank);
+        let expected: Vec<(String, u64)> = expected.into_iter().take(k).map(|(weight, word)| (word, weight)).collect();
+        assert_eq!(trie.top_k(&prefix, k), expected, "top_k({:?}, {})", prefix, k);
+    }
+    for (word, (weight, payload)) in &model {
+        assert_eq!(trie.weight(word), Some(*weight));
+        assert_eq!(trie.get(word), Some(payload));
+    }
+}
+
+// Random words over one-, two-, three- and four-byte characters (including a
+// combining accent) against a HashSet, checking that deletes prune every node
+// they leave empty
+fn unicode_check(operations: usize, seed: u64) {
+    let mut rng = XorShift(seed);
+    let alphabet = ['a', 'b', 'é', 'ß', 'ж', '\u{301}', '中', '語', '😀'];
+    let random_word = |rng: &mut XorShift| -> String {
+        let len = rng.next() % 5;
+        (0..len).map(|_| alphabet[(rng.next() % alphabet.len() as u64) as usize]).collect()
+    };
+    let mut trie = Trie::new();
+    let mut model: HashSet<String> = HashSet::new();
+    for _ in 0..operations {
+        let word = random_word(&mut rng);
+        if rng.next().is_multiple_of(2) {
+            trie.insert(&word);
+            model.insert(word.clone());
+        } else {
+            assert_eq!(trie.delete(&word).is_some(), model.remove(&word), "delete {:?}", word);
+            assert!(!trie.search(&word));
+        }
+        assert_eq!(trie.len(), model.len());
+        assert_eq!(trie.search(&word), model.contains(&word));
+
+        let prefix: String = word.chars().take((rng.next() % 3) as usize).collect();
+        let mut expected: Vec<String> = model.iter().filter(|w| w.starts_with(&prefix)).cloned().collect();
+        expected.sort();
+        assert_eq!(trie.starts_with(&prefix), !expected.is_empty() || prefix.is_empty());
+        assert_eq!(trie.find_words_with_prefix(&prefix), expected);
+    }
+
+    let words: Vec<String> = model.drain().collect();
+    for word in &words {
+        assert_eq!(trie.delete(word), Some(()));
+        assert_eq!(trie.delete(word), None);
+    }
+    assert!(trie.is_empty());
+    assert!(trie.root.children.is_empty(), "deleting every word should prune every node");
+    assert!(trie.root.best.is_empty());
+    assert_eq!(trie.words.free.len(), trie.words.text.len(), "deleting every word should free every slot");
+}
+
+// Refreshing stops at the first ancestor whose cache comes out unchanged. Check
+// every cache along one word against sorting its subtree, after an update that
+// stays below the top of the trie and after one that reaches the root, and that
+// refreshing the root by hand finds nothing stale.
+fn refresh_stop_check() {
+    let mut trie: Trie<()> = Trie::with_cache_size(2);
+    for (word, weight) in [("car", 90), ("cart", 80), ("carton", 5), ("cartons", 3), ("cat", 70)] {
+        trie.insert_with(word, weight, ());
+    }
+    let check = |trie: &Trie<()>| {
+        for end in 0..="cartons".len() {
+            let prefix = &"cartons"[..end];
+            let mut expected: Vec<(u64, String)> =
+                trie.find_words_with_prefix(prefix).into_iter().map(|word| (trie.weight(&word).unwrap(), word)).collect();
+            expected.sort_by(by_rank);
+            let expected: Vec<(String, u64)> = expected.into_iter().take(2).map(|(weight, word)| (word, weight)).collect();
+            assert_eq!(trie.top_k(prefix, 2), expected, "cache of {:?}", prefix);
+        }
+    };
+    // "cartons" stays below "car" and "cart", so the refresh stops under "carto"
+    trie.increment("cartons", 1);
+    check(&trie);
+    assert!(!trie.root.refresh(&trie.words, 2), "the root cache went stale");
+    // Now it overtakes every word and the refresh has to reach the root
+    trie.increment("cartons", 200);
+    check(&trie);
+    assert_eq!(trie.top_k("", 1), vec![("cartons".to_string(), 204)]);
+    assert!(!trie.root.refresh(&trie.words, 2), "the root cache went stale");
+}
+
+// Compare the fuzzy searches against measuring the distance to every word
+fn fuzzy_check(rounds: usize, seed: u64) {
+    use dynamic_programming::{damerau_distance, edit_distance};
+
+    let mut rng = XorShift(seed);
+    let alphabet = ['a', 'b', 'c', 'é'];
+    let random_word = |rng: &mut XorShift, max_len: u64| -> String {
+        let len = rng.next() % (max_len + 1);
+        (0..len).map(|_| alphabet[(rng.next() % alphabet.len() as u64) as usize]).collect()
+    };
+    let mut trie: Trie = Trie::with_cache_size(2);
+    let mut words: HashMap<String, u64> = HashMap::new();
+    for _ in 0..300 {
+        let word = random_word(&mut rng, 6);
+        let weight = rng.next() % 20;
+        trie.insert_with(&word, weight, ());
+        words.insert(word, weight);
+    }
+    for _ in 0..rounds {
+        let query = random_word(&mut rng, 5);
+        let max_edits = (rng.next() % 3) as usize;
+        let k = (rng.next() % 10) as usize;
+        for model in [EditModel::Levenshtein, EditModel::Damerau] {
+            let distance = |a: &str, b: &str| match model {
+                EditModel::Levenshtein => edit_distance(a, b),
+                EditModel::Damerau => damerau_distance(a, b),
+            };
+
+            let mut expected: Vec<(String, usize)> =
+                words.keys().map(|word| (word.clone(), distance(word, &query))).filter(|&(_, d)| d <= max_edits).collect();
+            expected.sort_by(|a, b| a.1.cmp(&b.1).then_with(|| a.0.cmp(&b.0)));
+            assert_eq!(trie.search_within_by(&query, max_edits, model), expected, "{:?} within {} of {:?}", model, max_edits, query);
+
+            let mut expected: Vec<(usize, u64, String)> = words
+                .iter()
+                .filter_map(|(word, &weight)| {
+                    let chars: Vec<char> = word.chars().collect();
+                    let closest = (0..=chars.len()).map(|i| distance(&chars[..i].iter().collect::<String>(), &query)).min().unwrap();
+                    (closest <= max_edits).then(|| (closest, weight, word.clone()))
+                })
+                .collect();
+            expected.sort_by(|a, b| a.0.cmp(&b.0).then_with(|| b.1.cmp(&a.1)).then_with(|| a.2.cmp(&b.2)));
+            let expected: Vec<(String, usize)> = expected.into_iter().take(k).map(|(d, _, word)| (word, d)).collect();
+            assert_eq!(trie.fuzzy_top_k(&query, max_edits, model, k), expected, "{:?} fuzzy prefix {:?}", model, query);
+        }
     }
 }
 
@@ -134,7 +707,40 @@ fn main() {
     println!("Words with prefix 'ba': {:?}", trie.find_words_with_prefix("ba"));
 
     // Delete a word
-    println!("Delete 'apple': {}", trie.delete("apple"));
+    println!("Delete 'apple': {}", trie.delete("apple").is_some()); // true
+    println!("Delete 'appl': {}", trie.delete("appl").is_some()); // false
     println!("Search 'apple' after deletion: {}", trie.search("apple")); // false
     println!("Search 'app' after deletion: {}", trie.search("app")); // true
+
+    // Ranked autocomplete: weights from query counts, payloads for the result page
+    let mut search_box: Trie<&str> = Trie::default();
+    for (query, count, page) in [
+        ("rust", 950, "/rust"),
+        ("rust book", 420, "/rust/book"),
+        ("rust by example", 260, "/rust/example"),
+        ("rustup", 610, "/rustup"),
+        ("rustfmt", 180, "/rustfmt"),
+        ("ruby", 700, "/ruby"),
+    ] {
+        search_box.insert_with(query, count, page);
+    }
+    search_box.increment("rust book", 300);
+    println!("Top 3 for 'rus': {:?}", search_box.top_k("rus", 3));
+    println!("Top 10 for 'ru': {:?}", search_box.top_k("ru", 10));
+    println!("Page for 'rustup': {:?}", search_box.get("rustup"));
+
+    // Typo tolerance
+    println!("Within 1 edit of 'bath': {:?}", trie.search_within("bath", 1));
+    println!("Within 2 edits of 'appel': {:?}", trie.search_within("appel", 2));
+    println!("Within 1 edit of 'abt' (Damerau): {:?}", trie.search_within_by("abt", 1, EditModel::Damerau));
+    println!("Fuzzy top 3 for 'rsut': {:?}", search_box.fuzzy_top_k("rsut", 1, EditModel::Damerau, 3));
+
+    randomized_check(5_000, 0x2545F4914F6CDD1D);
+    println!("Randomized check passed: top_k matched sorting every completion");
+    refresh_stop_check();
+    println!("Refresh check passed: caches stayed exact when refreshing stopped early");
+    unicode_check(5_000, 0xD1B54A32D192ED03);
+    println!("Unicode check passed: insert, delete and prefix search matched a HashSet");
+    fuzzy_check(500, 0x9E3779B97F4A7C15);
+    println!("Fuzzy check passed: searches matched the distance to every word");
 }